@@ -0,0 +1,163 @@
+use std::{
+    future::ready,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use futures::{Stream, StreamExt};
+use rspc::internal::jsonrpc::{Request, RequestId, RequestInner, Response, ResponseInner};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::{Error, Transport};
+
+/// a query or mutation which can be executed as part of a batch using [`Client::batch`].
+#[derive(Debug, Clone)]
+pub enum Operation {
+    Query { key: String, input: Option<Value> },
+    Mutation { key: String, input: Option<Value> },
+}
+
+impl Operation {
+    pub fn query(key: impl Into<String>, input: impl Serialize) -> Result<Self, Error> {
+        Ok(Self::Query {
+            key: key.into(),
+            input: serialize_input(input)?,
+        })
+    }
+
+    pub fn mutation(key: impl Into<String>, input: impl Serialize) -> Result<Self, Error> {
+        Ok(Self::Mutation {
+            key: key.into(),
+            input: serialize_input(input)?,
+        })
+    }
+}
+
+impl From<Operation> for RequestInner {
+    fn from(op: Operation) -> Self {
+        match op {
+            Operation::Query { key, input } => RequestInner::Query { path: key, input },
+            Operation::Mutation { key, input } => RequestInner::Mutation { path: key, input },
+        }
+    }
+}
+
+/// a client for calling the procedures of an rspc router through a [`Transport`].
+pub struct Client<T: Transport> {
+    transport: T,
+    next_id: AtomicU32,
+}
+
+impl<T: Transport> Client<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            next_id: AtomicU32::new(0),
+        }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    pub async fn query<TArg, TResult>(&self, key: &str, input: TArg) -> Result<TResult, Error>
+    where
+        TArg: Serialize,
+        TResult: DeserializeOwned,
+    {
+        self.exec(Operation::query(key, input)?).await
+    }
+
+    pub async fn mutation<TArg, TResult>(&self, key: &str, input: TArg) -> Result<TResult, Error>
+    where
+        TArg: Serialize,
+        TResult: DeserializeOwned,
+    {
+        self.exec(Operation::mutation(key, input)?).await
+    }
+
+    /// executes multiple operations in a single round trip. The results are returned in the same order as the operations.
+    pub async fn batch(
+        &self,
+        operations: impl IntoIterator<Item = Operation>,
+    ) -> Result<Vec<Result<Value, Error>>, Error> {
+        let requests = operations
+            .into_iter()
+            .map(|op| self.request(op.into()))
+            .collect::<Vec<_>>();
+        let len = requests.len();
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+
+        let responses = self.transport.execute(requests).await?;
+        if responses.len() != len {
+            return Err(Error::UnexpectedResponse);
+        }
+
+        Ok(responses.into_iter().map(response_result).collect())
+    }
+
+    /// starts a subscription. The subscription is stopped when the returned stream is dropped and the stream ends when the server completes the subscription.
+    pub async fn subscription<TArg, TResult>(
+        &self,
+        key: &str,
+        input: TArg,
+    ) -> Result<impl Stream<Item = Result<TResult, Error>> + Send + 'static, Error>
+    where
+        TArg: Serialize,
+        TResult: DeserializeOwned + 'static,
+    {
+        let stream = self
+            .transport
+            .subscribe(self.request(RequestInner::Subscription {
+                path: key.into(),
+                input: serialize_input(input)?,
+            }))
+            .await?;
+
+        Ok(stream
+            .take_while(|resp| ready(!matches!(resp.result, ResponseInner::Complete)))
+            .map(|resp| match resp.result {
+                ResponseInner::Event(v) => {
+                    serde_json::from_value(v).map_err(Error::DeserializingResult)
+                }
+                ResponseInner::Error(err) => Err(Error::Procedure(err)),
+                _ => Err(Error::UnexpectedResponse),
+            }))
+    }
+
+    async fn exec<TResult: DeserializeOwned>(&self, op: Operation) -> Result<TResult, Error> {
+        let resp = self
+            .transport
+            .execute(vec![self.request(op.into())])
+            .await?
+            .pop()
+            .ok_or(Error::UnexpectedResponse)?;
+
+        serde_json::from_value(response_result(resp)?).map_err(Error::DeserializingResult)
+    }
+
+    fn request(&self, inner: RequestInner) -> Request {
+        Request {
+            jsonrpc: Some("2.0".into()),
+            id: RequestId::Number(self.next_id.fetch_add(1, Ordering::Relaxed)),
+            inner,
+        }
+    }
+}
+
+fn serialize_input(input: impl Serialize) -> Result<Option<Value>, Error> {
+    match serde_json::to_value(input).map_err(Error::SerializingInput)? {
+        Value::Null => Ok(None),
+        v => Ok(Some(v)),
+    }
+}
+
+fn response_result(resp: Response) -> Result<Value, Error> {
+    match resp.result {
+        ResponseInner::Response(v) => Ok(v),
+        ResponseInner::Error(err) => Err(Error::Procedure(err)),
+        _ => Err(Error::UnexpectedResponse),
+    }
+}
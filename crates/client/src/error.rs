@@ -0,0 +1,36 @@
+use rspc::internal::jsonrpc::JsonRPCError;
+
+/// an error returned by the rspc client.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("error returned by procedure: {}", .0.message)]
+    Procedure(JsonRPCError),
+    #[error("error serializing procedure input: {0}")]
+    SerializingInput(serde_json::Error),
+    #[error("error deserializing procedure result: {0}")]
+    DeserializingResult(serde_json::Error),
+    #[error("error deserializing response: {0}")]
+    DeserializingResponse(serde_json::Error),
+    #[cfg(feature = "http")]
+    #[error("http error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[cfg(feature = "http")]
+    #[error("server responded with status code {0}")]
+    HttpStatus(u16),
+    #[cfg(feature = "ws")]
+    #[error("websocket error: {0}")]
+    Websocket(Box<tokio_tungstenite::tungstenite::Error>),
+    #[error("the connection to the server was closed")]
+    ConnectionClosed,
+    #[error("subscriptions are not supported by this transport")]
+    SubscriptionsUnsupported,
+    #[error("the server sent an unexpected response")]
+    UnexpectedResponse,
+}
+
+#[cfg(feature = "ws")]
+impl From<tokio_tungstenite::tungstenite::Error> for Error {
+    fn from(err: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::Websocket(Box::new(err))
+    }
+}
@@ -0,0 +1,91 @@
+use futures::{future::BoxFuture, stream::BoxStream};
+use reqwest::header::CONTENT_TYPE;
+use rspc::internal::jsonrpc::{Request, RequestInner, Response};
+use serde::de::DeserializeOwned;
+
+use crate::{Error, Transport};
+
+/// a transport which sends requests to a router over HTTP. Multiple requests are sent to the batch endpoint of the router in a single request.
+///
+/// Subscriptions are not supported over HTTP, use the [`WebsocketTransport`](crate::WebsocketTransport) for them.
+pub struct HttpTransport {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl HttpTransport {
+    /// creates a transport for the router mounted at `url`, eg. `http://localhost:4000/rspc`.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::with_client(reqwest::Client::new(), url)
+    }
+
+    pub fn with_client(client: reqwest::Client, url: impl Into<String>) -> Self {
+        Self {
+            client,
+            url: url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(&self, req: reqwest::RequestBuilder) -> Result<T, Error> {
+        let resp = req.send().await?;
+        let status = resp.status();
+        // Rate limited procedures respond with a `429` status along with the error so the body is still read
+        match serde_json::from_slice(&resp.bytes().await?) {
+            Ok(v) => Ok(v),
+            Err(_) if !status.is_success() => Err(Error::HttpStatus(status.as_u16())),
+            Err(err) => Err(Error::DeserializingResponse(err)),
+        }
+    }
+}
+
+impl Transport for HttpTransport {
+    fn execute(&self, mut requests: Vec<Request>) -> BoxFuture<'_, Result<Vec<Response>, Error>> {
+        Box::pin(async move {
+            if requests.len() != 1 {
+                let body = serde_json::to_vec(&requests).map_err(Error::SerializingInput)?;
+                return self
+                    .send(
+                        self.client
+                            .post(format!("{}/_batch", self.url))
+                            .header(CONTENT_TYPE, "application/json")
+                            .body(body),
+                    )
+                    .await;
+            }
+
+            let req = requests.remove(0);
+            let builder = match req.inner {
+                RequestInner::Query { path, input } => {
+                    let builder = self.client.get(format!("{}/{}", self.url, path));
+                    match input {
+                        Some(input) => builder.query(&[(
+                            "input",
+                            serde_json::to_string(&input).map_err(Error::SerializingInput)?,
+                        )]),
+                        None => builder,
+                    }
+                }
+                RequestInner::Mutation { path, input } => self
+                    .client
+                    .post(format!("{}/{}", self.url, path))
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(match input {
+                        Some(input) => {
+                            serde_json::to_vec(&input).map_err(Error::SerializingInput)?
+                        }
+                        None => Vec::new(),
+                    }),
+                _ => return Err(Error::SubscriptionsUnsupported),
+            };
+
+            // The single request endpoints don't know the id of the request so we restore it.
+            let mut resp: Response = self.send(builder).await?;
+            resp.id = req.id;
+            Ok(vec![resp])
+        })
+    }
+
+    fn subscribe(&self, _: Request) -> BoxFuture<'_, Result<BoxStream<'static, Response>, Error>> {
+        Box::pin(async move { Err(Error::SubscriptionsUnsupported) })
+    }
+}
@@ -0,0 +1,34 @@
+//! rspc-client: a Rust client for calling rspc routers.
+//!
+//! The client speaks the same JSON-RPC protocol as the rspc frontend packages and can talk to a router over HTTP, over a websocket or directly in-process.
+#![forbid(unsafe_code)]
+#![warn(
+    clippy::all,
+    clippy::cargo,
+    clippy::unwrap_used,
+    clippy::panic,
+    clippy::todo,
+    clippy::panic_in_result_fn,
+    // missing_docs
+)]
+
+mod client;
+mod error;
+#[cfg(feature = "http")]
+mod http;
+mod router;
+mod transport;
+#[cfg(feature = "ws")]
+mod ws;
+
+pub use client::*;
+pub use error::*;
+#[cfg(feature = "http")]
+pub use http::*;
+pub use router::*;
+pub use transport::*;
+#[cfg(feature = "ws")]
+pub use ws::*;
+
+// Re-exported so generated bindings can name the type of a subscription.
+pub use futures::Stream;
@@ -0,0 +1,102 @@
+use std::{collections::HashMap, sync::Arc};
+
+use futures::{
+    future::{join_all, BoxFuture},
+    stream::BoxStream,
+    StreamExt,
+};
+use rspc::{
+    internal::jsonrpc::{handle_json_rpc, Request, RequestId, Response, Sender, SubscriptionMap},
+    Router,
+};
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+use crate::{transport::SubscriptionStream, Error, Transport};
+
+/// a transport which executes requests directly against an in-process router. This is useful for testing a router without starting a webserver.
+pub struct RouterTransport<TCtx, TMeta = ()>
+where
+    TCtx: 'static,
+{
+    router: Arc<Router<TCtx, TMeta>>,
+    ctx_fn: Arc<dyn Fn() -> TCtx + Send + Sync>,
+    subscriptions: Arc<Mutex<HashMap<RequestId, oneshot::Sender<()>>>>,
+}
+
+impl<TCtx, TMeta> RouterTransport<TCtx, TMeta>
+where
+    TCtx: 'static,
+{
+    pub fn new(
+        router: Arc<Router<TCtx, TMeta>>,
+        ctx_fn: impl Fn() -> TCtx + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            router,
+            ctx_fn: Arc::new(ctx_fn),
+            subscriptions: Default::default(),
+        }
+    }
+}
+
+impl<TCtx, TMeta> Transport for RouterTransport<TCtx, TMeta>
+where
+    TCtx: Send + 'static,
+    TMeta: Send + Sync + 'static,
+{
+    fn execute(&self, requests: Vec<Request>) -> BoxFuture<'_, Result<Vec<Response>, Error>> {
+        Box::pin(async move {
+            join_all(requests.into_iter().map(|req| async move {
+                let mut sender = Sender::Response(None);
+                handle_json_rpc(
+                    (self.ctx_fn)(),
+                    req,
+                    &self.router,
+                    &mut sender,
+                    &mut SubscriptionMap::None,
+                )
+                .await;
+
+                match sender {
+                    Sender::Response(Some(resp)) => Ok(resp),
+                    _ => Err(Error::UnexpectedResponse),
+                }
+            }))
+            .await
+            .into_iter()
+            .collect()
+        })
+    }
+
+    fn subscribe(
+        &self,
+        request: Request,
+    ) -> BoxFuture<'_, Result<BoxStream<'static, Response>, Error>> {
+        Box::pin(async move {
+            let id = request.id.clone();
+            let (mut tx, rx) = mpsc::unbounded_channel();
+            handle_json_rpc(
+                (self.ctx_fn)(),
+                request,
+                &self.router,
+                &mut Sender::ResponseChannel(&mut tx),
+                &mut SubscriptionMap::Mutex(&self.subscriptions),
+            )
+            .await;
+
+            let subscriptions = self.subscriptions.clone();
+            Ok(SubscriptionStream {
+                rx,
+                on_drop: Some(Box::new(move || {
+                    // Removing the subscription drops it's shutdown sender which will stop it.
+                    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                        handle.spawn(async move {
+                            subscriptions.lock().await.remove(&id);
+                        });
+                    }
+                })),
+            }
+            .boxed())
+        })
+    }
+}
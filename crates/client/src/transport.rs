@@ -0,0 +1,44 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{future::BoxFuture, stream::BoxStream, Stream};
+use rspc::internal::jsonrpc::{Request, Response};
+use tokio::sync::mpsc;
+
+use crate::Error;
+
+/// a transport is responsible for delivering JSON-RPC requests to a router and returning its responses.
+pub trait Transport: Send + Sync + 'static {
+    /// executes one or more queries or mutations. The responses must be returned in the same order as the requests.
+    fn execute(&self, requests: Vec<Request>) -> BoxFuture<'_, Result<Vec<Response>, Error>>;
+
+    /// starts a subscription. The subscription is stopped when the returned stream is dropped.
+    fn subscribe(
+        &self,
+        request: Request,
+    ) -> BoxFuture<'_, Result<BoxStream<'static, Response>, Error>>;
+}
+
+/// the responses for a single subscription. The `on_drop` callback is run when the consumer stops listening.
+pub(crate) struct SubscriptionStream {
+    pub(crate) rx: mpsc::UnboundedReceiver<Response>,
+    pub(crate) on_drop: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl Stream for SubscriptionStream {
+    type Item = Response;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl Drop for SubscriptionStream {
+    fn drop(&mut self) {
+        if let Some(on_drop) = self.on_drop.take() {
+            on_drop();
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::{collections::HashMap, time::Duration};
+
+use futures::{future::BoxFuture, stream::BoxStream, SinkExt, StreamExt};
+use rspc::internal::jsonrpc::{Request, RequestId, RequestInner, Response, ResponseInner};
+use tokio::{
+    net::TcpStream,
+    sync::{mpsc, oneshot},
+};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+use crate::{transport::SubscriptionStream, Error, Transport};
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+enum Command {
+    Execute(Vec<(Request, oneshot::Sender<Result<Response, Error>>)>),
+    Subscribe(Request, mpsc::UnboundedSender<Response>),
+    Stop(RequestId),
+}
+
+/// a transport which sends requests to a router over a websocket. Queries, mutations and subscriptions are all supported.
+///
+/// If the connection is lost the transport will reconnect with an exponential backoff and restart all active subscriptions.
+/// Queries and mutations which were in-flight or sent while disconnected will fail with [`Error::ConnectionClosed`].
+pub struct WebsocketTransport {
+    commands: mpsc::UnboundedSender<Command>,
+}
+
+impl WebsocketTransport {
+    /// connects to the websocket endpoint of a router, eg. `ws://localhost:4000/rspc/ws`.
+    pub async fn connect(url: impl Into<String>) -> Result<Self, Error> {
+        let url = url.into();
+        let (socket, _) = connect_async(&url).await?;
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(run(url, socket, rx));
+        Ok(Self { commands: tx })
+    }
+}
+
+impl Transport for WebsocketTransport {
+    fn execute(&self, requests: Vec<Request>) -> BoxFuture<'_, Result<Vec<Response>, Error>> {
+        Box::pin(async move {
+            let (requests, receivers): (Vec<_>, Vec<_>) = requests
+                .into_iter()
+                .map(|req| {
+                    let (tx, rx) = oneshot::channel();
+                    ((req, tx), rx)
+                })
+                .unzip();
+            self.commands
+                .send(Command::Execute(requests))
+                .map_err(|_| Error::ConnectionClosed)?;
+
+            let mut responses = Vec::with_capacity(receivers.len());
+            for rx in receivers {
+                responses.push(rx.await.map_err(|_| Error::ConnectionClosed)??);
+            }
+            Ok(responses)
+        })
+    }
+
+    fn subscribe(
+        &self,
+        request: Request,
+    ) -> BoxFuture<'_, Result<BoxStream<'static, Response>, Error>> {
+        Box::pin(async move {
+            let id = request.id.clone();
+            let (tx, rx) = mpsc::unbounded_channel();
+            self.commands
+                .send(Command::Subscribe(request, tx))
+                .map_err(|_| Error::ConnectionClosed)?;
+
+            let commands = self.commands.clone();
+            Ok(SubscriptionStream {
+                rx,
+                on_drop: Some(Box::new(move || {
+                    let _ = commands.send(Command::Stop(id));
+                })),
+            }
+            .boxed())
+        })
+    }
+}
+
+async fn run(url: String, socket: Socket, mut commands: mpsc::UnboundedReceiver<Command>) {
+    let mut socket = Some(socket);
+    let mut requests = HashMap::<RequestId, oneshot::Sender<Result<Response, Error>>>::new();
+    let mut subscriptions = HashMap::<RequestId, (Request, mpsc::UnboundedSender<Response>)>::new();
+
+    loop {
+        let mut socket = match socket.take() {
+            Some(socket) => socket,
+            None => match reconnect(&url, &mut commands, &mut subscriptions).await {
+                Some(socket) => socket,
+                None => return,
+            },
+        };
+
+        let active = subscriptions
+            .values()
+            .map(|(req, _)| req)
+            .collect::<Vec<_>>();
+        if !active.is_empty() && send(&mut socket, &active).await.is_err() {
+            continue;
+        }
+
+        loop {
+            tokio::select! {
+                cmd = commands.recv() => match cmd {
+                    Some(Command::Execute(reqs)) => {
+                        let reqs = reqs
+                            .into_iter()
+                            .map(|(req, tx)| {
+                                requests.insert(req.id.clone(), tx);
+                                req
+                            })
+                            .collect::<Vec<_>>();
+                        if send(&mut socket, &reqs.iter().collect::<Vec<_>>()).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Command::Subscribe(req, tx)) => {
+                        let result = send(&mut socket, &[&req]).await;
+                        subscriptions.insert(req.id.clone(), (req, tx));
+                        if result.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Command::Stop(id)) => {
+                        if subscriptions.remove(&id).is_some() {
+                            let req = Request {
+                                jsonrpc: Some("2.0".into()),
+                                id,
+                                inner: RequestInner::SubscriptionStop,
+                            };
+                            if send(&mut socket, &[&req]).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    None => {
+                        let _ = socket.close(None).await;
+                        return;
+                    }
+                },
+                msg = socket.next() => match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        handle_response(text.as_bytes(), &mut requests, &mut subscriptions)
+                    }
+                    Some(Ok(Message::Binary(bytes))) => {
+                        handle_response(&bytes, &mut requests, &mut subscriptions)
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(_)) | None => break,
+                }
+            }
+        }
+
+        // The connection was lost so the in-flight requests will never get a response.
+        for (_, tx) in requests.drain() {
+            let _ = tx.send(Err(Error::ConnectionClosed));
+        }
+    }
+}
+
+async fn reconnect(
+    url: &str,
+    commands: &mut mpsc::UnboundedReceiver<Command>,
+    subscriptions: &mut HashMap<RequestId, (Request, mpsc::UnboundedSender<Response>)>,
+) -> Option<Socket> {
+    let mut backoff = INITIAL_BACKOFF;
+    loop {
+        let sleep = tokio::time::sleep(backoff);
+        tokio::pin!(sleep);
+        loop {
+            tokio::select! {
+                _ = &mut sleep => break,
+                cmd = commands.recv() => match cmd {
+                    Some(Command::Execute(reqs)) => {
+                        for (_, tx) in reqs {
+                            let _ = tx.send(Err(Error::ConnectionClosed));
+                        }
+                    }
+                    // Subscriptions are started once the connection is reestablished.
+                    Some(Command::Subscribe(req, tx)) => {
+                        subscriptions.insert(req.id.clone(), (req, tx));
+                    }
+                    Some(Command::Stop(id)) => {
+                        subscriptions.remove(&id);
+                    }
+                    None => return None,
+                }
+            }
+        }
+
+        if let Ok((socket, _)) = connect_async(url).await {
+            return Some(socket);
+        }
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+async fn send(socket: &mut Socket, reqs: &[&Request]) -> Result<(), Error> {
+    let msg = match reqs {
+        [req] => serde_json::to_string(req),
+        reqs => serde_json::to_string(reqs),
+    }
+    .map_err(Error::SerializingInput)?;
+
+    socket.send(Message::Text(msg)).await?;
+    Ok(())
+}
+
+fn handle_response(
+    msg: &[u8],
+    requests: &mut HashMap<RequestId, oneshot::Sender<Result<Response, Error>>>,
+    subscriptions: &mut HashMap<RequestId, (Request, mpsc::UnboundedSender<Response>)>,
+) {
+    let resp = match serde_json::from_slice::<Response>(msg) {
+        Ok(resp) => resp,
+        Err(_) => return,
+    };
+
+    if let Some(tx) = requests.remove(&resp.id) {
+        let _ = tx.send(Ok(resp));
+    } else if matches!(resp.result, ResponseInner::Complete) {
+        if let Some((_, tx)) = subscriptions.remove(&resp.id) {
+            let _ = tx.send(resp);
+        }
+    } else if let Some((_, tx)) = subscriptions.get(&resp.id) {
+        let _ = tx.send(resp);
+    }
+}
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use futures::StreamExt;
+use rspc::{Error, ErrorCode, Router};
+use rspc_client::{Client, Operation, RouterTransport};
+
+fn router() -> Arc<Router<i32>> {
+    <Router<i32>>::new()
+        .query("version", |t| t(|ctx, _: ()| format!("v{}", ctx)))
+        .query("add", |t| t(|_, (a, b): (i32, i32)| a + b))
+        .mutation("fail", |t| {
+            t(|_, _: ()| Err(Error::new(ErrorCode::Conflict, "nope".into())) as Result<(), _>)
+        })
+        .subscription("count", |t| {
+            t(|_, to: i32| futures::stream::iter(0..to))
+        })
+        .build()
+        .arced()
+}
+
+#[tokio::test]
+async fn test_router_transport() {
+    let client = Client::new(RouterTransport::new(router(), || 1));
+
+    assert_eq!(client.query::<_, String>("version", ()).await.unwrap(), "v1");
+    assert_eq!(client.query::<_, i32>("add", (1, 2)).await.unwrap(), 3);
+    match client.mutation::<_, ()>("fail", ()).await {
+        Err(rspc_client::Error::Procedure(err)) => assert_eq!(err.message, "nope"),
+        v => panic!("expected procedure error, got {:?}", v),
+    }
+
+    let results = client
+        .batch([
+            Operation::query("add", (2, 2)).unwrap(),
+            Operation::query("unknown", ()).unwrap(),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(results[0].as_ref().unwrap(), 4);
+    assert!(results[1].is_err());
+
+    let events = client
+        .subscription::<_, i32>("count", 3)
+        .await
+        .unwrap()
+        .map(|v| v.unwrap())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(events, vec![0, 1, 2]);
+}
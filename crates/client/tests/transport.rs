@@ -0,0 +1,192 @@
+use std::{
+    net::{SocketAddr, TcpListener},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::StreamExt;
+use rspc::{Error, ErrorCode, Router};
+use rspc_client::{Client, HttpTransport, Operation, WebsocketTransport};
+use tokio::task::JoinHandle;
+
+fn router() -> Arc<Router<i32>> {
+    <Router<i32>>::new()
+        .query("version", |t| t(|ctx, _: ()| format!("v{}", ctx)))
+        .query("add", |t| t(|_, (a, b): (i32, i32)| a + b))
+        .mutation("echo", |t| t(|_, v: String| v))
+        .mutation("fail", |t| {
+            t(|_, _: ()| Err(Error::new(ErrorCode::Conflict, "nope".into())) as Result<(), _>)
+        })
+        .subscription("count", |t| t(|_, to: i32| futures::stream::iter(0..to)))
+        .subscription("ticks", |t| {
+            t(|_, _: ()| {
+                futures::stream::unfold(0, |i| async move {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    Some((i, i + 1))
+                })
+            })
+        })
+        .build()
+        .arced()
+}
+
+/// serves the router at `/rspc` on a random port and returns the address it's listening on.
+fn serve(router: Arc<Router<i32>>) -> SocketAddr {
+    let app = axum::Router::new().route("/rspc/:id", router.endpoint(|| 1).axum());
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    addr
+}
+
+/// forwards TCP connections to `target` so a test can cut every open connection without stopping the server.
+struct Proxy {
+    addr: SocketAddr,
+    connections: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl Proxy {
+    async fn new(target: SocketAddr) -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connections = Arc::new(Mutex::new(Vec::new()));
+
+        let conns = connections.clone();
+        tokio::spawn(async move {
+            while let Ok((mut inbound, _)) = listener.accept().await {
+                conns.lock().unwrap().push(tokio::spawn(async move {
+                    let mut outbound = tokio::net::TcpStream::connect(target).await.unwrap();
+                    let _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+                }));
+            }
+        });
+
+        Self { addr, connections }
+    }
+
+    /// closes every connection which is currently open. New connections are still accepted.
+    fn disconnect(&self) {
+        for conn in self.connections.lock().unwrap().drain(..) {
+            conn.abort();
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_http_transport() {
+    let addr = serve(router());
+    let client = Client::new(HttpTransport::new(format!("http://{}/rspc/", addr)));
+
+    assert_eq!(
+        client.query::<_, String>("version", ()).await.unwrap(),
+        "v1"
+    );
+    assert_eq!(client.query::<_, i32>("add", (1, 2)).await.unwrap(), 3);
+    assert_eq!(
+        client.mutation::<_, String>("echo", "hello").await.unwrap(),
+        "hello"
+    );
+    match client.mutation::<_, ()>("fail", ()).await {
+        Err(rspc_client::Error::Procedure(err)) => {
+            assert_eq!(err.code, ErrorCode::Conflict.to_status_code() as i32);
+            assert_eq!(err.message, "nope");
+        }
+        v => panic!("expected procedure error, got {:?}", v),
+    }
+
+    // More than one operation is sent to the batch endpoint
+    let results = client
+        .batch([
+            Operation::query("add", (2, 2)).unwrap(),
+            Operation::query("unknown", ()).unwrap(),
+            Operation::mutation("echo", "batched").unwrap(),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(results[0].as_ref().unwrap(), 4);
+    assert!(matches!(
+        &results[1],
+        Err(rspc_client::Error::Procedure(err)) if err.code == ErrorCode::NotFound.to_status_code() as i32
+    ));
+    assert_eq!(results[2].as_ref().unwrap().as_str(), Some("batched"));
+
+    assert!(matches!(
+        client.subscription::<_, i32>("count", 3).await,
+        Err(rspc_client::Error::SubscriptionsUnsupported)
+    ));
+}
+
+#[tokio::test]
+async fn test_websocket_transport() {
+    let addr = serve(router());
+    let client = Client::new(
+        WebsocketTransport::connect(format!("ws://{}/rspc/ws", addr))
+            .await
+            .unwrap(),
+    );
+
+    assert_eq!(
+        client.query::<_, String>("version", ()).await.unwrap(),
+        "v1"
+    );
+    assert_eq!(client.query::<_, i32>("add", (1, 2)).await.unwrap(), 3);
+    match client.mutation::<_, ()>("fail", ()).await {
+        Err(rspc_client::Error::Procedure(err)) => assert_eq!(err.message, "nope"),
+        v => panic!("expected procedure error, got {:?}", v),
+    }
+
+    let results = client
+        .batch([
+            Operation::query("add", (2, 2)).unwrap(),
+            Operation::query("unknown", ()).unwrap(),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(results[0].as_ref().unwrap(), 4);
+    assert!(results[1].is_err());
+
+    let events = client
+        .subscription::<_, i32>("count", 3)
+        .await
+        .unwrap()
+        .map(|v| v.unwrap())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(events, vec![0, 1, 2]);
+}
+
+#[tokio::test]
+async fn test_websocket_transport_reconnects() {
+    let proxy = Proxy::new(serve(router())).await;
+    let client = Client::new(
+        WebsocketTransport::connect(format!("ws://{}/rspc/ws", proxy.addr))
+            .await
+            .unwrap(),
+    );
+
+    let mut ticks = Box::pin(client.subscription::<_, i32>("ticks", ()).await.unwrap());
+    assert_eq!(ticks.next().await.unwrap().unwrap(), 0);
+    assert_eq!(ticks.next().await.unwrap().unwrap(), 1);
+
+    proxy.disconnect();
+
+    // The subscription is started again on the new connection so it counts from zero
+    let restarted = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if ticks.next().await.unwrap().unwrap() == 0 {
+                break;
+            }
+        }
+    })
+    .await;
+    assert!(
+        restarted.is_ok(),
+        "the subscription wasn't restarted after reconnecting"
+    );
+
+    assert_eq!(client.query::<_, i32>("add", (1, 2)).await.unwrap(), 3);
+}
@@ -15,8 +15,8 @@ pub struct NormiContext {
 }
 
 // TODO: Convert this in a macro in rspc maybe???
-pub fn typed<TLayerCtx, TResolver, TArg, TResolverMarker, TResultMarker, TIncomingResult>(
-    builder: BuiltProcedureBuilder<TResolver>,
+pub fn typed<TLayerCtx, TResolver, TArg, TResolverMarker, TResultMarker, TIncomingResult, TMeta>(
+    builder: BuiltProcedureBuilder<TResolver, TMeta>,
 ) -> BuiltProcedureBuilder<
     impl RequestResolver<
         TLayerCtx,
@@ -25,6 +25,7 @@ pub fn typed<TLayerCtx, TResolver, TArg, TResolverMarker, TResultMarker, TIncomi
         Arg = TArg,
         Data = <TIncomingResult::Data as Object>::NormalizedResult,
     >,
+    TMeta,
 >
 where
     TLayerCtx: Send + Sync + 'static,
@@ -66,6 +67,9 @@ where
         kind: builder.kind,
         typedef: builder.typedef,
         data: builder.data,
+        meta: builder.meta,
+        timeout: builder.timeout,
+        error: builder.error,
         resolver: move |ctx, arg| {
             let val = builder.resolver.exec(ctx, arg);
 
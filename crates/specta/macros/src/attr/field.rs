@@ -1,6 +1,8 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
 use syn::{Attribute, Result};
 
-use super::parse_assign_str;
+use super::{parse_assign_f64, parse_assign_str, parse_assign_usize};
 use crate::utils::parse_attrs;
 
 #[derive(Default)]
@@ -10,6 +12,13 @@ pub struct FieldAttr {
     pub skip: bool,
     pub optional: bool,
     pub flatten: bool,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub pattern: Option<String>,
+    pub min_items: Option<usize>,
+    pub max_items: Option<usize>,
 }
 
 #[cfg(feature = "serde")]
@@ -33,6 +42,13 @@ impl FieldAttr {
             skip,
             optional,
             flatten,
+            min_length,
+            max_length,
+            min,
+            max,
+            pattern,
+            min_items,
+            max_items,
         }: FieldAttr,
     ) {
         self.rename = self.rename.take().or(rename);
@@ -40,6 +56,41 @@ impl FieldAttr {
         self.skip = self.skip || skip;
         self.optional |= optional;
         self.flatten |= flatten;
+        self.min_length = self.min_length.or(min_length);
+        self.max_length = self.max_length.or(max_length);
+        self.min = self.min.or(min);
+        self.max = self.max.or(max);
+        self.pattern = self.pattern.take().or(pattern);
+        self.min_items = self.min_items.or(min_items);
+        self.max_items = self.max_items.or(max_items);
+    }
+
+    /// returns the tokens to construct the field's `Constraints`.
+    pub fn constraints(&self, crate_ref: &TokenStream) -> TokenStream {
+        fn option<T: ToTokens>(value: &Option<T>) -> TokenStream {
+            match value {
+                Some(value) => quote!(Some(#value)),
+                None => quote!(None),
+            }
+        }
+
+        let min_length = option(&self.min_length);
+        let max_length = option(&self.max_length);
+        let min = option(&self.min);
+        let max = option(&self.max);
+        let pattern = option(&self.pattern.as_ref().map(|p| quote!(#p.to_string())));
+        let min_items = option(&self.min_items);
+        let max_items = option(&self.max_items);
+
+        quote!(#crate_ref::Constraints {
+            min_length: #min_length,
+            max_length: #max_length,
+            min: #min,
+            max: #max,
+            pattern: #pattern,
+            min_items: #min_items,
+            max_items: #max_items,
+        })
     }
 }
 
@@ -50,6 +101,13 @@ impl_parse! {
         "skip" => out.skip = true,
         "optional" => out.optional = true,
         "flatten" => out.flatten = true,
+        "min_length" => out.min_length = Some(parse_assign_usize(input)?),
+        "max_length" => out.max_length = Some(parse_assign_usize(input)?),
+        "min" => out.min = Some(parse_assign_f64(input)?),
+        "max" => out.max = Some(parse_assign_f64(input)?),
+        "pattern" => out.pattern = Some(parse_assign_str(input)?),
+        "min_items" => out.min_items = Some(parse_assign_usize(input)?),
+        "max_items" => out.max_items = Some(parse_assign_usize(input)?),
     }
 }
 
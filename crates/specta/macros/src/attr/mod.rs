@@ -65,6 +65,26 @@ fn parse_assign_str(input: ParseStream) -> Result<String> {
     }
 }
 
+fn parse_assign_usize(input: ParseStream) -> Result<usize> {
+    input.parse::<Token![=]>()?;
+    match Lit::parse(input)? {
+        Lit::Int(int) => int.base10_parse(),
+        other => Err(Error::new(other.span(), "expected integer")),
+    }
+}
+
+fn parse_assign_f64(input: ParseStream) -> Result<f64> {
+    input.parse::<Token![=]>()?;
+    let negative = input.parse::<Option<Token![-]>>()?.is_some();
+    let value = match Lit::parse(input)? {
+        Lit::Int(int) => int.base10_parse::<f64>()?,
+        Lit::Float(float) => float.base10_parse::<f64>()?,
+        other => return Err(Error::new(other.span(), "expected number")),
+    };
+
+    Ok(if negative { -value } else { value })
+}
+
 fn parse_assign_inflection(input: ParseStream) -> Result<Inflection> {
     parse_assign_str(input).and_then(Inflection::try_from)
 }
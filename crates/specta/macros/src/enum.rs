@@ -1,7 +1,7 @@
 use crate::{
     attr::{ContainerAttr, EnumAttr, FieldAttr, Tagged, VariantAttr},
     construct_datatype,
-    utils::unraw_raw_ident,
+    utils::{doc_comments_tokens, unraw_raw_ident},
 };
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
@@ -11,6 +11,7 @@ pub fn parse_enum(
     enum_name_str: &str,
     enum_attrs: &EnumAttr,
     container_attrs: &ContainerAttr,
+    description: &TokenStream,
     generics: &Generics,
     crate_ref: &TokenStream,
     data: &DataEnum,
@@ -65,10 +66,14 @@ pub fn parse_enum(
             };
 
             let generic_idents = generic_idents.clone().collect::<Vec<_>>();
+            let variant_description = doc_comments_tokens(&variant.attrs);
 
             match &variant.fields {
                 Fields::Unit => {
-                    quote!(#crate_ref::EnumVariant::Unit(#variant_name_str.to_string()))
+                    quote!(#crate_ref::EnumVariant::Unit {
+                        name: #variant_name_str.to_string(),
+                        description: #variant_description,
+                    })
                 }
                 Fields::Unnamed(fields) => {
                     let fields = fields.unnamed.iter().map(|field| {
@@ -92,7 +97,8 @@ pub fn parse_enum(
                     quote!(#crate_ref::EnumVariant::Unnamed(#crate_ref::TupleType {
                         name: #variant_name_str.to_string(),
                         fields: vec![#(#fields),*],
-                        generics: vec![]
+                        generics: vec![],
+                        description: #variant_description,
                     }))
                 }
                 Fields::Named(fields) => {
@@ -107,13 +113,17 @@ pub fn parse_enum(
                             false,
                         );
 
+                        let constraints = field_attrs.constraints(crate_ref);
                         let field_name = field_attrs
                             .rename
                             .unwrap_or_else(|| unraw_raw_ident(field.ident.as_ref().unwrap()));
+                        let field_description = doc_comments_tokens(&field.attrs);
 
                         quote!(#crate_ref::ObjectField {
                             name: #field_name.to_string(),
                             optional: false,
+                            description: #field_description,
+                            constraints: #constraints,
                             ty: {
                                 #generic_vars
 
@@ -127,6 +137,7 @@ pub fn parse_enum(
                         fields: vec![#(#fields),*],
                         generics: vec![],
                         tag: None,
+                        description: #variant_description,
                         type_id: None
                     }))
                 }
@@ -153,6 +164,7 @@ pub fn parse_enum(
             generics: vec![#(#definition_generics),*],
             variants: vec![#(#variants),*],
             repr: #crate_ref::EnumRepr::#repr,
+            description: #description,
             type_id: std::any::TypeId::of::<Self>()
         })),
         quote!(#crate_ref::DataType::Reference {
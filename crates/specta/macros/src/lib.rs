@@ -13,6 +13,7 @@ use generics::*;
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, Data, DeriveInput};
+use utils::doc_comments_tokens;
 
 #[proc_macro_derive(Type, attributes(specta, serde))]
 pub fn derive_type(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -56,8 +57,17 @@ fn derive_type_internal(
         .clone()
         .unwrap_or_else(|| ident.to_string());
 
+    let description = doc_comments_tokens(attrs);
+
     let (inlines, reference) = match data {
-        Data::Struct(data) => parse_struct(&name_str, &container_attrs, generics, &crate_ref, data),
+        Data::Struct(data) => parse_struct(
+            &name_str,
+            &container_attrs,
+            &description,
+            generics,
+            &crate_ref,
+            data,
+        ),
         Data::Enum(data) => {
             let enum_attrs = EnumAttr::from_attrs(attrs).unwrap();
 
@@ -65,6 +75,7 @@ fn derive_type_internal(
                 &name_str,
                 &enum_attrs,
                 &container_attrs,
+                &description,
                 generics,
                 &crate_ref,
                 data,
@@ -118,6 +129,7 @@ fn derive_type_internal(
                         generics: vec![],
                         fields: vec![],
                         tag: None,
+                        description: None,
                         type_id: Some(std::any::TypeId::of::<Self>())
                     }));
 
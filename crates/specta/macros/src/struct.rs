@@ -1,6 +1,6 @@
 use crate::{
     attr::{ContainerAttr, FieldAttr},
-    utils::unraw_raw_ident,
+    utils::{doc_comments_tokens, unraw_raw_ident},
 };
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
@@ -11,6 +11,7 @@ use crate::construct_datatype;
 pub fn parse_struct(
     struct_name: &str,
     container_attrs: &ContainerAttr,
+    description: &TokenStream,
     generics: &Generics,
     crate_ref: &TokenStream,
     data: &DataStruct,
@@ -52,6 +53,8 @@ pub fn parse_struct(
                     return None;
                 }
 
+                let constraints = field_attrs.constraints(crate_ref);
+
                 let ty = construct_datatype(
                     format_ident!("ty"),
                     &field.ty,
@@ -69,6 +72,7 @@ pub fn parse_struct(
                 };
 
                 let optional = field_attrs.optional;
+                let field_description = doc_comments_tokens(&field.attrs);
 
                 if field_attrs.flatten {
                     let field_ty = &field.ty;
@@ -88,6 +92,8 @@ pub fn parse_struct(
                     Some(quote!(vec![#crate_ref::ObjectField {
                         name: #field_name.to_string(),
                         optional: #optional,
+                        description: #field_description,
+                        constraints: #constraints,
                         ty: {
                             #ty
 
@@ -111,6 +117,7 @@ pub fn parse_struct(
                     .flatten()
                     .collect::<Vec<_>>(),
                 tag: #tag,
+                description: #description,
                 type_id: Some(std::any::TypeId::of::<Self>())
             }))
         }
@@ -140,13 +147,15 @@ pub fn parse_struct(
             quote!(#crate_ref::DataType::Tuple(#crate_ref::TupleType {
                 name: #struct_name.to_string(),
                 generics: vec![#(#definition_generics),*],
-                fields: vec![#(#fields),*]
+                fields: vec![#(#fields),*],
+                description: #description,
             }))
         }
         Fields::Unit => quote!(#crate_ref::DataType::Tuple(#crate_ref::TupleType {
             name: #struct_name.to_string(),
             generics: vec![#(#definition_generics),*],
             fields: vec![],
+            description: #description,
         })),
     };
 
@@ -1,6 +1,8 @@
 use std::convert::TryFrom;
 
-use syn::{Attribute, Error, Ident, Result};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Error, Ident, Lit, Meta, MetaNameValue, Result};
 
 macro_rules! syn_err {
     ($l:literal $(, $a:expr)*) => {
@@ -76,3 +78,33 @@ pub fn unraw_raw_ident(ident: &Ident) -> String {
         ident
     }
 }
+
+/// collects the `///` doc comments on an item into a single string.
+fn parse_doc_comments(attrs: &[Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter(|a| a.path.is_ident("doc"))
+        .filter_map(|a| match a.parse_meta() {
+            Ok(Meta::NameValue(MetaNameValue {
+                lit: Lit::Str(doc), ..
+            })) => Some(doc.value()),
+            _ => None,
+        })
+        .flat_map(|doc| {
+            doc.split('\n')
+                .map(|line| line.strip_prefix(' ').unwrap_or(line).trim_end().to_owned())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let doc = lines.join("\n").trim().to_owned();
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// returns the tokens for an `Option<String>` containing the doc comments on an item.
+pub fn doc_comments_tokens(attrs: &[Attribute]) -> TokenStream {
+    match parse_doc_comments(attrs) {
+        Some(doc) => quote!(Some(#doc.to_string())),
+        None => quote!(None),
+    }
+}
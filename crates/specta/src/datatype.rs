@@ -3,6 +3,7 @@ use std::any::TypeId;
 use crate::{EnumType, ObjectType};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum DataType {
     // Always inlined
     Any,
@@ -18,6 +19,7 @@ pub enum DataType {
     Reference {
         name: String,
         generics: Vec<DataType>,
+        #[cfg_attr(feature = "serde", serde(skip))]
         type_id: TypeId,
     },
     Generic(String),
@@ -25,6 +27,7 @@ pub enum DataType {
 
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum PrimitiveType {
     Never,
     i8,
@@ -47,8 +50,11 @@ pub enum PrimitiveType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TupleType {
     pub name: String,
     pub fields: Vec<DataType>,
     pub generics: Vec<&'static str>,
+    /// the doc comment on the tuple struct or enum variant.
+    pub description: Option<String>,
 }
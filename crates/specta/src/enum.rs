@@ -3,12 +3,16 @@ use std::any::TypeId;
 use crate::{DataType, ObjectType, PrimitiveType, TupleType};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct EnumType {
     pub name: String,
     pub variants: Vec<EnumVariant>,
     pub generics: Vec<&'static str>,
     pub repr: EnumRepr,
-    pub type_id: TypeId
+    /// the doc comment on the enum.
+    pub description: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub type_id: TypeId,
 }
 
 impl PartialEq for EnumType {
@@ -18,6 +22,7 @@ impl PartialEq for EnumType {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum EnumRepr {
     External,
     Internal { tag: String },
@@ -26,8 +31,12 @@ pub enum EnumRepr {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum EnumVariant {
-    Unit(String),
+    Unit {
+        name: String,
+        description: Option<String>,
+    },
     Unnamed(TupleType),
     Named(ObjectType),
 }
@@ -35,15 +44,23 @@ pub enum EnumVariant {
 impl EnumVariant {
     pub fn name(&self) -> &str {
         match self {
-            Self::Unit(name) => name,
+            Self::Unit { name, .. } => name,
             Self::Unnamed(tuple_type) => &tuple_type.name,
             Self::Named(object_type) => &object_type.name,
         }
     }
 
+    pub fn description(&self) -> Option<&str> {
+        match self {
+            Self::Unit { description, .. } => description.as_deref(),
+            Self::Unnamed(tuple_type) => tuple_type.description.as_deref(),
+            Self::Named(object_type) => object_type.description.as_deref(),
+        }
+    }
+
     pub fn data_type(&self) -> DataType {
         match self {
-            Self::Unit(_) => DataType::Primitive(PrimitiveType::Never),
+            Self::Unit { .. } => DataType::Primitive(PrimitiveType::Never),
             Self::Unnamed(tuple_type) => DataType::Tuple(tuple_type.clone()),
             Self::Named(object_type) => DataType::Object(object_type.clone()),
         }
@@ -37,7 +37,8 @@ macro_rules! impl_tuple {
                 DataType::Tuple(TupleType {
                     name: stringify!(($($i),*)).to_string(),
                     fields: vec![$($i),*],
-                    generics: vec![]
+                    generics: vec![],
+                    description: None,
                 })
             }
 
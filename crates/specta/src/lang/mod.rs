@@ -1,7 +1,9 @@
 #[cfg(feature = "openapi")]
 mod openapi;
+mod rust;
 mod typescript;
 
 #[cfg(feature = "openapi")]
 pub use openapi::*;
+pub use rust::*;
 pub use typescript::*;
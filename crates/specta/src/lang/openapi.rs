@@ -2,7 +2,9 @@ use openapiv3::{
     ArrayType, NumberType, ReferenceOr, Schema, SchemaData, SchemaKind, StringType, Type,
 };
 
-use crate::{DataType, EnumType, EnumVariant, ObjectField, ObjectType, PrimitiveType, TupleType};
+use crate::{
+    Constraints, DataType, EnumType, EnumVariant, ObjectField, ObjectType, PrimitiveType, TupleType,
+};
 
 // pub fn to_openapi_export(def: &DataType) -> Result<openapiv3::Schema, String> {
 //     Ok(match &def {
@@ -52,8 +54,13 @@ pub fn to_openapi(typ: &DataType) -> ReferenceOr<Schema> {
         deprecated: false, // TODO
         example: None,     // TODO
         title: None,       // TODO
-        description: None, // TODO
-        default: None,     // TODO
+        description: match typ {
+            DataType::Object(ObjectType { description, .. })
+            | DataType::Enum(EnumType { description, .. })
+            | DataType::Tuple(TupleType { description, .. }) => description.clone(),
+            _ => None,
+        },
+        default: None, // TODO
         ..Default::default()
     };
 
@@ -109,7 +116,7 @@ pub fn to_openapi(typ: &DataType) -> ReferenceOr<Schema> {
                     schema_kind: SchemaKind::Type(Type::Object(openapiv3::ObjectType::default())), // TODO: This should be `null` type
                 })
             }
-            [ty] => to_openapi(ty),
+            [ty] => with_description(to_openapi(ty), schema_data.description),
             tys => todo!(),
         },
         DataType::Object(ObjectType {
@@ -133,17 +140,28 @@ pub fn to_openapi(typ: &DataType) -> ReferenceOr<Schema> {
                     schema_kind: SchemaKind::Type(Type::Object(openapiv3::ObjectType {
                         properties: fields
                             .iter()
-                            .map(|ObjectField { name, ty, optional }| {
-                                (
-                                    name.clone(),
-                                    match to_openapi(ty) {
-                                        ReferenceOr::Item(v) => ReferenceOr::Item(Box::new(v)),
-                                        ReferenceOr::Reference { reference } => {
-                                            ReferenceOr::Reference { reference }
-                                        }
-                                    },
-                                )
-                            })
+                            .map(
+                                |ObjectField {
+                                     name,
+                                     ty,
+                                     description,
+                                     constraints,
+                                     ..
+                                 }| {
+                                    (
+                                        name.clone(),
+                                        match with_constraints(
+                                            with_description(to_openapi(ty), description.clone()),
+                                            constraints,
+                                        ) {
+                                            ReferenceOr::Item(v) => ReferenceOr::Item(Box::new(v)),
+                                            ReferenceOr::Reference { reference } => {
+                                                ReferenceOr::Reference { reference }
+                                            }
+                                        },
+                                    )
+                                },
+                            )
                             .collect(),
                         ..Default::default()
                     })),
@@ -203,12 +221,17 @@ pub fn to_openapi(typ: &DataType) -> ReferenceOr<Schema> {
                         any_of: variants
                             .iter()
                             .map(|variant| match variant {
-                                EnumVariant::Unit(_) => ReferenceOr::Item(Schema {
-                                    schema_data: Default::default(),
-                                    schema_kind: SchemaKind::Type(Type::Object(
-                                        openapiv3::ObjectType::default(), // TODO: Is this correct?
-                                    )),
-                                }),
+                                EnumVariant::Unit { description, .. } => {
+                                    ReferenceOr::Item(Schema {
+                                        schema_data: SchemaData {
+                                            description: description.clone(),
+                                            ..Default::default()
+                                        },
+                                        schema_kind: SchemaKind::Type(Type::Object(
+                                            openapiv3::ObjectType::default(), // TODO: Is this correct?
+                                        )),
+                                    })
+                                }
                                 EnumVariant::Unnamed(tuple) => {
                                     to_openapi(&DataType::Tuple(tuple.clone()))
                                 }
@@ -248,3 +271,44 @@ pub fn to_openapi(typ: &DataType) -> ReferenceOr<Schema> {
         }
     }
 }
+
+/// sets the description of a schema. References can't have a description so they are left unchanged.
+fn with_description(
+    schema: ReferenceOr<Schema>,
+    description: Option<String>,
+) -> ReferenceOr<Schema> {
+    match (schema, description) {
+        (ReferenceOr::Item(mut schema), Some(description)) => {
+            schema.schema_data.description = Some(description);
+            ReferenceOr::Item(schema)
+        }
+        (schema, _) => schema,
+    }
+}
+
+/// adds the constraints declared on a field to its schema.
+fn with_constraints(schema: ReferenceOr<Schema>, constraints: &Constraints) -> ReferenceOr<Schema> {
+    match schema {
+        ReferenceOr::Item(mut schema) => {
+            match &mut schema.schema_kind {
+                SchemaKind::Type(Type::String(string)) => {
+                    string.min_length = constraints.min_length.or(string.min_length);
+                    string.max_length = constraints.max_length.or(string.max_length);
+                    string.pattern = constraints.pattern.clone().or(string.pattern.take());
+                }
+                SchemaKind::Type(Type::Number(number)) => {
+                    number.minimum = constraints.min.or(number.minimum);
+                    number.maximum = constraints.max.or(number.maximum);
+                }
+                SchemaKind::Type(Type::Array(array)) => {
+                    array.min_items = constraints.min_items.or(array.min_items);
+                    array.max_items = constraints.max_items.or(array.max_items);
+                }
+                _ => {}
+            }
+
+            ReferenceOr::Item(schema)
+        }
+        schema => schema,
+    }
+}
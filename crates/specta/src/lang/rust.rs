@@ -0,0 +1,281 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    DataType, EnumRepr, EnumType, EnumVariant, ObjectField, ObjectType, PrimitiveType, TupleType,
+};
+
+const DERIVE: &str = "#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]";
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "do", "final", "macro", "override", "priv", "try", "typeof",
+    "unsized", "virtual", "yield",
+];
+
+/// converts a type definition into a Rust struct or enum which is (de)serialized the same as the original type.
+pub fn to_rust_export(def: &DataType) -> Result<String, String> {
+    Ok(match def {
+        DataType::Object(ObjectType {
+            name,
+            generics,
+            fields,
+            tag,
+            ..
+        }) => {
+            let generics = rust_generics(generics);
+            match &fields[..] {
+                [] => format!("{DERIVE}\npub struct {name}{generics};"),
+                fields => {
+                    let tag = match tag {
+                        Some(tag) => format!("#[serde(tag = \"{tag}\")]\n"),
+                        None => "".into(),
+                    };
+
+                    format!(
+                        "{DERIVE}\n{tag}pub struct {name}{generics} {{\n{}}}",
+                        rust_fields(fields, "    ", "pub ")
+                    )
+                }
+            }
+        }
+        DataType::Enum(EnumType {
+            name,
+            generics,
+            variants,
+            repr,
+            ..
+        }) => {
+            let repr = match repr {
+                EnumRepr::External => "".into(),
+                EnumRepr::Internal { tag } => format!("#[serde(tag = \"{tag}\")]\n"),
+                EnumRepr::Adjacent { tag, content } => {
+                    format!("#[serde(tag = \"{tag}\", content = \"{content}\")]\n")
+                }
+                EnumRepr::Untagged => "#[serde(untagged)]\n".into(),
+            };
+            let variants = variants
+                .iter()
+                .map(|variant| {
+                    let ident = pascal_case_ident(variant.name());
+                    let rename = rename_attr(&ident, variant.name(), "    ");
+                    let body = match variant {
+                        EnumVariant::Unit { .. } => "".into(),
+                        EnumVariant::Unnamed(TupleType { fields, .. }) => format!(
+                            "({})",
+                            fields.iter().map(to_rust).collect::<Vec<_>>().join(", ")
+                        ),
+                        EnumVariant::Named(ObjectType { fields, .. }) => {
+                            format!(" {{\n{}    }}", rust_fields(fields, "        ", ""))
+                        }
+                    };
+
+                    format!("{rename}    {ident}{body},\n")
+                })
+                .collect::<String>();
+
+            format!(
+                "{DERIVE}\n{repr}pub enum {name}{} {{\n{variants}}}",
+                rust_generics(generics)
+            )
+        }
+        DataType::Tuple(TupleType {
+            name,
+            fields,
+            generics,
+            ..
+        }) => {
+            let generics = rust_generics(generics);
+            match &fields[..] {
+                [] => format!("{DERIVE}\npub struct {name}{generics};"),
+                fields => format!(
+                    "{DERIVE}\npub struct {name}{generics}({});",
+                    fields
+                        .iter()
+                        .map(|ty| format!("pub {}", to_rust(ty)))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            }
+        }
+        _ => return Err(format!("Type cannot be exported: {:?}", def)),
+    })
+}
+
+/// converts a type into the Rust type used to reference it. Named types must be exported using [`to_rust_export`].
+pub fn to_rust(typ: &DataType) -> String {
+    match &typ {
+        DataType::Any => "serde_json::Value".into(),
+        DataType::Primitive(PrimitiveType::Never) => "()".into(),
+        // The primitive types are named the same as their Rust types
+        DataType::Primitive(primitive) => format!("{:?}", primitive),
+        DataType::Nullable(def) => format!("Option<{}>", to_rust(def)),
+        DataType::Record(def) => format!(
+            "std::collections::HashMap<{}, {}>",
+            to_rust(&def.0),
+            to_rust(&def.1)
+        ),
+        DataType::List(def) => format!("Vec<{}>", to_rust(def)),
+        DataType::Tuple(TupleType { name, fields, .. }) if is_anonymous_tuple(name) => {
+            match &fields[..] {
+                [] => "()".into(),
+                [ty] => format!("({},)", to_rust(ty)),
+                tys => format!(
+                    "({})",
+                    tys.iter().map(to_rust).collect::<Vec<_>>().join(", ")
+                ),
+            }
+        }
+        DataType::Tuple(TupleType { name, .. })
+        | DataType::Object(ObjectType { name, .. })
+        | DataType::Enum(EnumType { name, .. }) => name.clone(),
+        DataType::Reference { name, generics, .. } => match &generics[..] {
+            [] => name.to_string(),
+            generics => {
+                let generics = generics.iter().map(to_rust).collect::<Vec<_>>().join(", ");
+
+                format!("{name}<{generics}>")
+            }
+        },
+        DataType::Generic(ident) => ident.to_string(),
+    }
+}
+
+/// collects the named types in `typ`, including `typ` itself, which are inlined instead of referenced.
+/// They must be exported alongside the type definitions for the generated Rust code to compile.
+pub fn rust_inline_defs(typ: &DataType, out: &mut BTreeMap<String, DataType>) {
+    if let DataType::Object(ObjectType { name, .. })
+    | DataType::Enum(EnumType { name, .. })
+    | DataType::Tuple(TupleType { name, .. }) = typ
+    {
+        if !is_anonymous_tuple(name) && !out.contains_key(name) {
+            out.insert(name.clone(), typ.clone());
+        }
+    }
+
+    match typ {
+        DataType::Nullable(ty) | DataType::List(ty) => rust_inline_defs(ty, out),
+        DataType::Record(def) => {
+            rust_inline_defs(&def.0, out);
+            rust_inline_defs(&def.1, out);
+        }
+        DataType::Tuple(TupleType { fields, .. }) => {
+            fields.iter().for_each(|ty| rust_inline_defs(ty, out))
+        }
+        DataType::Object(ObjectType { fields, .. }) => fields
+            .iter()
+            .for_each(|field| rust_inline_defs(&field.ty, out)),
+        DataType::Enum(EnumType { variants, .. }) => {
+            for variant in variants {
+                match variant {
+                    EnumVariant::Unit { .. } => {}
+                    EnumVariant::Unnamed(TupleType { fields, .. }) => {
+                        fields.iter().for_each(|ty| rust_inline_defs(ty, out))
+                    }
+                    EnumVariant::Named(ObjectType { fields, .. }) => fields
+                        .iter()
+                        .for_each(|field| rust_inline_defs(&field.ty, out)),
+                }
+            }
+        }
+        DataType::Reference { generics, .. } => {
+            generics.iter().for_each(|ty| rust_inline_defs(ty, out))
+        }
+        DataType::Any | DataType::Primitive(_) | DataType::Generic(_) => {}
+    }
+}
+
+fn rust_fields(fields: &[ObjectField], indent: &str, vis: &str) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            let ident = snake_case_ident(&field.name);
+            let rename = rename_attr(&ident, &field.name, indent);
+
+            let ty = match (field.optional, &field.ty) {
+                (true, DataType::Nullable(_)) | (false, _) => to_rust(&field.ty),
+                (true, ty) => format!("Option<{}>", to_rust(ty)),
+            };
+            let optional = match field.optional {
+                true => format!(
+                    "{indent}#[serde(default, skip_serializing_if = \"Option::is_none\")]\n"
+                ),
+                false => "".into(),
+            };
+
+            format!("{rename}{optional}{indent}{vis}{ident}: {ty},\n")
+        })
+        .collect()
+}
+
+fn rust_generics(generics: &[&'static str]) -> String {
+    match generics.len() {
+        0 => "".into(),
+        _ => format!("<{}>", generics.join(", ")),
+    }
+}
+
+fn rename_attr(ident: &str, name: &str, indent: &str) -> String {
+    match ident.trim_start_matches("r#") == name {
+        true => "".into(),
+        false => format!("{indent}#[serde(rename = \"{name}\")]\n"),
+    }
+}
+
+fn is_anonymous_tuple(name: &str) -> bool {
+    name.is_empty() || name.starts_with('(')
+}
+
+/// converts a name into a valid snake case Rust identifier, eg. `userId` becomes `user_id`.
+pub fn snake_case_ident(value: &str) -> String {
+    let chars = value.chars().collect::<Vec<_>>();
+    let mut ident = String::with_capacity(value.len());
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let prev = i.checked_sub(1).and_then(|i| chars.get(i));
+            let next = chars.get(i + 1);
+            if prev
+                .map(|p| p.is_lowercase() || p.is_numeric())
+                .unwrap_or(false)
+                || (prev.map(|p| p.is_uppercase()).unwrap_or(false)
+                    && next.map(|n| n.is_lowercase()).unwrap_or(false))
+            {
+                ident.push('_');
+            }
+            ident.extend(c.to_lowercase());
+        } else if c.is_alphanumeric() {
+            ident.push(*c);
+        } else if !ident.ends_with('_') {
+            ident.push('_');
+        }
+    }
+
+    sanitise_ident(ident)
+}
+
+/// converts a name into a valid pascal case Rust identifier, eg. `my_variant` becomes `MyVariant`.
+pub fn pascal_case_ident(value: &str) -> String {
+    let ident = value
+        .split(|c: char| !c.is_alphanumeric())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => "".into(),
+            }
+        })
+        .collect::<String>();
+
+    sanitise_ident(ident)
+}
+
+fn sanitise_ident(ident: String) -> String {
+    match ident.as_str() {
+        "" => "_".into(),
+        "self" | "Self" | "super" | "crate" => format!("{ident}_"),
+        v if v.starts_with(|c: char| c.is_numeric()) => format!("_{ident}"),
+        v if KEYWORDS.contains(&v) => format!("r#{ident}"),
+        _ => ident,
+    }
+}
@@ -49,8 +49,17 @@ pub fn ts_export<T: Type>() -> Result<String, String> {
 
 pub fn to_ts_export(def: &DataType) -> Result<String, String> {
     let inline_ts = to_ts(def);
+    let docs = match def {
+        DataType::Object(ObjectType { description, .. })
+        | DataType::Enum(EnumType { description, .. })
+        | DataType::Tuple(TupleType { description, .. }) => match description {
+            Some(description) => format!("{}\n", js_doc(description)),
+            None => "".into(),
+        },
+        _ => "".into(),
+    };
 
-    Ok(match &def {
+    let export = match &def {
         // Named struct
         DataType::Object(ObjectType {
             name,
@@ -82,7 +91,31 @@ pub fn to_ts_export(def: &DataType) -> Result<String, String> {
             format!("export type {name} = {inline_ts}")
         }
         _ => return Err(format!("Type cannot be exported: {:?}", def)),
-    })
+    };
+
+    Ok(format!("{docs}{export}"))
+}
+
+/// formats a description as a JSDoc comment.
+pub fn js_doc(description: &str) -> String {
+    let description = description.replace("*/", "*\\/");
+    match description.lines().count() {
+        0 | 1 => format!("/** {description} */"),
+        _ => format!(
+            "/**\n{} */",
+            description
+                .lines()
+                .map(|line| format!(" * {line}\n").replace(" * \n", " *\n"))
+                .collect::<String>()
+        ),
+    }
+}
+
+fn inline_js_doc(description: Option<&str>) -> String {
+    match description {
+        Some(description) => format!("{} ", js_doc(description)),
+        None => "".into(),
+    }
 }
 
 macro_rules! primitive_def {
@@ -132,9 +165,10 @@ pub fn to_ts(typ: &DataType) -> String {
                 .iter()
                 .map(|variant| {
                     let sanitised_name = sanitise_name(variant.name());
+                    let docs = inline_js_doc(variant.description());
 
-                    match (repr, variant) {
-                        (EnumRepr::Internal { tag }, EnumVariant::Unit(_)) => {
+                    docs + &match (repr, variant) {
+                        (EnumRepr::Internal { tag }, EnumVariant::Unit { .. }) => {
                             format!("{{ {tag}: \"{sanitised_name}\" }}")
                         }
                         (EnumRepr::Internal { tag }, EnumVariant::Unnamed(tuple)) => {
@@ -149,7 +183,7 @@ pub fn to_ts(typ: &DataType) -> String {
 
                             format!("{{ {} }}", fields.join(", "))
                         }
-                        (EnumRepr::External, EnumVariant::Unit(_)) => {
+                        (EnumRepr::External, EnumVariant::Unit { .. }) => {
                             format!("\"{sanitised_name}\"")
                         }
                         (EnumRepr::External, v) => {
@@ -157,9 +191,9 @@ pub fn to_ts(typ: &DataType) -> String {
 
                             format!("{{ {sanitised_name}: {ts_values} }}")
                         }
-                        (EnumRepr::Untagged, EnumVariant::Unit(_)) => "null".to_string(),
+                        (EnumRepr::Untagged, EnumVariant::Unit { .. }) => "null".to_string(),
                         (EnumRepr::Untagged, v) => to_ts(&v.data_type()),
-                        (EnumRepr::Adjacent { tag, .. }, EnumVariant::Unit(_)) => {
+                        (EnumRepr::Adjacent { tag, .. }, EnumVariant::Unit { .. }) => {
                             format!("{{ {tag}: \"{sanitised_name}\" }}")
                         }
                         (EnumRepr::Adjacent { tag, content }, v) => {
@@ -201,11 +235,41 @@ pub fn object_fields(fields: &[ObjectField]) -> Vec<String> {
                 false => (field_name_safe, &field.ty),
             };
 
-            format!("{key}: {}", to_ts(ty))
+            format!(
+                "{}{key}: {}",
+                inline_js_doc(field_docs(field).as_deref()),
+                to_ts(ty)
+            )
         })
         .collect::<Vec<_>>()
 }
 
+/// returns the description of a field followed by its constraints as JSDoc tags.
+fn field_docs(field: &ObjectField) -> Option<String> {
+    let constraints = &field.constraints;
+    let tags = [
+        ("minLength", constraints.min_length.map(|v| v.to_string())),
+        ("maxLength", constraints.max_length.map(|v| v.to_string())),
+        ("minimum", constraints.min.map(|v| v.to_string())),
+        ("maximum", constraints.max.map(|v| v.to_string())),
+        ("pattern", constraints.pattern.clone()),
+        ("minItems", constraints.min_items.map(|v| v.to_string())),
+        ("maxItems", constraints.max_items.map(|v| v.to_string())),
+    ];
+
+    let lines = field
+        .description
+        .iter()
+        .cloned()
+        .chain(
+            tags.into_iter()
+                .filter_map(|(tag, value)| value.map(|value| format!("@{tag} {value}"))),
+        )
+        .collect::<Vec<_>>();
+
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
 pub fn sanitise_name(value: &str) -> String {
     let valid = value
         .chars()
@@ -381,6 +381,8 @@ mod uhlc_impls {
                     ObjectField {
                         name: "id".to_string(),
                         optional: false,
+                        description: None,
+                        constraints: Default::default(),
                         ty: {
                             let ty = <ID as Type>::reference(
                                 DefOpts {
@@ -395,6 +397,8 @@ mod uhlc_impls {
                     ObjectField {
                         name: "time".to_string(),
                         optional: false,
+                        description: None,
+                        constraints: Default::default(),
                         ty: {
                             let ty = <NTP64 as Type>::reference(
                                 DefOpts {
@@ -408,6 +412,7 @@ mod uhlc_impls {
                     },
                 ],
                 tag: None,
+                description: None,
                 type_id: Some(TypeId::of::<Self>()),
             })
         }
@@ -435,6 +440,7 @@ mod uhlc_impls {
                         generics: vec![],
                         fields: vec![],
                         tag: None,
+                        description: None,
                         type_id: Some(TypeId::of::<Self>()),
                     }),
                 );
@@ -3,18 +3,52 @@ use std::any::TypeId;
 use crate::DataType;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ObjectField {
     pub name: String,
     pub ty: DataType,
     pub optional: bool,
+    /// the doc comment on the field.
+    pub description: Option<String>,
+    pub constraints: Constraints,
+}
+
+/// the validation constraints declared on a field using `#[specta(...)]`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Constraints {
+    /// the minimum number of characters in a string.
+    pub min_length: Option<usize>,
+    /// the maximum number of characters in a string.
+    pub max_length: Option<usize>,
+    /// the minimum value of a number.
+    pub min: Option<f64>,
+    /// the maximum value of a number.
+    pub max: Option<f64>,
+    /// a regular expression a string must match.
+    pub pattern: Option<String>,
+    /// the minimum number of items in a list.
+    pub min_items: Option<usize>,
+    /// the maximum number of items in a list.
+    pub max_items: Option<usize>,
+}
+
+impl Constraints {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ObjectType {
     pub name: String,
     pub generics: Vec<&'static str>,
     pub fields: Vec<ObjectField>,
     pub tag: Option<String>,
+    /// the doc comment on the struct or enum variant.
+    pub description: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub type_id: Option<TypeId>,
 }
 
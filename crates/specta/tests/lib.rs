@@ -1,2 +1,3 @@
+mod rust;
 mod ts_rs;
 mod typescript;
@@ -0,0 +1,64 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use specta::{to_rust, to_rust_export, DefOpts, Type, TypeDefs};
+
+fn inline<T: Type>() -> specta::DataType {
+    T::inline(
+        DefOpts {
+            parent_inline: true,
+            type_map: &mut TypeDefs::new(),
+        },
+        &[],
+    )
+}
+
+#[derive(Type, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct User {
+    user_id: i32,
+    r#type: Option<String>,
+}
+
+#[derive(Type, Serialize)]
+#[serde(tag = "t", content = "c")]
+enum Event {
+    Created,
+    Renamed(String),
+}
+
+#[test]
+fn rust_types() {
+    assert_eq!(to_rust(&inline::<i32>()), "i32");
+    assert_eq!(to_rust(&inline::<()>()), "()");
+    assert_eq!(to_rust(&inline::<(String, bool)>()), "(String, bool)");
+    assert_eq!(to_rust(&inline::<Vec<Option<u8>>>()), "Vec<Option<u8>>");
+    assert_eq!(
+        to_rust(&inline::<HashMap<String, i64>>()),
+        "std::collections::HashMap<String, i64>"
+    );
+}
+
+#[test]
+fn rust_exports() {
+    assert_eq!(
+        to_rust_export(&inline::<User>()).unwrap(),
+        r#"#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct User {
+    #[serde(rename = "userId")]
+    pub user_id: i32,
+    pub r#type: Option<String>,
+}"#
+    );
+    assert_eq!(
+        to_rust_export(&inline::<Event>()).unwrap(),
+        r#"#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "t", content = "c")]
+pub enum Event {
+    Created,
+    Renamed(String),
+}"#
+    );
+}
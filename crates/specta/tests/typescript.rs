@@ -6,7 +6,7 @@ use std::{
     path::PathBuf,
 };
 
-use specta::{ts_inline, Type};
+use specta::{ts_export, ts_inline, Type};
 
 macro_rules! assert_ts_type {
     ($t:ty, $e:expr) => {
@@ -90,6 +90,27 @@ fn typescript_types() {
     assert_ts_type!(GenericStruct<String>, "{ arg: string }");
 }
 
+#[test]
+fn typescript_doc_comments() {
+    assert_eq!(
+        ts_export::<DocumentedStruct>().unwrap(),
+        "/** a struct with docs */\nexport interface DocumentedStruct { /**\n * the first field\n *\n * it has two paragraphs\n */ a: number, b: string }"
+    );
+    assert_eq!(
+        ts_export::<DocumentedEnum>().unwrap(),
+        r#"/** an enum with docs */
+export type DocumentedEnum = /** the unit variant */ "A" | { B: { /** a field */ c: number } }"#
+    );
+}
+
+#[test]
+fn typescript_constraints() {
+    assert_eq!(
+        ts_export::<ConstrainedStruct>().unwrap(),
+        "export interface ConstrainedStruct { /**\n * the name\n * @minLength 1\n * @pattern ^[a-z]+$\n */ a: string, /** @minimum -1 */ b: number, /** @maxItems 3 */ c: Array<number> }"
+    );
+}
+
 #[derive(Type)]
 struct Unit;
 
@@ -146,3 +167,35 @@ struct InlinerStruct {
 struct GenericStruct<T> {
     arg: T,
 }
+
+/// a struct with docs
+#[derive(Type)]
+struct DocumentedStruct {
+    /// the first field
+    ///
+    /// it has two paragraphs
+    a: i32,
+    b: String,
+}
+
+#[derive(Type)]
+struct ConstrainedStruct {
+    /// the name
+    #[specta(min_length = 1, pattern = "^[a-z]+$")]
+    a: String,
+    #[specta(min = -1)]
+    b: i32,
+    #[specta(max_items = 3)]
+    c: Vec<i32>,
+}
+
+/// an enum with docs
+#[derive(Type)]
+enum DocumentedEnum {
+    /// the unit variant
+    A,
+    B {
+        /// a field
+        c: i32,
+    },
+}
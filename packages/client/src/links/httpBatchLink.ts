@@ -3,8 +3,6 @@ import { dataLoader } from "../internals/dataLoader";
 import {
   HTTPLinkOptions,
   HTTPResult,
-  getUrl,
-  httpRequest,
   resolveHTTPLinkOptions,
 } from "./internals/httpUtils";
 import { transformResult } from "./internals/transformResult";
@@ -14,7 +12,15 @@ import { ProceduresDef, RSPCError } from "..";
 type ProcedureType = any; // TODO
 
 export interface HttpBatchLinkOptions extends HTTPLinkOptions {
+  /**
+   * The maximum length of a single batch request
+   * @deprecated Batches are now sent in the request body so this limits the length of the body. Use `maxBatchSize` instead.
+   */
   maxURLLength?: number;
+  /**
+   * The maximum number of operations to send in a single batch request
+   */
+  maxBatchSize?: number;
 }
 
 export function httpBatchLink<TProcedures extends ProceduresDef>(
@@ -26,52 +32,82 @@ export function httpBatchLink<TProcedures extends ProceduresDef>(
     type BatchOperation = { id: number; path: string; input: unknown };
 
     const maxURLLength = opts.maxURLLength || Infinity;
+    const maxBatchSize = opts.maxBatchSize || Infinity;
 
     const batchLoader = (type: ProcedureType) => {
+      const batchBody = (batchOps: BatchOperation[]) =>
+        JSON.stringify(
+          batchOps.map((op) => ({
+            jsonrpc: "2.0",
+            id: op.id,
+            method: type,
+            params: {
+              path: op.path,
+              input: runtime.transformer.serialize(op.input),
+            },
+          }))
+        );
+
       const validate = (batchOps: BatchOperation[]) => {
+        if (batchOps.length > maxBatchSize) {
+          return false;
+        }
         if (maxURLLength === Infinity) {
           // escape hatch for quick calcs
           return true;
         }
-        const path = batchOps.map((op) => op.path).join(",");
-        const inputs = batchOps.map((op) => op.input);
-
-        const url = getUrl({
-          ...resolvedOpts,
-          runtime,
-          type,
-          path,
-          inputs,
-        });
-        return url.length <= maxURLLength;
+        return batchBody(batchOps).length <= maxURLLength;
       };
 
       const fetch = (batchOps: BatchOperation[]) => {
-        const path = batchOps.map((op) => op.path).join(",");
-        const inputs = batchOps.map((op) => op.input);
-
-        const { promise, cancel } = httpRequest({
-          ...resolvedOpts,
-          runtime,
-          type,
-          path,
-          inputs,
+        const ac = resolvedOpts.AbortController
+          ? new resolvedOpts.AbortController()
+          : null;
+
+        const promise = new Promise<HTTPResult[]>((resolve, reject) => {
+          if (type === "subscription") {
+            throw new Error("Subscriptions should use wsLink");
+          }
+
+          const body = batchBody(batchOps);
+
+          const meta = {} as HTTPResult["meta"];
+          Promise.resolve(resolvedOpts.headers())
+            .then((headers) =>
+              resolvedOpts.fetch(resolvedOpts.url + "/_batch", {
+                method: "POST",
+                signal: ac?.signal,
+                body,
+                headers: {
+                  "content-type": "application/json",
+                  ...headers,
+                },
+              })
+            )
+            .then((res) => {
+              meta.response = res;
+              return res.json();
+            })
+            .then((json) => {
+              const resJSON: unknown[] = Array.isArray(json)
+                ? json
+                : batchOps.map(() => json);
+
+              resolve(
+                resJSON.map((item) => ({
+                  meta,
+                  json: item,
+                }))
+              );
+            })
+            .catch(reject);
         });
 
         return {
-          promise: promise.then((res) => {
-            const resJSON = Array.isArray(res.json)
-              ? res.json
-              : batchOps.map(() => res.json);
-
-            const result = resJSON.map((item) => ({
-              meta: res.meta,
-              json: item,
-            }));
-
-            return result;
-          }),
-          cancel,
+          promise,
+          cancel: () => {
+            ac?.abort();
+          },
         };
       };
 
@@ -0,0 +1,103 @@
+// @ts-nocheck // TODO
+
+import { createWSClient, wsLink } from "./wsLink";
+
+class FakeWebSocket {
+  static instances: FakeWebSocket[] = [];
+  sent: any[] = [];
+  listeners: Record<string, ((ev: any) => void)[]> = {};
+
+  constructor(public url: string) {
+    FakeWebSocket.instances.push(this);
+  }
+
+  addEventListener(type: string, listener: (ev: any) => void) {
+    (this.listeners[type] ??= []).push(listener);
+  }
+
+  send(data: string) {
+    const msg = JSON.parse(data);
+    this.sent.push(...(Array.isArray(msg) ? msg : [msg]));
+  }
+
+  close() {}
+
+  emit(type: string, ev: any = {}) {
+    for (const listener of this.listeners[type] ?? []) listener(ev);
+  }
+
+  respond(msg: any) {
+    this.emit("message", { data: JSON.stringify({ jsonrpc: "2.0", ...msg }) });
+  }
+}
+
+// Messages are dispatched on the next tick
+const tick = () => new Promise((resolve) => setTimeout(resolve));
+
+function setup() {
+  FakeWebSocket.instances = [];
+  const client = createWSClient({ url: "ws://localhost", WebSocket: FakeWebSocket });
+  const ws = FakeWebSocket.instances[0];
+  ws.emit("open");
+  const link = wsLink({ client })({
+    transformer: { serialize: (v) => v, deserialize: (v) => v },
+  });
+  return { ws, link };
+}
+
+test("no cancel is sent after a query resolves", async () => {
+  const { ws, link } = setup();
+  const next = jest.fn();
+  const complete = jest.fn();
+  const sub = link({
+    op: { type: "query", id: 1, input: null, path: "version", context: {} },
+  }).subscribe({ next, complete });
+  await tick();
+
+  ws.respond({ id: 1, result: { type: "response", data: "v1" } });
+  sub.unsubscribe();
+  await tick();
+
+  expect(next).toHaveBeenCalledTimes(1);
+  expect(complete).toHaveBeenCalledTimes(1);
+  expect(ws.sent.map((msg) => msg.method)).toEqual(["query"]);
+});
+
+test("an in-flight query is cancelled", async () => {
+  const { ws, link } = setup();
+  const sub = link({
+    op: { type: "query", id: 1, input: null, path: "slow", context: {} },
+  }).subscribe({});
+  await tick();
+
+  sub.unsubscribe();
+  await tick();
+
+  expect(ws.sent).toEqual([
+    { id: 1, method: "query", params: { input: null, path: "slow" } },
+    { id: 1, method: "cancel" },
+  ]);
+});
+
+test("a subscription is only stopped if the server hasn't completed it", async () => {
+  const { ws, link } = setup();
+  const complete = jest.fn();
+  const sub1 = link({
+    op: { type: "subscription", id: 1, input: null, path: "count", context: {} },
+  }).subscribe({ complete });
+  const sub2 = link({
+    op: { type: "subscription", id: 2, input: null, path: "count", context: {} },
+  }).subscribe({});
+  await tick();
+
+  ws.respond({ id: 1, result: { type: "event", data: 0 } });
+  ws.respond({ id: 1, result: { type: "complete" } });
+  sub1.unsubscribe();
+  sub2.unsubscribe();
+  await tick();
+
+  expect(complete).toHaveBeenCalledTimes(1);
+  expect(ws.sent.filter((msg) => msg.method === "subscriptionStop")).toEqual([
+    { id: 2, method: "subscriptionStop" },
+  ]);
+});
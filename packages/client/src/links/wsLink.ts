@@ -173,6 +173,12 @@ export function createWSClient(opts: WebSocketClientOptions) {
         return;
       }
 
+      // A query or mutation only gets one response and a subscription ends with `complete` so they are no longer pending.
+      // This is done before calling `next` so unsubscribing from it doesn't cancel or stop a request the server has already finished.
+      if (req.type !== "subscription" || data.result?.type === "complete") {
+        delete pendingRequests[data.id];
+      }
+
       req.callbacks.next?.(data);
       if (req.ws !== activeConnection && conn === activeConnection) {
         const oldWs = req.ws;
@@ -261,13 +267,17 @@ export function createWSClient(opts: WebSocketClientOptions) {
       outgoing = outgoing.filter((msg) => msg.id !== id);
 
       callbacks?.complete?.();
-      if (op.type === "subscription") {
-        outgoing.push({
-          id,
-          method: "subscriptionStop",
-        });
-        dispatch();
+      if (!callbacks) {
+        // The server has already responded so there is nothing to cancel or stop
+        return;
       }
+
+      // A subscription is stopped and a query or mutation which is still in-flight is cancelled
+      outgoing.push({
+        id,
+        method: op.type === "subscription" ? "subscriptionStop" : "cancel",
+      });
+      dispatch();
     };
   }
   return {
@@ -344,6 +354,14 @@ export function wsLink<TProcedures extends ProceduresDef>(
               }
             },
             next(message) {
+              if (message.result.type === "complete") {
+                // the server has finished the subscription
+                isDone = true;
+                unsub();
+                observer.complete();
+                return;
+              }
+
               const transformed = transformResult(message, runtime);
 
               if (!transformed.ok) {
@@ -5,10 +5,16 @@ export type OperationType =
   | "query"
   | "mutation"
   | "subscription"
-  | "subscriptionStop";
+  | "subscriptionStop"
+  | "cancel";
 
 // TODO
-export type ProcedureDef = { key: string; input: any; result: any };
+export type ProcedureDef = {
+  key: string;
+  input: any;
+  result: any;
+  error?: any;
+};
 
 /**
  * This type represents the Typescript bindings which are generated from the router by Rust.
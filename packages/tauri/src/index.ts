@@ -76,6 +76,12 @@ export function createTauriClient(): TRPCWebSocketClient {
         // do something?
         return;
       }
+      // A query or mutation only gets one response and a subscription ends with `complete` so they are no longer pending.
+      // This is done before calling `next` so unsubscribing from it doesn't cancel or stop a request which has already finished.
+      if (req.type !== "subscription" || data.result?.type === "complete") {
+        delete pendingRequests[data.id];
+      }
+
       req.callbacks.next?.(data);
       if ("result" in data && data.result.type === "stopped") {
         req.callbacks.complete();
@@ -110,13 +116,17 @@ export function createTauriClient(): TRPCWebSocketClient {
       delete pendingRequests[id];
       outgoing = outgoing.filter((msg) => msg.id !== id);
       callbacks?.complete?.();
-      if (op.type === "subscription") {
-        outgoing.push({
-          id,
-          method: "subscriptionStop",
-        });
-        dispatch();
+      if (!callbacks) {
+        // The backend has already responded so there is nothing to cancel or stop
+        return;
       }
+
+      // A subscription is stopped and a query or mutation which is still in-flight is cancelled
+      outgoing.push({
+        id,
+        method: op.type === "subscription" ? "subscriptionStop" : "cancel",
+      });
+      dispatch();
     };
   }
 
@@ -0,0 +1,171 @@
+use std::error;
+
+use serde_json::Value;
+
+use crate::internal::jsonrpc;
+
+#[derive(thiserror::Error, Debug)]
+pub enum CodecError {
+    #[error("error encoding message: {0}")]
+    Encode(Box<dyn error::Error + Send + Sync>),
+    #[error("error decoding message: {0}")]
+    Decode(Box<dyn error::Error + Send + Sync>),
+    #[error("the message contains more than {0} requests")]
+    TooManyRequests(usize),
+}
+
+/// a wire format for JSON-RPC requests and responses. Codecs are added to a router using [`Config::codec`](crate::Config::codec).
+///
+/// Messages are decoded straight into the request types and responses are encoded straight from the response types,
+/// so every codec must encode structs as maps to give `jsonrpc::Request` and `jsonrpc::Response` the same shape as JSON.
+pub trait Codec: Send + Sync + 'static {
+    /// the MIME type which selects the codec in the `Content-Type` and `Accept` headers of HTTP requests.
+    fn content_type(&self) -> &'static str;
+
+    /// the websocket subprotocol which selects the codec for a websocket connection.
+    fn subprotocol(&self) -> &'static str;
+
+    /// whether messages are sent in binary or text websocket frames.
+    fn is_binary(&self) -> bool;
+
+    /// decodes the input sent in the body of a HTTP request.
+    fn decode_input(&self, bytes: &[u8]) -> Result<Value, CodecError>;
+
+    /// decodes a single request or an array of requests.
+    fn decode_requests(&self, bytes: &[u8]) -> Result<jsonrpc::Requests, CodecError>;
+
+    fn encode_response(&self, response: &jsonrpc::Response) -> Result<Vec<u8>, CodecError>;
+
+    fn encode_responses(&self, responses: &[jsonrpc::Response]) -> Result<Vec<u8>, CodecError>;
+}
+
+impl dyn Codec {
+    /// decodes a single request or an array of up to `max_length` requests. Decoding stops as soon as the array is too long
+    /// and a `TooManyRequests` error is returned.
+    pub fn decode_batch(
+        &self,
+        bytes: &[u8],
+        max_length: Option<usize>,
+    ) -> Result<Vec<jsonrpc::Request>, CodecError> {
+        jsonrpc::Requests::with_max_length(max_length, || self.decode_requests(bytes))
+            .ok_or_else(|| CodecError::TooManyRequests(max_length.unwrap_or_default()))?
+            .map(|requests| requests.0)
+    }
+}
+
+/// the JSON codec which is used when a request doesn't ask for another codec.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn content_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn subprotocol(&self) -> &'static str {
+        "rspc.json"
+    }
+
+    fn is_binary(&self) -> bool {
+        false
+    }
+
+    fn decode_input(&self, bytes: &[u8]) -> Result<Value, CodecError> {
+        serde_json::from_slice(bytes).map_err(|err| CodecError::Decode(Box::new(err)))
+    }
+
+    fn decode_requests(&self, bytes: &[u8]) -> Result<jsonrpc::Requests, CodecError> {
+        serde_json::from_slice(bytes).map_err(|err| CodecError::Decode(Box::new(err)))
+    }
+
+    fn encode_response(&self, response: &jsonrpc::Response) -> Result<Vec<u8>, CodecError> {
+        serde_json::to_vec(response).map_err(|err| CodecError::Encode(Box::new(err)))
+    }
+
+    fn encode_responses(&self, responses: &[jsonrpc::Response]) -> Result<Vec<u8>, CodecError> {
+        serde_json::to_vec(responses).map_err(|err| CodecError::Encode(Box::new(err)))
+    }
+}
+
+/// the MessagePack codec - https://msgpack.org
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MsgpackCodec;
+
+#[cfg(feature = "msgpack")]
+impl Codec for MsgpackCodec {
+    fn content_type(&self) -> &'static str {
+        "application/msgpack"
+    }
+
+    fn subprotocol(&self) -> &'static str {
+        "rspc.msgpack"
+    }
+
+    fn is_binary(&self) -> bool {
+        true
+    }
+
+    fn decode_input(&self, bytes: &[u8]) -> Result<Value, CodecError> {
+        rmp_serde::from_slice(bytes).map_err(|err| CodecError::Decode(Box::new(err)))
+    }
+
+    fn decode_requests(&self, bytes: &[u8]) -> Result<jsonrpc::Requests, CodecError> {
+        rmp_serde::from_slice(bytes).map_err(|err| CodecError::Decode(Box::new(err)))
+    }
+
+    // Structs are encoded as maps instead of `rmp_serde`'s default of arrays so the responses have the same shape as JSON
+    fn encode_response(&self, response: &jsonrpc::Response) -> Result<Vec<u8>, CodecError> {
+        rmp_serde::to_vec_named(response).map_err(|err| CodecError::Encode(Box::new(err)))
+    }
+
+    fn encode_responses(&self, responses: &[jsonrpc::Response]) -> Result<Vec<u8>, CodecError> {
+        rmp_serde::to_vec_named(responses).map_err(|err| CodecError::Encode(Box::new(err)))
+    }
+}
+
+/// the CBOR codec - https://www.rfc-editor.org/rfc/rfc8949
+#[cfg(feature = "cbor")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CborCodec;
+
+#[cfg(feature = "cbor")]
+impl CborCodec {
+    fn encode<T: serde::Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, CodecError> {
+        let mut buf = Vec::new();
+        ciborium::ser::into_writer(value, &mut buf)
+            .map_err(|err| CodecError::Encode(Box::new(err)))?;
+        Ok(buf)
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl Codec for CborCodec {
+    fn content_type(&self) -> &'static str {
+        "application/cbor"
+    }
+
+    fn subprotocol(&self) -> &'static str {
+        "rspc.cbor"
+    }
+
+    fn is_binary(&self) -> bool {
+        true
+    }
+
+    fn decode_input(&self, bytes: &[u8]) -> Result<Value, CodecError> {
+        ciborium::de::from_reader(bytes).map_err(|err| CodecError::Decode(Box::new(err)))
+    }
+
+    fn decode_requests(&self, bytes: &[u8]) -> Result<jsonrpc::Requests, CodecError> {
+        ciborium::de::from_reader(bytes).map_err(|err| CodecError::Decode(Box::new(err)))
+    }
+
+    fn encode_response(&self, response: &jsonrpc::Response) -> Result<Vec<u8>, CodecError> {
+        Self::encode(response)
+    }
+
+    fn encode_responses(&self, responses: &[jsonrpc::Response]) -> Result<Vec<u8>, CodecError> {
+        Self::encode(responses)
+    }
+}
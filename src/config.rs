@@ -1,10 +1,23 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use crate::{plugins::limits::check_concurrency_limit, Codec, Metrics};
 
 /// TODO
 #[derive(Default)]
 pub struct Config {
     pub(crate) export_bindings_on_build: Option<PathBuf>,
+    pub(crate) export_rust_bindings_on_build: Option<PathBuf>,
     pub(crate) bindings_header: Option<&'static str>,
+    pub(crate) concurrency_limit: Option<usize>,
+    pub(crate) introspection: bool,
+    pub(crate) default_timeout: Option<Duration>,
+    pub(crate) subscription_idle_timeout: Option<Duration>,
+    pub(crate) metrics: Option<Metrics>,
+    pub(crate) max_body_size: Option<usize>,
+    pub(crate) max_input_length: Option<usize>,
+    pub(crate) max_message_size: Option<usize>,
+    pub(crate) max_batch_length: Option<usize>,
+    pub(crate) codecs: Vec<Arc<dyn Codec>>,
 }
 
 impl Config {
@@ -22,10 +35,93 @@ impl Config {
         self
     }
 
+    /// will export Rust bindings and a typed client for the router to a file every time the router is built.
+    /// Note: The bindings are only exported when `debug_assertions` are enabled (Rust is in debug mode).
+    pub fn export_rust_bindings<TPath>(mut self, export_path: TPath) -> Self
+    where
+        PathBuf: From<TPath>,
+    {
+        self.export_rust_bindings_on_build = Some(PathBuf::from(export_path));
+        self
+    }
+
     /// allows you to add a custom string to the top of the exported Typescript bindings file.
     /// This is useful if you want to disable ESLint or Prettier.
     pub fn set_ts_bindings_header(mut self, custom: &'static str) -> Self {
         self.bindings_header = Some(custom);
         self
     }
+
+    /// limits how many requests from a single websocket or Tauri connection are executed at the same time.
+    /// Requests over the limit are queued until a running request finishes. By default there is no limit.
+    ///
+    /// Panics if the limit is zero as no request could ever run.
+    pub fn concurrency_limit(mut self, limit: usize) -> Self {
+        self.concurrency_limit = Some(check_concurrency_limit(limit));
+        self
+    }
+
+    /// adds the reserved `rspc.introspect` query to the router which returns the [`RouterSchema`](crate::RouterSchema) of the router.
+    /// This is useful for tooling and playgrounds which need to discover the API at runtime.
+    pub fn enable_introspection(mut self) -> Self {
+        self.introspection = true;
+        self
+    }
+
+    /// sets how long a query or mutation can run for before it is cancelled and a `Timeout` error is returned.
+    /// This can be overridden for a single procedure using `.timeout()` on the procedure builder.
+    pub fn default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+
+    /// sets how long a subscription can go without yielding an item before it is stopped with a `Timeout` error.
+    /// The time is measured from when the subscription starts so it's also the deadline for the subscription's first item.
+    /// This can be overridden for a single subscription using `.timeout()` on the procedure builder.
+    pub fn subscription_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.subscription_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// records metrics about the router's procedures, websocket connections and subscriptions into `metrics`.
+    /// The same [`Metrics`] should be served using its Prometheus endpoint or [`Metrics::encode`].
+    pub fn metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// limits the size in bytes of the body of HTTP requests. Larger requests are rejected with a `PayloadTooLarge` error before they are parsed.
+    /// The body has already been read into memory by the HTTP server at this point so its own body limit should also be set.
+    pub fn max_body_size(mut self, bytes: usize) -> Self {
+        self.max_body_size = Some(bytes);
+        self
+    }
+
+    /// limits the length in bytes of the `input` parameter of HTTP `GET` requests after it's URL-decoded.
+    /// Longer inputs are rejected with a `PayloadTooLarge` error before they are parsed.
+    pub fn max_input_length(mut self, length: usize) -> Self {
+        self.max_input_length = Some(length);
+        self
+    }
+
+    /// limits the size in bytes of each websocket message. Larger messages are rejected with a `PayloadTooLarge` error before they are parsed.
+    /// Messages are received in full before they are checked and the websocket library's own limits of 64 MiB per message and 16 MiB per frame always apply.
+    pub fn max_message_size(mut self, bytes: usize) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+
+    /// limits how many requests can be sent in a single HTTP batch or websocket message.
+    /// Decoding stops at the first request over the limit and the batch is rejected with a `PayloadTooLarge` error.
+    pub fn max_batch_length(mut self, length: usize) -> Self {
+        self.max_batch_length = Some(length);
+        self
+    }
+
+    /// allows requests and responses to be encoded using `codec` instead of JSON. Over HTTP the codec is chosen by the `Content-Type` and `Accept` headers
+    /// and for websockets by the subprotocol requested when the connection is opened.
+    pub fn codec(mut self, codec: impl Codec) -> Self {
+        self.codecs.push(Arc::new(codec));
+        self
+    }
 }
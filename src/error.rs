@@ -1,18 +1,30 @@
-use std::{error, fmt, sync::Arc};
+use std::{error, fmt, sync::Arc, time::Duration};
 
 use serde::Serialize;
+use serde_json::Value;
 use specta::Type;
 
-use crate::internal::jsonrpc::JsonRPCError;
+use crate::internal::{jsonrpc::JsonRPCError, ValidationError};
 
 #[derive(thiserror::Error, Debug)]
 pub enum ExecError {
     #[error("the requested operation '{0}' is not supported by this server")]
     OperationNotFound(String),
+    #[error("the procedure '{0}' doesn't take and return the types it was called with")]
+    ProcedureTypeMismatch(String),
     #[error("error deserializing procedure arguments: {0}")]
-    DeserializingArgErr(serde_json::Error),
+    DeserializingArgErr(
+        serde_path_to_error::Error<serde_json::Error>,
+        Option<String>, // The Typescript type expected at the path of the error
+    ),
+    #[error("procedure arguments failed validation")]
+    ValidationErr(Vec<ValidationError>),
+    #[error("error serializing procedure arguments: {0}")]
+    SerializingArgErr(serde_json::Error),
     #[error("error serializing procedure result: {0}")]
     SerializingResultErr(serde_json::Error),
+    #[error("error deserializing procedure result: {0}")]
+    DeserializingResultErr(serde_json::Error),
     #[error("error in axum extractor")]
     AxumExtractorError,
     #[error("invalid JSON-RPC version")]
@@ -25,6 +37,16 @@ pub enum ExecError {
     ErrSubscriptionWithNullId,
     #[error("error creating subscription with duplicate id")]
     ErrSubscriptionDuplicateId,
+    #[error("error executing request with the id of a request which is still running")]
+    ErrRequestDuplicateId,
+    #[error("the request was cancelled by the client")]
+    ErrRequestCancelled,
+    #[error("the procedure did not complete within its timeout")]
+    Timeout,
+    #[error("the procedure was called too many times")]
+    TooManyRequests(Option<Duration>), // How long until the procedure can be called again
+    #[error("the request exceeded the '{0}' limit of {1}")]
+    PayloadTooLarge(&'static str, usize), // The name of the limit which was exceeded and its value
 }
 
 impl From<ExecError> for Error {
@@ -34,42 +56,112 @@ impl From<ExecError> for Error {
                 code: ErrorCode::NotFound,
                 message: "the requested operation is not supported by this server".to_string(),
                 cause: None,
+                data: None,
+            },
+            ExecError::ProcedureTypeMismatch(_) => Error {
+                code: ErrorCode::InternalServerError,
+                message: "the procedure doesn't take and return the types it was called with"
+                    .into(),
+                cause: None,
+                data: None,
             },
-            ExecError::DeserializingArgErr(err) => Error {
+            ExecError::DeserializingArgErr(err, expected) => Error {
                 code: ErrorCode::BadRequest,
                 message: "error deserializing procedure arguments".to_string(),
+                data: Some(serde_json::json!({
+                    "path": err.path().to_string(),
+                    "expected": expected,
+                    "message": err.inner().to_string(),
+                })),
+                cause: Some(Arc::new(err)),
+            },
+            ExecError::ValidationErr(errors) => Error {
+                code: ErrorCode::BadRequest,
+                message: "procedure arguments failed validation".to_string(),
+                data: Some(serde_json::json!({ "errors": errors })),
+                cause: None,
+            },
+            ExecError::SerializingArgErr(err) => Error {
+                code: ErrorCode::InternalServerError,
+                message: "error serializing procedure arguments".to_string(),
+                cause: Some(Arc::new(err)),
+                data: None,
+            },
+            ExecError::DeserializingResultErr(err) => Error {
+                code: ErrorCode::InternalServerError,
+                message: "error deserializing procedure result".to_string(),
                 cause: Some(Arc::new(err)),
+                data: None,
             },
             ExecError::SerializingResultErr(err) => Error {
                 code: ErrorCode::InternalServerError,
                 message: "error serializing procedure result".to_string(),
                 cause: Some(Arc::new(err)),
+                data: None,
             },
             ExecError::AxumExtractorError => Error {
                 code: ErrorCode::BadRequest,
                 message: "Error running Axum extractors on the HTTP request".into(),
                 cause: None,
+                data: None,
             },
             ExecError::InvalidJsonRpcVersion => Error {
                 code: ErrorCode::BadRequest,
                 message: "invalid JSON-RPC version".into(),
                 cause: None,
+                data: None,
             },
             ExecError::ErrResolverError(err) => err,
             ExecError::UnsupportedMethod(_) => Error {
                 code: ErrorCode::BadRequest,
                 message: "unsupported metho".into(),
                 cause: None,
+                data: None,
             },
             ExecError::ErrSubscriptionWithNullId => Error {
                 code: ErrorCode::BadRequest,
                 message: "error creating subscription with null request id".into(),
                 cause: None,
+                data: None,
             },
             ExecError::ErrSubscriptionDuplicateId => Error {
                 code: ErrorCode::BadRequest,
                 message: "error creating subscription with duplicate id".into(),
                 cause: None,
+                data: None,
+            },
+            ExecError::ErrRequestDuplicateId => Error {
+                code: ErrorCode::BadRequest,
+                message: "error executing request with the id of a request which is still running"
+                    .into(),
+                cause: None,
+                data: None,
+            },
+            ExecError::ErrRequestCancelled => Error {
+                code: ErrorCode::ClientClosedRequest,
+                message: "the request was cancelled by the client".into(),
+                cause: None,
+                data: None,
+            },
+            ExecError::Timeout => Error {
+                code: ErrorCode::Timeout,
+                message: "the procedure did not complete within its timeout".into(),
+                cause: None,
+                data: None,
+            },
+            ExecError::TooManyRequests(retry_after) => Error {
+                code: ErrorCode::TooManyRequests,
+                message: "the procedure was called too many times".into(),
+                cause: None,
+                data: retry_after.map(|retry_after| {
+                    serde_json::json!({ "retryAfter": retry_after.as_secs_f64().ceil() as u64 })
+                }),
+            },
+            ExecError::PayloadTooLarge(limit, max) => Error {
+                code: ErrorCode::PayloadTooLarge,
+                message: "the request is too large".into(),
+                cause: None,
+                data: Some(serde_json::json!({ "limit": limit, "max": max })),
             },
         }
     }
@@ -86,6 +178,10 @@ impl From<ExecError> for JsonRPCError {
 pub enum ExportError {
     #[error("IO error exporting bindings: {0}")]
     IOErr(#[from] std::io::Error),
+    #[error("procedures '{0}' and '{1}' both map to the Rust method '{2}'")]
+    RustIdentCollision(String, String, String),
+    #[error("error exporting type to Rust: {0}")]
+    RustTypeErr(String),
 }
 
 #[derive(Debug, Clone, Serialize, Type)]
@@ -95,6 +191,7 @@ pub struct Error {
     pub(crate) message: String,
     #[serde(skip)]
     pub(crate) cause: Option<Arc<dyn std::error::Error + Send + Sync>>, // We are using `Arc` instead of `Box` so we can clone the error cause `Clone` isn't dyn safe.
+    pub(crate) data: Option<Value>,
 }
 
 impl From<Error> for JsonRPCError {
@@ -102,7 +199,7 @@ impl From<Error> for JsonRPCError {
         JsonRPCError {
             code: err.code.to_status_code() as i32,
             message: err.message,
-            data: None,
+            data: err.data,
         }
     }
 }
@@ -129,6 +226,7 @@ impl Error {
             code,
             message,
             cause: None,
+            data: None,
         }
     }
 
@@ -140,6 +238,26 @@ impl Error {
             code,
             message,
             cause: Some(Arc::new(cause)),
+            data: None,
+        }
+    }
+
+    /// attaches a payload to the error which is sent to the client in the `data` field of the error. Use `.error::<T>()` on the procedure builder to export the type of the payload to Typescript.
+    /// If the payload fails to serialize the error is replaced with an `InternalServerError` caused by the serialization error.
+    pub fn with_data<TData>(mut self, data: TData) -> Self
+    where
+        TData: Serialize + Type,
+    {
+        match serde_json::to_value(data) {
+            Ok(data) => {
+                self.data = Some(data);
+                self
+            }
+            Err(err) => Self::with_cause(
+                ErrorCode::InternalServerError,
+                "error serializing error data".into(),
+                err,
+            ),
         }
     }
 }
@@ -156,6 +274,7 @@ pub enum ErrorCode {
     PreconditionFailed,
     PayloadTooLarge,
     MethodNotSupported,
+    TooManyRequests,
     ClientClosedRequest,
     InternalServerError,
 }
@@ -172,6 +291,7 @@ impl ErrorCode {
             ErrorCode::PreconditionFailed => 412,
             ErrorCode::PayloadTooLarge => 413,
             ErrorCode::MethodNotSupported => 405,
+            ErrorCode::TooManyRequests => 429,
             ErrorCode::ClientClosedRequest => 499,
             ErrorCode::InternalServerError => 500,
         }
@@ -188,6 +308,7 @@ impl ErrorCode {
             412 => Some(ErrorCode::PreconditionFailed),
             413 => Some(ErrorCode::PayloadTooLarge),
             405 => Some(ErrorCode::MethodNotSupported),
+            429 => Some(ErrorCode::TooManyRequests),
             499 => Some(ErrorCode::ClientClosedRequest),
             500 => Some(ErrorCode::InternalServerError),
             _ => None,
@@ -3,22 +3,105 @@ pub use super::httpz_extractors::*;
 use futures::{SinkExt, StreamExt};
 use httpz::{
     cookie::CookieJar,
-    http::{Method, Response, StatusCode},
+    http::{header, HeaderValue, Method, Response, StatusCode},
     ws::{Message, WebsocketUpgrade},
     Endpoint, GenericEndpoint, HttpEndpoint, HttpResponse, Request,
 };
 use serde_json::Value;
-use std::{collections::HashMap, sync::Arc};
+use std::sync::Arc;
 use tokio::sync::mpsc;
 
 use crate::{
     internal::{
-        jsonrpc::{self, handle_json_rpc, RequestId, Sender, SubscriptionMap},
-        ProcedureKind,
+        jsonrpc::{
+            self, handle_batched_json_rpc, handle_json_rpc, RequestId, ResponseInner, Sender,
+            Sender2, SubscriptionMap,
+        },
+        Connection, ProcedureKind,
     },
-    Router,
+    Codec, CodecError, Config, ErrorCode, ExecError, JsonCodec, LiveRouter, Metrics, Router,
 };
 
+#[cfg(feature = "tracing")]
+use crate::internal::TraceParent;
+
+/// returns a `PayloadTooLarge` error if `len` exceeds the router's `limit`.
+fn check_limit(name: &'static str, limit: Option<usize>, len: usize) -> Result<(), ExecError> {
+    match limit {
+        Some(limit) if len > limit => Err(ExecError::PayloadTooLarge(name, limit)),
+        _ => Ok(()),
+    }
+}
+
+/// returns a `PayloadTooLarge` error if the body of a HTTP request is larger than the router's `max_body_size`.
+/// The `Content-Length` header is checked first so a request which declares an oversized body is rejected without looking at it.
+// httpz buffers the whole body before the request reaches rspc so the body can't be read through a capped reader here, the server's own body limit should be set as well.
+fn check_body_size(config: &Config, req: &Request) -> Result<(), ExecError> {
+    let content_length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    if let Some(content_length) = content_length {
+        check_limit("maxBodySize", config.max_body_size, content_length)?;
+    }
+
+    check_limit("maxBodySize", config.max_body_size, req.body().len())
+}
+
+/// serializes the response to a request which failed before it could be executed.
+fn error_body(codec: &dyn Codec, err: ExecError) -> Vec<u8> {
+    codec
+        .encode_response(&jsonrpc::Response {
+            jsonrpc: "2.0",
+            id: RequestId::Null,
+            result: ResponseInner::Error(err.into()),
+        })
+        .unwrap_or_else(|_| b"[]".to_vec())
+}
+
+/// the codecs added to the router followed by JSON which is always supported.
+fn codecs(config: &Config) -> impl Iterator<Item = Arc<dyn Codec>> + '_ {
+    let json: Arc<dyn Codec> = Arc::new(JsonCodec);
+    config.codecs.iter().cloned().chain(std::iter::once(json))
+}
+
+/// finds the first codec named by a `Content-Type`, `Accept` or `Sec-WebSocket-Protocol` header.
+fn negotiate_codec(
+    config: &Config,
+    req: &Request,
+    header: &str,
+    name: fn(&dyn Codec) -> &'static str,
+) -> Option<Arc<dyn Codec>> {
+    req.headers()
+        .get(header)
+        .and_then(|header| header.to_str().ok())?
+        .split(',')
+        .filter_map(|value| value.split(';').next())
+        .find_map(|value| {
+            codecs(config).find(|codec| name(&**codec).eq_ignore_ascii_case(value.trim()))
+        })
+}
+
+/// returns the codec the body of a HTTP request is encoded with and the codec the response should be encoded with.
+/// The response uses the codec of the request unless the `Accept` header asks for another one.
+fn http_codecs(config: &Config, req: &Request) -> (Arc<dyn Codec>, Arc<dyn Codec>) {
+    let decoder = negotiate_codec(config, req, "Content-Type", |codec| codec.content_type())
+        .unwrap_or_else(|| Arc::new(JsonCodec));
+    let encoder = negotiate_codec(config, req, "Accept", |codec| codec.content_type())
+        .unwrap_or_else(|| decoder.clone());
+    (decoder, encoder)
+}
+
+/// parses the W3C trace context of a request so the procedures it executes are part of the caller's trace.
+#[cfg(feature = "tracing")]
+fn trace_parent(req: &Request) -> Option<TraceParent> {
+    req.headers()
+        .get("traceparent")
+        .and_then(|header| header.to_str().ok())
+        .and_then(TraceParent::parse)
+}
+
 impl<TCtx, TMeta> Router<TCtx, TMeta>
 where
     TCtx: Send + Sync + 'static,
@@ -28,7 +111,7 @@ where
         self: Arc<Self>,
         ctx_fn: TCtxFn,
     ) -> Endpoint<impl HttpEndpoint> {
-        self.internal_endpoint(None, ctx_fn)
+        LiveRouter::new(self).internal_endpoint(None, ctx_fn)
     }
 
     pub fn endpoint_with_prefix<
@@ -38,6 +121,48 @@ where
         self: Arc<Self>,
         url_prefix: &'static str,
         ctx_fn: TCtxFn,
+    ) -> Endpoint<impl HttpEndpoint> {
+        LiveRouter::new(self).internal_endpoint(Some(url_prefix), ctx_fn)
+    }
+}
+
+impl Metrics {
+    /// creates an endpoint which serves the metrics in the Prometheus text exposition format.
+    pub fn endpoint(self) -> Endpoint<impl HttpEndpoint> {
+        GenericEndpoint::new([Method::GET], move |_req: Request| {
+            let metrics = self.clone();
+            async move {
+                let mut resp = Response::new(metrics.encode().into_bytes());
+                resp.headers_mut().insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("text/plain; version=0.0.4"),
+                );
+                resp
+            }
+        })
+    }
+}
+
+impl<TCtx, TMeta> LiveRouter<TCtx, TMeta>
+where
+    TCtx: Send + Sync + 'static,
+    TMeta: Send + Sync + 'static,
+{
+    /// creates an endpoint which executes each request on the current router. See [`LiveRouter::swap`].
+    pub fn endpoint<TCtxFnMarker: Send + Sync + 'static, TCtxFn: TCtxFunc<TCtx, TCtxFnMarker>>(
+        self,
+        ctx_fn: TCtxFn,
+    ) -> Endpoint<impl HttpEndpoint> {
+        self.internal_endpoint(None, ctx_fn)
+    }
+
+    pub fn endpoint_with_prefix<
+        TCtxFnMarker: Send + Sync + 'static,
+        TCtxFn: TCtxFunc<TCtx, TCtxFnMarker>,
+    >(
+        self,
+        url_prefix: &'static str,
+        ctx_fn: TCtxFn,
     ) -> Endpoint<impl HttpEndpoint> {
         self.internal_endpoint(Some(url_prefix), ctx_fn)
     }
@@ -46,22 +171,28 @@ where
         TCtxFnMarker: Send + Sync + 'static,
         TCtxFn: TCtxFunc<TCtx, TCtxFnMarker>,
     >(
-        self: Arc<Self>,
+        self,
         url_prefix: Option<&'static str>,
         ctx_fn: TCtxFn,
     ) -> Endpoint<impl HttpEndpoint> {
         GenericEndpoint::new([Method::GET, Method::POST], move |req: Request| {
             // TODO: It would be nice if these clones weren't per request. Maybe httpz could allow context to be generated per thread and stored in thread local?
-            let router = self.clone();
+            let live = self.clone();
             let ctx_fn = ctx_fn.clone();
 
             async move {
                 let websocket_url = format!("{}/ws", url_prefix.unwrap_or("/rspc")); // TODO: Match on variable in URL and not not the entire URL??
+                let batch_url = format!("{}/_batch", url_prefix.unwrap_or("/rspc"));
                 let cookies = req.cookies();
 
                 match (req.method(), req.uri().path()) {
                     (&Method::GET, url) if url == websocket_url => {
-                        handle_websocket(ctx_fn, req, cookies, router).into_response()
+                        handle_live_websocket(ctx_fn, req, cookies, live).into_response()
+                    }
+                    (&Method::POST, url) if url == batch_url => {
+                        handle_http_batch(ctx_fn, req, cookies, &live.get())
+                            .await
+                            .into_response()
                     }
                     (&Method::GET, _) => handle_http(
                         ctx_fn,
@@ -69,7 +200,7 @@ where
                         ProcedureKind::Query,
                         req,
                         cookies,
-                        &router,
+                        &live.get(),
                     )
                     .await
                     .into_response(),
@@ -79,7 +210,7 @@ where
                         ProcedureKind::Mutation,
                         req,
                         cookies,
-                        &router,
+                        &live.get(),
                     )
                     .await
                     .into_response(),
@@ -100,9 +231,39 @@ pub async fn handle_http<TCtx, TMeta, TCtxFn, TCtxFnMarker>(
 ) -> impl HttpResponse
 where
     TCtx: Send + Sync + 'static,
+    TMeta: Send + Sync + 'static,
     TCtxFn: TCtxFunc<TCtx, TCtxFnMarker>,
 {
+    #[cfg(feature = "tracing")]
+    let span = TraceParent::span(trace_parent(&req).as_ref(), "http");
+
     let uri = req.uri().clone();
+    let (decoder, encoder) = http_codecs(&router.config, &req);
+
+    // The parameter is found the same way as it's parsed so the limit can't be avoided by percent-encoding its name
+    let query_input = match *req.method() {
+        Method::GET => req
+            .query_pairs()
+            .and_then(|mut params| params.find(|e| e.0 == "input").map(|e| e.1.into_owned())),
+        _ => None,
+    };
+    let size = match *req.method() {
+        Method::GET => check_limit(
+            "maxInputLength",
+            router.config.max_input_length,
+            query_input.as_ref().map(String::len).unwrap_or_default(),
+        ),
+        _ => check_body_size(&router.config, &req),
+    };
+    if let Err(err) = size {
+        return Ok((
+            Response::builder()
+                .status(StatusCode::PAYLOAD_TOO_LARGE)
+                .header("Content-Type", encoder.content_type())
+                .body(error_body(&*encoder, err))?,
+            cookies,
+        ));
+    }
 
     let key = match uri.path().strip_prefix(url_prefix) {
         Some(key) => key,
@@ -118,13 +279,11 @@ where
     };
 
     let input = match *req.method() {
-        Method::GET => req
-            .query_pairs()
-            .and_then(|mut params| params.find(|e| e.0 == "input").map(|e| e.1))
-            .map(|v| serde_json::from_str(&v))
+        Method::GET => query_input
+            .map(|v| serde_json::from_str(&v).map_err(|err| CodecError::Decode(Box::new(err))))
             .unwrap_or(Ok(None as Option<Value>)),
         Method::POST => (!req.body().is_empty())
-            .then(|| serde_json::from_slice(req.body()))
+            .then(|| decoder.decode_input(req.body()).map(Some))
             .unwrap_or(Ok(None)),
         _ => unreachable!(),
     };
@@ -150,13 +309,12 @@ where
         }
     };
 
-    #[cfg(feature = "tracing")]
-    tracing::debug!(
-        "Executing operation '{}' with key '{}' with params {:?}",
-        kind.to_str(),
-        key,
-        input
-    );
+    let deprecation = match kind {
+        ProcedureKind::Query => router.queries.store.get(key),
+        ProcedureKind::Mutation => router.mutations.store.get(key),
+        ProcedureKind::Subscription => None,
+    }
+    .and_then(|procedure| procedure.ty.deprecation.clone());
 
     let mut resp = Sender::Response(None);
 
@@ -189,7 +347,7 @@ where
         }
     };
 
-    handle_json_rpc(
+    let fut = handle_json_rpc(
         ctx,
         jsonrpc::Request {
             jsonrpc: None,
@@ -220,16 +378,52 @@ where
         router,
         &mut resp,
         &mut SubscriptionMap::None,
-    )
-    .await;
+    );
+    #[cfg(feature = "tracing")]
+    let fut = tracing::Instrument::instrument(fut, span);
+    fut.await;
 
     match resp {
         Sender::Response(Some(resp)) => Ok((
-            match serde_json::to_vec(&resp) {
-                Ok(v) => Response::builder()
-                    .status(StatusCode::OK)
-                    .header("Content-Type", "application/json")
-                    .body(v)?,
+            match encoder.encode_response(&resp) {
+                Ok(v) => {
+                    let mut builder =
+                        Response::builder().header("Content-Type", encoder.content_type());
+
+                    // Deprecation headers - https://datatracker.ietf.org/doc/draft-ietf-httpapi-deprecation-header and https://www.rfc-editor.org/rfc/rfc8594
+                    if let Some(deprecation) = deprecation {
+                        builder = builder.header("Deprecation", "true");
+                        if let Some(sunset) = deprecation.sunset {
+                            builder = builder.header("Sunset", sunset);
+                        }
+                        if let Some(key) = deprecation.replaced_by {
+                            builder = builder.header(
+                                "Link",
+                                format!("<{}{}>; rel=\"successor-version\"", url_prefix, key),
+                            );
+                        }
+                    }
+
+                    // Limited requests respond with a `429` so clients and proxies honour the `Retry-After` header - https://www.rfc-editor.org/rfc/rfc9110#field.retry-after
+                    match &resp.result {
+                        ResponseInner::Error(err)
+                            if err.code == ErrorCode::TooManyRequests.to_status_code() as i32 =>
+                        {
+                            builder = builder.status(StatusCode::TOO_MANY_REQUESTS);
+                            let retry_after = err
+                                .data
+                                .as_ref()
+                                .and_then(|data| data.get("retryAfter"))
+                                .and_then(Value::as_u64);
+                            if let Some(retry_after) = retry_after {
+                                builder = builder.header("Retry-After", retry_after.to_string());
+                            }
+                        }
+                        _ => builder = builder.status(StatusCode::OK),
+                    }
+
+                    builder.body(v)?
+                }
                 Err(_err) => {
                     #[cfg(feature = "tracing")]
                     tracing::error!("Error serializing response: {}", _err);
@@ -246,12 +440,141 @@ where
     }
 }
 
+/// executes a batch of JSON-RPC requests sent as a JSON array in the body of a `POST` request.
+/// The requests are run concurrently and an array of responses is returned in the same order as the requests.
+pub async fn handle_http_batch<TCtx, TMeta, TCtxFn, TCtxFnMarker>(
+    ctx_fn: TCtxFn,
+    req: Request,
+    cookies: CookieJar,
+    router: &Arc<Router<TCtx, TMeta>>,
+) -> impl HttpResponse
+where
+    TCtx: Send + Sync + 'static,
+    TMeta: Send + Sync + 'static,
+    TCtxFn: TCtxFunc<TCtx, TCtxFnMarker>,
+{
+    #[cfg(feature = "tracing")]
+    let span = TraceParent::span(trace_parent(&req).as_ref(), "http");
+
+    let (decoder, encoder) = http_codecs(&router.config, &req);
+    if let Err(err) = check_body_size(&router.config, &req) {
+        return Ok((
+            Response::builder()
+                .status(StatusCode::PAYLOAD_TOO_LARGE)
+                .header("Content-Type", encoder.content_type())
+                .body(error_body(&*encoder, err))?,
+            cookies,
+        ));
+    }
+
+    // The batch length is checked while decoding so an oversized batch is rejected before it's decoded in full
+    let requests = match decoder.decode_batch(req.body(), router.config.max_batch_length) {
+        Ok(v) => v,
+        Err(CodecError::TooManyRequests(max)) => {
+            return Ok((
+                Response::builder()
+                    .status(StatusCode::PAYLOAD_TOO_LARGE)
+                    .header("Content-Type", encoder.content_type())
+                    .body(error_body(
+                        &*encoder,
+                        ExecError::PayloadTooLarge("maxBatchLength", max),
+                    ))?,
+                cookies,
+            ));
+        }
+        Err(_err) => {
+            #[cfg(feature = "tracing")]
+            tracing::error!("Error parsing batch request: {}", _err);
+
+            return Ok((
+                Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .header("Content-Type", "application/json")
+                    .body(b"[]".to_vec())?,
+                cookies,
+            ));
+        }
+    };
+
+    #[cfg(not(feature = "workers"))]
+    let mut req = httpz::axum::axum::extract::RequestParts::new(req.into());
+
+    let mut responses = Vec::with_capacity(requests.len());
+    for request in requests {
+        #[cfg(not(feature = "workers"))]
+        let ctx = match ctx_fn.exec(&mut req) {
+            TCtxFuncResult::Value(v) => v,
+            TCtxFuncResult::Future(v) => v.await,
+        };
+        #[cfg(feature = "workers")]
+        let ctx = match ctx_fn.exec() {
+            TCtxFuncResult::Value(v) => v,
+            TCtxFuncResult::Future(v) => v.await,
+        };
+
+        responses.push(async move {
+            match ctx {
+                Ok(ctx) => handle_batched_json_rpc(ctx, request, router).await,
+                Err(err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!("Error executing context function: {}", err);
+
+                    jsonrpc::Response {
+                        jsonrpc: "2.0",
+                        id: request.id,
+                        result: ResponseInner::Error(err.into()),
+                    }
+                }
+            }
+        });
+    }
+
+    let responses = futures::future::join_all(responses);
+    #[cfg(feature = "tracing")]
+    let responses = tracing::Instrument::instrument(responses, span);
+    let responses = responses.await;
+
+    Ok((
+        match encoder.encode_responses(&responses) {
+            Ok(v) => Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", encoder.content_type())
+                .body(v)?,
+            Err(_err) => {
+                #[cfg(feature = "tracing")]
+                tracing::error!("Error serializing batch response: {}", _err);
+
+                Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .header("Content-Type", "application/json")
+                    .body(b"[]".to_vec())?
+            }
+        },
+        cookies,
+    ))
+}
+
 pub fn handle_websocket<TCtx, TMeta, TCtxFn, TCtxFnMarker>(
     ctx_fn: TCtxFn,
     req: Request,
     cookies: CookieJar,
     router: Arc<Router<TCtx, TMeta>>,
 ) -> impl HttpResponse
+where
+    TCtx: Send + Sync + 'static,
+    TMeta: Send + Sync + 'static,
+    TCtxFn: TCtxFunc<TCtx, TCtxFnMarker>,
+{
+    handle_live_websocket(ctx_fn, req, cookies, LiveRouter::new(router))
+}
+
+/// handles a websocket connection where every incoming request is executed on the router which is current when it's received.
+pub fn handle_live_websocket<TCtx, TMeta, TCtxFn, TCtxFnMarker>(
+    ctx_fn: TCtxFn,
+    req: Request,
+    cookies: CookieJar,
+    live: LiveRouter<TCtx, TMeta>,
+) -> impl HttpResponse
 where
     TCtx: Send + Sync + 'static,
     TMeta: Send + Sync + 'static,
@@ -260,6 +583,10 @@ where
     #[cfg(feature = "tracing")]
     tracing::debug!("Accepting websocket connection");
 
+    // Every procedure executed over the websocket is part of the trace of the upgrade request
+    #[cfg(all(feature = "tracing", feature = "axum"))]
+    let span = TraceParent::span(trace_parent(&req).as_ref(), "websocket");
+
     #[cfg(not(feature = "axum"))]
     return {
         println!("Sorry websocket are not supported on your platform yet!");
@@ -268,110 +595,169 @@ where
             .body(vec![])?)
     };
 
+    // Binary messages are encoded with the first subprotocol the client asked for which the router supports
     #[cfg(feature = "axum")]
-    WebsocketUpgrade::from_req_with_cookies(req, cookies, move |req, mut socket| async move {
-        use httpz::axum::axum::extract::RequestParts;
+    let codec = negotiate_codec(
+        &live.get().config,
+        &req,
+        "Sec-WebSocket-Protocol",
+        |codec| codec.subprotocol(),
+    );
+    #[cfg(feature = "axum")]
+    let subprotocol = codec.as_ref().map(|codec| codec.subprotocol());
 
-        let mut subscriptions = HashMap::new();
-        let (mut tx, mut rx) = mpsc::channel::<jsonrpc::Response>(100);
-        let mut req = RequestParts::new(req.into());
+    #[cfg(feature = "axum")]
+    let mut resp = WebsocketUpgrade::from_req_with_cookies(req, cookies, move |req, socket| async move {
+        use httpz::axum::axum::extract::RequestParts;
 
-        loop {
-            tokio::select! {
-                biased; // Note: Order is important here
-                msg = rx.recv() => {
-                    match socket.send(Message::Text(match serde_json::to_string(&msg) {
-                        Ok(v) => v,
-                        Err(_err) => {
-                            #[cfg(feature = "tracing")]
-                            tracing::error!("Error serializing websocket message: {}", _err);
+        let codec = codec.unwrap_or_else(|| Arc::new(JsonCodec));
 
-                            continue;
-                        }
-                    })).await {
-                        Ok(_) => {}
-                        Err(_err) => {
+        let (tx, mut rx) = mpsc::channel::<jsonrpc::Response>(100);
+        let mut req = RequestParts::new(req.into());
+        let _connection = live.get().config.metrics.as_ref().map(Metrics::connection);
+        let conn = Connection::new(live.get().config.concurrency_limit);
+        #[cfg(feature = "tracing")]
+        let conn = conn.instrument(span);
+
+        // Responses are written on their own task so a full channel can't stop the connection from reading the messages which would drain it
+        let (mut sink, mut stream) = socket.split();
+        let writer = tokio::spawn(async move {
+            while let Some(msg) = rx.recv().await {
+                let msg = codec.encode_response(&msg).and_then(|v| match codec.is_binary() {
+                    true => Ok(Message::Binary(v)),
+                    false => String::from_utf8(v)
+                        .map(Message::Text)
+                        .map_err(|err| CodecError::Encode(Box::new(err))),
+                });
+
+                match msg {
+                    Ok(msg) => {
+                        if let Err(_err) = sink.send(msg).await {
                             #[cfg(feature = "tracing")]
                             tracing::error!("Error sending websocket message: {}", _err);
-
-                            continue;
                         }
                     }
+                    Err(_err) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::error!("Error serializing websocket message: {}", _err);
+                    }
                 }
-                msg = socket.next() => {
-                    match msg {
-                        Some(Ok(msg) )=> {
-                           let res = match msg {
-                                Message::Text(text) => serde_json::from_str::<Value>(&text),
-                                Message::Binary(binary) => serde_json::from_slice(&binary),
-                                Message::Ping(_) | Message::Pong(_) | Message::Close(_) => {
-                                    continue;
-                                }
-                                Message::Frame(_) => unreachable!(),
-                            };
-
-                            match res.and_then(|v| match v.is_array() {
-                                    true => serde_json::from_value::<Vec<jsonrpc::Request>>(v),
-                                    false => serde_json::from_value::<jsonrpc::Request>(v).map(|v| vec![v]),
-                                }) {
-                                Ok(reqs) => {
-                                    for request in reqs {
-                                        #[cfg(feature = "workers")]
-                                        compile_error!("You can't have the 'axum' and 'workers' features enabled at the same time!");
-                                        #[cfg(not(feature = "workers"))]
-                                        {
-                                            let ctx = match ctx_fn.exec(&mut req) {
-                                                TCtxFuncResult::Value(v) => v,
-                                                TCtxFuncResult::Future(v) => v.await,
-                                            };
-
-
-                                            handle_json_rpc(match ctx {
-                                                Ok(v) => v,
-                                                Err(_err) => {
-                                                    #[cfg(feature = "tracing")]
-                                                    tracing::error!("Error executing context function: {}", _err);
-
-                                                    continue;
-                                                }
-                                            }, request, &router, &mut Sender::Channel(&mut tx),
-                                            &mut SubscriptionMap::Ref(&mut subscriptions)).await;
-                                        }
-                                    }
-                                },
-                                Err(_err) => {
-                                    #[cfg(feature = "tracing")]
-                                    tracing::error!("Error parsing websocket message: {}", _err);
-
-                                    // TODO: Send report of error to frontend
+            }
+        });
+
+        while let Some(msg) = stream.next().await {
+            match msg {
+                Ok(msg) => {
+                    let (max_message_size, max_batch_length) = {
+                        let router = live.get();
+                        (router.config.max_message_size, router.config.max_batch_length)
+                    };
+                    // The websocket library reads the whole message before this check and httpz doesn't expose its config so its default limits
+                    // of 64 MiB per message and 16 MiB per frame still apply to larger messages.
+                    let size = match &msg {
+                        Message::Text(text) => text.len(),
+                        Message::Binary(binary) => binary.len(),
+                        _ => 0,
+                    };
+                    if let Err(err) = check_limit("maxMessageSize", max_message_size, size) {
+                        let _ = tx.send(jsonrpc::Response {
+                            jsonrpc: "2.0",
+                            id: RequestId::Null,
+                            result: ResponseInner::Error(err.into()),
+                        }).await;
+                        continue;
+                    }
 
-                                    println!("Error in websocket: {}", _err);
+                    // Text messages are always JSON and binary messages use the connection's codec
+                    let json: &dyn Codec = &JsonCodec;
+                    let res = match msg {
+                        Message::Text(text) => json.decode_batch(text.as_bytes(), max_batch_length),
+                        Message::Binary(binary) => codec.decode_batch(&binary, max_batch_length),
+                        Message::Ping(_) | Message::Pong(_) | Message::Close(_) => {
+                            continue;
+                        }
+                        Message::Frame(_) => unreachable!(),
+                    };
+
+                    match res {
+                        Ok(reqs) => {
+                            for request in reqs {
+                                // Requests run on the router which is current when they are received so a swap doesn't affect in-flight subscriptions.
+                                let router = live.get();
+
+                                #[cfg(feature = "workers")]
+                                compile_error!("You can't have the 'axum' and 'workers' features enabled at the same time!");
+                                #[cfg(not(feature = "workers"))]
+                                {
+                                    let ctx = match ctx_fn.exec(&mut req) {
+                                        TCtxFuncResult::Value(v) => v,
+                                        TCtxFuncResult::Future(v) => v.await,
+                                    };
+
+                                    let ctx = match ctx {
+                                        Ok(v) => v,
+                                        Err(_err) => {
+                                            #[cfg(feature = "tracing")]
+                                            tracing::error!("Error executing context function: {}", _err);
+
+                                            continue;
+                                        }
+                                    };
 
-                                    continue;
+                                    conn.handle(ctx, request, &router, Sender2::Channel(tx.clone())).await;
                                 }
-                            };
+                            }
+                        },
+                        Err(CodecError::TooManyRequests(max)) => {
+                            let _ = tx.send(jsonrpc::Response {
+                                jsonrpc: "2.0",
+                                id: RequestId::Null,
+                                result: ResponseInner::Error(ExecError::PayloadTooLarge("maxBatchLength", max).into()),
+                            }).await;
                         }
-                        Some(Err(_err)) => {
+                        Err(_err) => {
                             #[cfg(feature = "tracing")]
-                            tracing::error!("Error in websocket: {}", _err);
-
-                            println!("Error in websocket: {}", _err);
+                            tracing::error!("Error parsing websocket message: {}", _err);
 
                             // TODO: Send report of error to frontend
 
+                            println!("Error in websocket: {}", _err);
+
                             continue;
-                        },
-                        None => {
-                            #[cfg(feature = "tracing")]
-                            tracing::debug!("Shutting down websocket connection");
+                        }
+                    };
+                }
+                Err(_err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!("Error in websocket: {}", _err);
 
-                            // TODO: Send report of error to frontend
+                    println!("Error in websocket: {}", _err);
 
-                            return;
-                        },
-                    }
-                }
+                    // TODO: Send report of error to frontend
+
+                    continue;
+                },
             }
         }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Shutting down websocket connection");
+
+        // Cancel all outstanding work for the connection
+        conn.close().await;
+        writer.abort();
     })
+    .into_response();
+
+    // The chosen subprotocol has to be sent back in the upgrade response or the client will close the connection
+    #[cfg(feature = "axum")]
+    if let (Ok(resp), Some(subprotocol)) = (&mut resp, subprotocol) {
+        resp.headers_mut().insert(
+            header::SEC_WEBSOCKET_PROTOCOL,
+            HeaderValue::from_static(subprotocol),
+        );
+    }
+
+    #[cfg(feature = "axum")]
+    resp
 }
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, sync::Arc};
+use std::sync::Arc;
 
 use serde_json::Value;
 use tauri::{
@@ -8,7 +8,10 @@ use tauri::{
 use tokio::sync::mpsc;
 
 use crate::{
-    internal::jsonrpc::{self, handle_json_rpc, Sender, SubscriptionMap},
+    internal::{
+        jsonrpc::{self, Sender2},
+        Connection,
+    },
     Router,
 };
 
@@ -23,17 +26,16 @@ where
     Builder::new("rspc")
         .setup(|app_handle| {
             let (tx, mut rx) = mpsc::unbounded_channel::<jsonrpc::Request>();
-            let (mut resp_tx, mut resp_rx) = mpsc::unbounded_channel::<jsonrpc::Response>();
-            let mut subscriptions = HashMap::new();
+            let (resp_tx, mut resp_rx) = mpsc::unbounded_channel::<jsonrpc::Response>();
+            let conn = Connection::new(router.config.concurrency_limit);
 
             tokio::spawn(async move {
                 while let Some(req) = rx.recv().await {
-                    handle_json_rpc(
+                    conn.handle(
                         ctx_fn(),
                         req,
                         &router,
-                        &mut Sender::ResponseChannel(&mut resp_tx),
-                        &mut SubscriptionMap::Ref(&mut subscriptions),
+                        Sender2::ResponseChannel(resp_tx.clone()),
                     )
                     .await;
                 }
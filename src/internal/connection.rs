@@ -0,0 +1,124 @@
+use std::{collections::HashMap, sync::Arc};
+
+use tokio::sync::{broadcast, oneshot, Mutex, Semaphore};
+
+use crate::Router;
+
+use super::jsonrpc::{
+    self, exec_json_rpc, register_json_rpc, send_error, RequestId, RequestInner, Sender2,
+    SubscriptionMap,
+};
+
+/// the requests which are running on a websocket or Tauri connection.
+pub(crate) struct Connection {
+    subscriptions: Arc<Mutex<HashMap<RequestId, oneshot::Sender<()>>>>,
+    limit: Option<Arc<Semaphore>>,
+    shutdown: broadcast::Sender<()>,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl Connection {
+    pub fn new(concurrency_limit: Option<usize>) -> Self {
+        Self {
+            subscriptions: Default::default(),
+            limit: concurrency_limit.map(|limit| Arc::new(Semaphore::new(limit))),
+            shutdown: broadcast::channel(1).0,
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::none(),
+        }
+    }
+
+    /// sets the span every request on the connection is executed in.
+    #[cfg(all(feature = "tracing", feature = "httpz", feature = "axum"))]
+    pub fn instrument(mut self, span: tracing::Span) -> Self {
+        self.span = span;
+        self
+    }
+
+    /// executes a request received on the connection. Queries, mutations and subscriptions are spawned so they run concurrently.
+    ///
+    /// When the connection's concurrency limit has been reached this waits for a running request to finish before spawning,
+    /// so the caller stops reading new messages until there is capacity.
+    pub async fn handle<TCtx, TMeta>(
+        &self,
+        ctx: TCtx,
+        req: jsonrpc::Request,
+        router: &Arc<Router<TCtx, TMeta>>,
+        mut sender: Sender2,
+    ) where
+        TCtx: Send + 'static,
+        TMeta: Send + Sync + 'static,
+    {
+        let is_stop = matches!(
+            req.inner,
+            RequestInner::SubscriptionStop | RequestInner::Cancel
+        );
+        let permit = match &self.limit {
+            Some(limit) if !is_stop => limit.clone().acquire_owned().await.ok(),
+            _ => None,
+        };
+
+        // Requests are registered before they are spawned so a stop or cancel which is received straight after them can't be missed
+        let cancel =
+            match register_json_rpc(&req, &mut SubscriptionMap::Mutex(&self.subscriptions)).await {
+                Ok(cancel) => cancel,
+                Err(err) => {
+                    send_error(&mut sender.as_sender(), req.id, err).await;
+                    return;
+                }
+            };
+
+        // Stopping a subscription or cancelling a request is done as soon as it's registered
+        if is_stop {
+            return;
+        }
+
+        let (router, subscriptions, mut shutdown) = (
+            router.clone(),
+            self.subscriptions.clone(),
+            self.shutdown.subscribe(),
+        );
+        #[cfg(feature = "tracing")]
+        let span = self.span.clone();
+        tokio::spawn(async move {
+            let _permit = permit;
+            let fut = async {
+                exec_json_rpc(
+                    ctx,
+                    req,
+                    &router,
+                    &mut sender.as_sender(),
+                    &mut SubscriptionMap::Mutex(&subscriptions),
+                    cancel,
+                )
+                .await
+            };
+            #[cfg(feature = "tracing")]
+            let fut = tracing::Instrument::instrument(fut, span);
+
+            tokio::select! {
+                biased; // Note: Order is important here
+                _ = shutdown.recv() => {}
+                _ = fut => {}
+            }
+        });
+    }
+
+    /// cancels all of the connection's in-flight requests and stops its subscriptions.
+    #[cfg(all(feature = "httpz", feature = "axum"))]
+    pub async fn close(&self) {
+        // Notifying the shutdown channel drops all in-flight requests and clearing the map stops all the active subscriptions.
+        let _ = self.shutdown.send(());
+        self.subscriptions.lock().await.clear();
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // The subscriptions of a connection which wasn't closed, eg. a `TestConnection` at the end of a test, are stopped when it's dropped
+        if let Ok(mut subscriptions) = self.subscriptions.try_lock() {
+            subscriptions.clear();
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use serde::{Deserialize, Serialize};
+use std::{cell::Cell, fmt};
+
+use serde::{de, Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use specta::Type;
 
@@ -12,6 +14,16 @@ pub enum RequestId {
     String(String),
 }
 
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestId::Null => write!(f, "null"),
+            RequestId::Number(id) => write!(f, "{}", id),
+            RequestId::String(id) => write!(f, "{}", id),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)] // TODO: Type on this
 pub struct Request {
     pub jsonrpc: Option<String>, // This is required in the JsonRPC spec but I make it optional.
@@ -27,6 +39,8 @@ pub enum RequestInner {
     Mutation { path: String, input: Option<Value> },
     Subscription { path: String, input: Option<Value> },
     SubscriptionStop,
+    // Cancels the in-flight query, mutation or subscription with the same id as this request.
+    Cancel,
 }
 
 #[derive(Debug, Clone, Serialize)] // TODO: Add `specta::Type` when supported
@@ -36,21 +50,125 @@ pub struct Response {
     pub result: ResponseInner,
 }
 
-#[derive(Debug, Clone, Serialize, Type)]
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
 #[serde(tag = "type", content = "data", rename_all = "camelCase")]
 pub enum ResponseInner {
     Event(Value),
     Response(Value),
     Error(JsonRPCError),
+    /// sent when a subscription's stream has finished. No more events will be sent for the subscription.
+    Complete,
 }
 
-#[derive(Debug, Clone, Serialize, Type)]
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct JsonRPCError {
     pub code: i32,
     pub message: String,
     pub data: Option<Value>,
 }
 
+// The version is always "2.0" so we can deserialize it into a `&'static str` without borrowing from the input.
+impl<'de> Deserialize<'de> for Response {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Raw {
+            jsonrpc: String,
+            id: RequestId,
+            result: ResponseInner,
+        }
+
+        let raw = Raw::deserialize(deserializer)?;
+        if raw.jsonrpc != "2.0" {
+            return Err(de::Error::invalid_value(
+                de::Unexpected::Str(&raw.jsonrpc),
+                &"JSON-RPC version '2.0'",
+            ));
+        }
+
+        Ok(Response {
+            jsonrpc: "2.0",
+            id: raw.id,
+            result: raw.result,
+        })
+    }
+}
+
+thread_local! {
+    // The maximum length of the `Requests` being decoded and whether it was exceeded. This is passed through a thread local as not every format accepts a `DeserializeSeed`.
+    static MAX_REQUESTS: Cell<(Option<usize>, bool)> = Cell::new((None, false));
+}
+
+/// a single request or an array of requests, which is how requests are sent in a HTTP batch or a websocket message.
+#[derive(Debug, Clone)]
+pub struct Requests(pub Vec<Request>);
+
+impl Requests {
+    /// runs `decode` and returns `None` if it tried to decode more than `max_length` requests.
+    /// The array is rejected as soon as it's too long so an oversized batch isn't decoded in full.
+    pub(crate) fn with_max_length<T>(
+        max_length: Option<usize>,
+        decode: impl FnOnce() -> T,
+    ) -> Option<T> {
+        let prev = MAX_REQUESTS.with(|max| max.replace((max_length, false)));
+        let result = decode();
+        match MAX_REQUESTS.with(|max| max.replace(prev)) {
+            (_, true) => None,
+            (_, false) => Some(result),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Requests {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RequestsVisitor;
+
+        impl<'de> de::Visitor<'de> for RequestsVisitor {
+            type Value = Requests;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a JSON-RPC request or an array of requests")
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Requests, A::Error> {
+                Request::deserialize(de::value::MapAccessDeserializer::new(map))
+                    .map(|request| Requests(vec![request]))
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Requests, A::Error> {
+                let (max_length, _) = MAX_REQUESTS.with(Cell::get);
+                let mut requests = Vec::new();
+                loop {
+                    // Once the batch is full any further element is skipped without being decoded as a request
+                    if let Some(max_length) = max_length.filter(|max| requests.len() >= *max) {
+                        if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                            MAX_REQUESTS.with(|max| max.set((Some(max_length), true)));
+                            return Err(de::Error::invalid_length(
+                                requests.len() + 1,
+                                &"no more requests than the maximum batch length",
+                            ));
+                        }
+                        break;
+                    }
+
+                    match seq.next_element()? {
+                        Some(request) => requests.push(request),
+                        None => break,
+                    }
+                }
+                Ok(Requests(requests))
+            }
+        }
+
+        deserializer.deserialize_any(RequestsVisitor)
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use std::{fs::File, io::Write, path::PathBuf};
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Instant};
 
 use futures::StreamExt;
 use serde_json::Value;
@@ -15,7 +15,7 @@ use super::{
 
 pub enum SubscriptionMap<'a> {
     Ref(&'a mut HashMap<RequestId, oneshot::Sender<()>>),
-    Mutex(&'a Mutex<HashMap<RequestId, oneshot::Sender<()>>>),
+    Mutex(&'a Arc<Mutex<HashMap<RequestId, oneshot::Sender<()>>>>),
     None,
 }
 
@@ -56,7 +56,29 @@ impl<'a> SubscriptionMap<'a> {
             SubscriptionMap::None => unreachable!(),
         }
     }
+
+    /// removes the request with the given id if it has finished. A running request with the same id is left alone.
+    pub(crate) async fn remove_closed(&mut self, id: &RequestId) {
+        self.with(|map| {
+            if matches!(map.get(id), Some(tx) if tx.is_closed()) {
+                map.remove(id);
+            }
+        })
+        .await;
+    }
+
+    async fn with<T>(
+        &mut self,
+        func: impl FnOnce(&mut HashMap<RequestId, oneshot::Sender<()>>) -> T,
+    ) -> Option<T> {
+        match self {
+            SubscriptionMap::Ref(map) => Some(func(map)),
+            SubscriptionMap::Mutex(map) => Some(func(&mut *map.lock().await)),
+            SubscriptionMap::None => None,
+        }
+    }
 }
+
 pub enum Sender<'a> {
     Channel(&'a mut mpsc::Sender<jsonrpc::Response>),
     ResponseChannel(&'a mut mpsc::UnboundedSender<jsonrpc::Response>),
@@ -93,6 +115,14 @@ impl Sender2 {
 
         Ok(())
     }
+
+    pub fn as_sender(&mut self) -> Sender<'_> {
+        match self {
+            Self::Channel(tx) => Sender::Channel(tx),
+            Self::ResponseChannel(tx) => Sender::ResponseChannel(tx),
+            Self::Broadcast(tx) => Sender::Broadcast(tx),
+        }
+    }
 }
 
 impl<'a> Sender<'a> {
@@ -139,102 +169,216 @@ pub async fn handle_json_rpc<TCtx, TMeta>(
     subscriptions: &mut SubscriptionMap<'_>,
 ) where
     TCtx: 'static,
+    TMeta: 'static,
 {
+    match register_json_rpc(&req, subscriptions).await {
+        Ok(cancel) => exec_json_rpc(ctx, req, router, sender, subscriptions, cancel).await,
+        Err(err) => send_error(sender, req.id, err).await,
+    }
+}
+
+/// executes a request which is part of a HTTP batch. Subscriptions can't be batched so they are responded to with an error.
+pub async fn handle_batched_json_rpc<TCtx, TMeta>(
+    ctx: TCtx,
+    req: jsonrpc::Request,
+    router: &Arc<Router<TCtx, TMeta>>,
+) -> jsonrpc::Response
+where
+    TCtx: 'static,
+    TMeta: 'static,
+{
+    let id = req.id.clone();
+    if let RequestInner::Subscription { .. }
+    | RequestInner::SubscriptionStop
+    | RequestInner::Cancel = req.inner
+    {
+        return jsonrpc::Response {
+            jsonrpc: "2.0",
+            id,
+            result: ResponseInner::Error(
+                ExecError::UnsupportedMethod("Subscription".to_string()).into(),
+            ),
+        };
+    }
+
+    let mut resp = Sender::Response(None);
+    handle_json_rpc(ctx, req, router, &mut resp, &mut SubscriptionMap::None).await;
+
+    match resp {
+        Sender::Response(Some(resp)) => resp,
+        // Queries and mutations always send a response
+        _ => unreachable!(),
+    }
+}
+
+/// sends the response to a request which failed before it could be executed.
+pub(crate) async fn send_error(sender: &mut Sender<'_>, id: RequestId, err: ExecError) {
+    let _ = sender
+        .send(jsonrpc::Response {
+            jsonrpc: "2.0",
+            id,
+            result: ResponseInner::Error(err.into()),
+        })
+        .await
+        .map_err(|_err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!("Failed to send response: {}", _err);
+        });
+}
+
+/// checks a request can be executed and registers it in the subscription map so it can be stopped or cancelled by a later request with the same id.
+/// Stopping a subscription or cancelling a request removes the request it targets instead.
+///
+/// Integrations which spawn requests must call this before spawning them, otherwise a stop or cancel received straight after a request could be missed.
+pub(crate) async fn register_json_rpc(
+    req: &jsonrpc::Request,
+    subscriptions: &mut SubscriptionMap<'_>,
+) -> Result<Option<oneshot::Receiver<()>>, ExecError> {
     if req.jsonrpc.is_some() && req.jsonrpc.as_deref() != Some("2.0") {
-        let _ = sender
-            .send(jsonrpc::Response {
-                jsonrpc: "2.0",
-                id: req.id.clone(),
-                result: ResponseInner::Error(ExecError::InvalidJsonRpcVersion.into()),
-            })
-            .await
-            .map_err(|_err| {
-                #[cfg(feature = "tracing")]
-                tracing::error!("Failed to send response: {}", _err);
-            });
+        return Err(ExecError::InvalidJsonRpcVersion);
     }
 
-    let (path, input, procedures, sub_id) = match req.inner {
-        RequestInner::Query { path, input } => (path, input, router.queries(), None),
-        RequestInner::Mutation { path, input } => (path, input, router.mutations(), None),
-        RequestInner::Subscription { path, input } => {
-            (path, input, router.subscriptions(), Some(req.id.clone()))
+    let is_subscription = match req.inner {
+        // Removing the request drops it's shutdown sender which will stop the subscription or cancel the in-flight request.
+        RequestInner::SubscriptionStop | RequestInner::Cancel => {
+            subscriptions.with(|map| map.remove(&req.id)).await;
+            return Ok(None);
+        }
+        RequestInner::Subscription { .. } => true,
+        RequestInner::Query { .. } | RequestInner::Mutation { .. } => false,
+    };
+
+    match &req.id {
+        _ if matches!(subscriptions, SubscriptionMap::None) => Ok(None),
+        RequestId::Null if is_subscription => Err(ExecError::ErrSubscriptionWithNullId),
+        // Queries and mutations without an id can't be cancelled so they aren't registered
+        RequestId::Null => Ok(None),
+        id => {
+            let (tx, rx) = oneshot::channel();
+            subscriptions
+                .with(|map| match map.get(id) {
+                    // The entry of a request which has finished is free to be reused
+                    Some(running) if !running.is_closed() => Err(match is_subscription {
+                        true => ExecError::ErrSubscriptionDuplicateId,
+                        false => ExecError::ErrRequestDuplicateId,
+                    }),
+                    _ => {
+                        map.insert(id.clone(), tx);
+                        Ok(Some(rx))
+                    }
+                })
+                .await
+                .unwrap_or(Ok(None))
+        }
+    }
+}
+
+/// executes a request which was registered using [`register_json_rpc`]. `cancel` is the receiver it returned.
+pub(crate) async fn exec_json_rpc<TCtx, TMeta>(
+    ctx: TCtx,
+    req: jsonrpc::Request,
+    router: &Arc<Router<TCtx, TMeta>>,
+    sender: &mut Sender<'_>,
+    subscriptions: &mut SubscriptionMap<'_>,
+    mut cancel: Option<oneshot::Receiver<()>>,
+) where
+    TCtx: 'static,
+    TMeta: 'static,
+{
+    let (path, input, procedures, kind) = match req.inner {
+        RequestInner::Query { path, input } => {
+            (path, input, router.queries(), ProcedureKind::Query)
         }
-        RequestInner::SubscriptionStop => {
-            subscriptions.remove(&req.id).await;
-            return;
+        RequestInner::Mutation { path, input } => {
+            (path, input, router.mutations(), ProcedureKind::Mutation)
         }
+        RequestInner::Subscription { path, input } => (
+            path,
+            input,
+            router.subscriptions(),
+            ProcedureKind::Subscription,
+        ),
+        RequestInner::SubscriptionStop | RequestInner::Cancel => return,
     };
+    // A subscription which fails before it starts streaming is also completed so the client knows it has finished
+    let complete_on_error = cancel.is_some() && matches!(kind, ProcedureKind::Subscription);
 
+    let start = Instant::now();
+    #[cfg(feature = "tracing")]
+    let span = procedure_span(&kind, &path, &req.id);
+
+    // Only procedures which exist are recorded so clients can't create an unbounded number of metrics
+    let metrics = router
+        .config
+        .metrics
+        .as_ref()
+        .filter(|_| procedures.contains_key(&path))
+        .map(|metrics| metrics.procedure(&kind, &path));
+
+    let mut timeout = None;
     let result = match procedures
         .get(&path)
         .ok_or_else(|| ExecError::OperationNotFound(path.clone()))
         .and_then(|v| {
-            v.exec.call(
+            #[cfg(feature = "tracing")]
+            let _entered = span.enter();
+
+            timeout = v.timeout;
+            let op = v.exec.call(
                 ctx,
                 input.unwrap_or(Value::Null),
                 RequestContext {
-                    kind: ProcedureKind::Query,
+                    kind,
                     path,
+                    meta: v.meta.clone(),
+                    typed: None,
                 },
-            )
+            )?;
+
+            #[cfg(feature = "tracing")]
+            let op = op.instrument(span.clone());
+            Ok(op)
         }) {
-        Ok(op) => match op.into_layer_return().await {
-            Ok(LayerReturn::Request(v)) => ResponseInner::Response(v),
-            Ok(LayerReturn::Stream(mut stream)) => {
-                if matches!(sender, Sender::Response(_))
-                    || matches!(subscriptions, SubscriptionMap::None)
-                {
-                    let _ = sender
-                        .send(jsonrpc::Response {
-                            jsonrpc: "2.0",
-                            id: req.id.clone(),
-                            result: ResponseInner::Error(
-                                ExecError::UnsupportedMethod("Subscription".to_string()).into(),
-                            ),
-                        })
-                        .await
-                        .map_err(|_err| {
-                            #[cfg(feature = "tracing")]
-                            tracing::error!("Failed to send response: {}", _err);
-                        });
-                }
+        Ok(op) => {
+            // Registered requests can be cancelled by the client while they are running
+            let result = match &mut cancel {
+                Some(cancel) => tokio::select! {
+                    biased; // Note: Order matters
+                    _ = cancel => Err(ExecError::ErrRequestCancelled),
+                    result = op.into_layer_return_with_timeout(timeout) => result,
+                },
+                None => op.into_layer_return_with_timeout(timeout).await,
+            };
 
-                if let Some(id) = sub_id {
-                    if matches!(id, RequestId::Null) {
-                        let _ = sender
-                            .send(jsonrpc::Response {
-                                jsonrpc: "2.0",
-                                id: req.id.clone(),
-                                result: ResponseInner::Error(
-                                    ExecError::ErrSubscriptionWithNullId.into(),
-                                ),
-                            })
-                            .await
-                            .map_err(|_err| {
-                                #[cfg(feature = "tracing")]
-                                tracing::error!("Failed to send response: {}", _err);
-                            });
-                    } else if subscriptions.has_subscription(&id).await {
-                        let _ = sender
-                            .send(jsonrpc::Response {
-                                jsonrpc: "2.0",
-                                id: req.id.clone(),
-                                result: ResponseInner::Error(
-                                    ExecError::ErrSubscriptionDuplicateId.into(),
-                                ),
-                            })
-                            .await
-                            .map_err(|_err| {
-                                #[cfg(feature = "tracing")]
-                                tracing::error!("Failed to send response: {}", _err);
-                            });
-                    }
+            match result {
+                Ok(LayerReturn::Request(v)) => ResponseInner::Response(v),
+                Ok(LayerReturn::Stream(mut stream)) => {
+                    let (id, mut shutdown_rx) = match cancel {
+                        Some(cancel) if !matches!(sender, Sender::Response(_)) => (req.id, cancel),
+                        _ => {
+                            send_error(
+                                sender,
+                                req.id,
+                                ExecError::UnsupportedMethod("Subscription".to_string()),
+                            )
+                            .await;
+                            return;
+                        }
+                    };
 
-                    let (shutdown_tx, mut shutdown_rx) = oneshot::channel();
-                    subscriptions.insert(id.clone(), shutdown_tx).await;
                     let mut sender2 = sender.sender2();
+                    let map = match subscriptions {
+                        SubscriptionMap::Mutex(map) => Some(Arc::clone(map)),
+                        // A borrowed map can't be updated from the subscription's task so its entry is replaced when the id is reused
+                        _ => None,
+                    };
+                    #[cfg(feature = "tracing")]
+                    let span = span.clone();
+                    if let Some(metrics) = &metrics {
+                        metrics.record(start.elapsed(), None);
+                    }
                     tokio::spawn(async move {
+                        let _active = metrics.as_ref().map(|metrics| metrics.subscription());
                         loop {
                             tokio::select! {
                                 biased; // Note: Order matters
@@ -244,6 +388,9 @@ pub async fn handle_json_rpc<TCtx, TMeta>(
                                     break;
                                 }
                                 v = stream.next() => {
+                                    #[cfg(feature = "tracing")]
+                                    let event_span = v.is_some().then(|| subscription_event_span(&span));
+
                                     match v {
                                         Some(Ok(v)) => {
                                             let _ = sender2.send(jsonrpc::Response {
@@ -257,29 +404,68 @@ pub async fn handle_json_rpc<TCtx, TMeta>(
                                                 tracing::error!("Failed to send response: {:?}", _err);
                                             });
                                         }
-                                        Some(Err(_err)) => {
-                                           #[cfg(feature = "tracing")]
-                                            tracing::error!("Subscription error: {:?}", _err);
+                                        Some(Err(err)) => {
+                                            #[cfg(feature = "tracing")]
+                                            tracing::error!("Subscription error: {:?}", err);
+
+                                            let err: jsonrpc::JsonRPCError = err.into();
+                                            #[cfg(feature = "tracing")]
+                                            if let Some(event_span) = &event_span {
+                                                event_span.record("rspc.error_code", err.code);
+                                            }
+                                            if let Some(metrics) = &metrics {
+                                                metrics.record_error(err.code);
+                                            }
+
+                                            let _ = sender2.send(jsonrpc::Response {
+                                                jsonrpc: "2.0",
+                                                id: id.clone(),
+                                                result: ResponseInner::Error(err),
+                                            })
+                                            .await
+                                            .map_err(|_err| {
+                                                #[cfg(feature = "tracing")]
+                                                tracing::error!("Failed to send response: {:?}", _err);
+                                            });
                                         }
                                         None => {
+                                            let _ = sender2.send(jsonrpc::Response {
+                                                jsonrpc: "2.0",
+                                                id: id.clone(),
+                                                result: ResponseInner::Complete,
+                                            })
+                                            .await
+                                            .map_err(|_err| {
+                                                #[cfg(feature = "tracing")]
+                                                tracing::error!("Failed to send response: {:?}", _err);
+                                            });
                                             break;
                                         }
                                     }
                                 }
                             }
                         }
+
+                        // The subscription has finished so it's removed from the map, unless its id has already been reused by a new subscription
+                        drop(shutdown_rx);
+                        if let Some(map) = &map {
+                            SubscriptionMap::Mutex(map).remove_closed(&id).await;
+                        }
+
+                        #[cfg(feature = "tracing")]
+                        record_result(&span, start, None);
                     });
-                }
 
-                return;
-            }
-            Err(err) => {
-                #[cfg(feature = "tracing")]
-                tracing::error!("Error executing operation: {:?}", err);
+                    return;
+                }
+                Err(err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!("Error executing operation: {:?}", err);
 
-                ResponseInner::Error(err.into())
+                    ResponseInner::Error(err.into())
+                }
             }
-        },
+        }
         Err(err) => {
             #[cfg(feature = "tracing")]
             tracing::error!("Error executing operation: {:?}", err);
@@ -287,10 +473,24 @@ pub async fn handle_json_rpc<TCtx, TMeta>(
         }
     };
 
+    // The request has finished so it's removed from the map, unless its id has already been reused by a new request
+    drop(cancel);
+    subscriptions.remove_closed(&req.id).await;
+
+    let error_code = match &result {
+        ResponseInner::Error(err) => Some(err.code),
+        _ => None,
+    };
+    #[cfg(feature = "tracing")]
+    record_result(&span, start, error_code);
+    if let Some(metrics) = &metrics {
+        metrics.record(start.elapsed(), error_code);
+    }
+
     let _ = sender
         .send(jsonrpc::Response {
             jsonrpc: "2.0",
-            id: req.id,
+            id: req.id.clone(),
             result,
         })
         .await
@@ -298,4 +498,49 @@ pub async fn handle_json_rpc<TCtx, TMeta>(
             #[cfg(feature = "tracing")]
             tracing::error!("Failed to send response: {:?}", _err);
         });
+
+    if complete_on_error {
+        let _ = sender
+            .send(jsonrpc::Response {
+                jsonrpc: "2.0",
+                id: req.id,
+                result: ResponseInner::Complete,
+            })
+            .await
+            .map_err(|_err| {
+                #[cfg(feature = "tracing")]
+                tracing::error!("Failed to send response: {:?}", _err);
+            });
+    }
+}
+
+/// creates the span a procedure is executed in.
+#[cfg(feature = "tracing")]
+fn procedure_span(kind: &ProcedureKind, path: &str, id: &RequestId) -> tracing::Span {
+    tracing::info_span!(
+        "rspc.procedure",
+        rspc.kind = kind.to_str(),
+        rspc.path = path,
+        rspc.request_id = %id,
+        rspc.duration_ms = tracing::field::Empty,
+        rspc.error_code = tracing::field::Empty,
+    )
+}
+
+/// creates a span for an event yielded by a subscription. It's a child of the subscription's procedure span.
+#[cfg(feature = "tracing")]
+fn subscription_event_span(span: &tracing::Span) -> tracing::Span {
+    tracing::info_span!(
+        parent: span,
+        "rspc.subscription.event",
+        rspc.error_code = tracing::field::Empty,
+    )
+}
+
+#[cfg(feature = "tracing")]
+fn record_result(span: &tracing::Span, start: Instant, error_code: Option<i32>) {
+    span.record("rspc.duration_ms", start.elapsed().as_secs_f64() * 1000.0);
+    if let Some(error_code) = error_code {
+        span.record("rspc.error_code", error_code);
+    }
 }
@@ -1,76 +1,80 @@
-use std::{marker::PhantomData, sync::Arc};
+use std::{any::Any, marker::PhantomData, sync::Arc, time::Duration};
 
-use futures::{future::BoxFuture, stream::BoxStream};
+use futures::{future::BoxFuture, stream::BoxStream, StreamExt};
 use serde::Serialize;
 use serde_json::Value;
 use specta::Type;
 
 use crate::{internal::MiddlewareLike, ExecError};
 
-pub trait MiddlewareBuilderLike<TCtx> {
+pub trait MiddlewareBuilderLike<TCtx, TMeta = ()> {
     type LayerContext: 'static;
 
-    fn build<T>(&self, next: T) -> Box<dyn Layer<TCtx>>
+    fn build<T>(&self, next: T) -> Box<dyn Layer<TCtx, TMeta>>
     where
-        T: Layer<Self::LayerContext>;
+        T: Layer<Self::LayerContext, TMeta>;
 }
 
-pub struct MiddlewareMerger<TCtx, TLayerCtx, TNewLayerCtx, TMiddleware, TIncomingMiddleware>
+pub struct MiddlewareMerger<TCtx, TLayerCtx, TNewLayerCtx, TMiddleware, TIncomingMiddleware, TMeta>
 where
-    TMiddleware: MiddlewareBuilderLike<TCtx, LayerContext = TLayerCtx>,
-    TIncomingMiddleware: MiddlewareBuilderLike<TLayerCtx, LayerContext = TNewLayerCtx>,
+    TMiddleware: MiddlewareBuilderLike<TCtx, TMeta, LayerContext = TLayerCtx>,
+    TIncomingMiddleware: MiddlewareBuilderLike<TLayerCtx, TMeta, LayerContext = TNewLayerCtx>,
 {
     pub middleware: TMiddleware,
     pub middleware2: TIncomingMiddleware,
-    pub phantom: PhantomData<(TCtx, TLayerCtx)>,
+    pub phantom: PhantomData<(TCtx, TLayerCtx, TMeta)>,
 }
 
-impl<TCtx, TLayerCtx, TNewLayerCtx, TMiddleware, TIncomingMiddleware> MiddlewareBuilderLike<TCtx>
-    for MiddlewareMerger<TCtx, TLayerCtx, TNewLayerCtx, TMiddleware, TIncomingMiddleware>
+impl<TCtx, TLayerCtx, TNewLayerCtx, TMiddleware, TIncomingMiddleware, TMeta>
+    MiddlewareBuilderLike<TCtx, TMeta>
+    for MiddlewareMerger<TCtx, TLayerCtx, TNewLayerCtx, TMiddleware, TIncomingMiddleware, TMeta>
 where
     TCtx: 'static,
     TLayerCtx: 'static,
     TNewLayerCtx: 'static,
-    TMiddleware: MiddlewareBuilderLike<TCtx, LayerContext = TLayerCtx>,
-    TIncomingMiddleware: MiddlewareBuilderLike<TLayerCtx, LayerContext = TNewLayerCtx>,
+    TMeta: 'static,
+    TMiddleware: MiddlewareBuilderLike<TCtx, TMeta, LayerContext = TLayerCtx>,
+    TIncomingMiddleware: MiddlewareBuilderLike<TLayerCtx, TMeta, LayerContext = TNewLayerCtx>,
 {
     type LayerContext = TNewLayerCtx;
 
-    fn build<T>(&self, next: T) -> Box<dyn Layer<TCtx>>
+    fn build<T>(&self, next: T) -> Box<dyn Layer<TCtx, TMeta>>
     where
-        T: Layer<Self::LayerContext>,
+        T: Layer<Self::LayerContext, TMeta>,
     {
         self.middleware.build(self.middleware2.build(next))
     }
 }
 
-pub struct MiddlewareLayerBuilder<TCtx, TLayerCtx, TNewLayerCtx, TMiddleware, TNewMiddleware>
+pub struct MiddlewareLayerBuilder<TCtx, TLayerCtx, TNewLayerCtx, TMiddleware, TNewMiddleware, TMeta>
 where
     TCtx: Send + Sync + 'static,
     TLayerCtx: Send + Sync + 'static,
     TNewLayerCtx: Send + Sync + 'static,
-    TMiddleware: MiddlewareBuilderLike<TCtx, LayerContext = TLayerCtx> + Send + 'static,
-    TNewMiddleware: MiddlewareLike<TLayerCtx, NewCtx = TNewLayerCtx>,
+    TMiddleware: MiddlewareBuilderLike<TCtx, TMeta, LayerContext = TLayerCtx> + Send + 'static,
+    TNewMiddleware: MiddlewareLike<TLayerCtx, TMeta, NewCtx = TNewLayerCtx>,
 {
     pub middleware: TMiddleware,
     pub mw: TNewMiddleware,
-    pub phantom: PhantomData<(TCtx, TLayerCtx, TNewLayerCtx)>,
+    pub phantom: PhantomData<(TCtx, TLayerCtx, TNewLayerCtx, TMeta)>,
 }
 
-impl<TCtx, TLayerCtx, TNewLayerCtx, TMiddleware, TNewMiddleware> MiddlewareBuilderLike<TCtx>
-    for MiddlewareLayerBuilder<TCtx, TLayerCtx, TNewLayerCtx, TMiddleware, TNewMiddleware>
+impl<TCtx, TLayerCtx, TNewLayerCtx, TMiddleware, TNewMiddleware, TMeta>
+    MiddlewareBuilderLike<TCtx, TMeta>
+    for MiddlewareLayerBuilder<TCtx, TLayerCtx, TNewLayerCtx, TMiddleware, TNewMiddleware, TMeta>
 where
     TCtx: Send + Sync + 'static,
     TLayerCtx: Send + Sync + 'static,
     TNewLayerCtx: Send + Sync + 'static,
-    TMiddleware: MiddlewareBuilderLike<TCtx, LayerContext = TLayerCtx> + Send + 'static,
-    TNewMiddleware: MiddlewareLike<TLayerCtx, NewCtx = TNewLayerCtx> + Send + Sync + 'static,
+    TMeta: Send + Sync + 'static,
+    TMiddleware: MiddlewareBuilderLike<TCtx, TMeta, LayerContext = TLayerCtx> + Send + 'static,
+    TNewMiddleware: MiddlewareLike<TLayerCtx, TMeta, NewCtx = TNewLayerCtx> + Send + Sync + 'static,
 {
     type LayerContext = TNewLayerCtx;
 
-    fn build<T>(&self, next: T) -> Box<dyn Layer<TCtx>>
+    fn build<T>(&self, next: T) -> Box<dyn Layer<TCtx, TMeta>>
     where
-        T: Layer<Self::LayerContext> + Sync,
+        T: Layer<Self::LayerContext, TMeta> + Sync,
     {
         self.middleware.build(MiddlewareLayer {
             next: Arc::new(next),
@@ -80,27 +84,34 @@ where
     }
 }
 
-pub struct MiddlewareLayer<TLayerCtx, TNewLayerCtx, TMiddleware, TNewMiddleware>
+pub struct MiddlewareLayer<TLayerCtx, TNewLayerCtx, TMiddleware, TNewMiddleware, TMeta>
 where
     TLayerCtx: Send + 'static,
     TNewLayerCtx: Send + 'static,
-    TMiddleware: Layer<TNewLayerCtx> + 'static,
-    TNewMiddleware: MiddlewareLike<TLayerCtx, NewCtx = TNewLayerCtx> + Send + Sync + 'static,
+    TMeta: 'static,
+    TMiddleware: Layer<TNewLayerCtx, TMeta> + 'static,
+    TNewMiddleware: MiddlewareLike<TLayerCtx, TMeta, NewCtx = TNewLayerCtx> + Send + Sync + 'static,
 {
     next: Arc<TMiddleware>, // TODO: Avoid arcing this if possible
     mw: TNewMiddleware,
-    phantom: PhantomData<(TLayerCtx, TNewLayerCtx)>,
+    phantom: PhantomData<(TLayerCtx, TNewLayerCtx, TMeta)>,
 }
 
-impl<TLayerCtx, TNewLayerCtx, TMiddleware, TNewMiddleware> Layer<TLayerCtx>
-    for MiddlewareLayer<TLayerCtx, TNewLayerCtx, TMiddleware, TNewMiddleware>
+impl<TLayerCtx, TNewLayerCtx, TMiddleware, TNewMiddleware, TMeta> Layer<TLayerCtx, TMeta>
+    for MiddlewareLayer<TLayerCtx, TNewLayerCtx, TMiddleware, TNewMiddleware, TMeta>
 where
     TLayerCtx: Send + Sync + 'static,
     TNewLayerCtx: Send + Sync + 'static,
-    TMiddleware: Layer<TNewLayerCtx> + Sync + 'static,
-    TNewMiddleware: MiddlewareLike<TLayerCtx, NewCtx = TNewLayerCtx> + Send + Sync + 'static,
+    TMeta: Send + Sync + 'static,
+    TMiddleware: Layer<TNewLayerCtx, TMeta> + Sync + 'static,
+    TNewMiddleware: MiddlewareLike<TLayerCtx, TMeta, NewCtx = TNewLayerCtx> + Send + Sync + 'static,
 {
-    fn call(&self, ctx: TLayerCtx, input: Value, req: RequestContext) -> ExecResult<LayerFuture> {
+    fn call(
+        &self,
+        ctx: TLayerCtx,
+        input: Value,
+        req: RequestContext<TMeta>,
+    ) -> ExecResult<LayerFuture> {
         Ok(self.mw.handle(ctx, input, req, self.next.clone()))
     }
 }
@@ -118,62 +129,81 @@ where
     }
 }
 
-impl<TCtx> MiddlewareBuilderLike<TCtx> for BaseMiddleware<TCtx>
+impl<TCtx, TMeta> MiddlewareBuilderLike<TCtx, TMeta> for BaseMiddleware<TCtx>
 where
     TCtx: Send + 'static,
+    TMeta: 'static,
 {
     type LayerContext = TCtx;
 
-    fn build<T>(&self, next: T) -> Box<dyn Layer<TCtx>>
+    fn build<T>(&self, next: T) -> Box<dyn Layer<TCtx, TMeta>>
     where
-        T: Layer<Self::LayerContext>,
+        T: Layer<Self::LayerContext, TMeta>,
     {
         Box::new(next)
     }
 }
 
 // TODO: Rename this so it doesn't conflict with the middleware builder struct
-pub trait Layer<TLayerCtx: 'static>: Send + Sync + 'static {
-    fn call(&self, a: TLayerCtx, b: Value, c: RequestContext) -> Result<LayerFuture, ExecError>;
+pub trait Layer<TLayerCtx: 'static, TMeta = ()>: Send + Sync + 'static {
+    fn call(
+        &self,
+        a: TLayerCtx,
+        b: Value,
+        c: RequestContext<TMeta>,
+    ) -> Result<LayerFuture, ExecError>;
 }
 
-pub struct ResolverLayer<TLayerCtx, T>
+pub struct ResolverLayer<TLayerCtx, T, TMeta = ()>
 where
     TLayerCtx: Send + Sync + 'static,
-    T: Fn(TLayerCtx, Value, RequestContext) -> Result<LayerFuture, ExecError>
+    T: Fn(TLayerCtx, Value, RequestContext<TMeta>) -> Result<LayerFuture, ExecError>
         + Send
         + Sync
         + 'static,
 {
     pub func: T,
-    pub phantom: PhantomData<TLayerCtx>,
+    pub phantom: PhantomData<(TLayerCtx, TMeta)>,
 }
 
-impl<T, TLayerCtx> Layer<TLayerCtx> for ResolverLayer<TLayerCtx, T>
+impl<T, TLayerCtx, TMeta> Layer<TLayerCtx, TMeta> for ResolverLayer<TLayerCtx, T, TMeta>
 where
     TLayerCtx: Send + Sync + 'static,
-    T: Fn(TLayerCtx, Value, RequestContext) -> Result<LayerFuture, ExecError>
+    TMeta: Send + Sync + 'static,
+    T: Fn(TLayerCtx, Value, RequestContext<TMeta>) -> Result<LayerFuture, ExecError>
         + Send
         + Sync
         + 'static,
 {
-    fn call(&self, a: TLayerCtx, b: Value, c: RequestContext) -> Result<LayerFuture, ExecError> {
+    fn call(
+        &self,
+        a: TLayerCtx,
+        b: Value,
+        c: RequestContext<TMeta>,
+    ) -> Result<LayerFuture, ExecError> {
         (self.func)(a, b, c)
     }
 }
 
-impl<TLayerCtx> Layer<TLayerCtx> for Box<dyn Layer<TLayerCtx> + 'static>
+impl<TLayerCtx, TMeta> Layer<TLayerCtx, TMeta> for Box<dyn Layer<TLayerCtx, TMeta> + 'static>
 where
     TLayerCtx: 'static,
+    TMeta: 'static,
 {
-    fn call(&self, a: TLayerCtx, b: Value, c: RequestContext) -> Result<LayerFuture, ExecError> {
+    fn call(
+        &self,
+        a: TLayerCtx,
+        b: Value,
+        c: RequestContext<TMeta>,
+    ) -> Result<LayerFuture, ExecError> {
         (**self).call(a, b, c)
     }
 }
 
 // TODO: Is this a duplicate of any type?
 // TODO: Move into public API cause it might be used in middleware
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub enum ProcedureKind {
     Query,
     Mutation,
@@ -191,10 +221,46 @@ impl ProcedureKind {
 }
 
 // TODO: Maybe rename to `Request` or something else. Also move into Public API cause it might be used in middleware
-#[derive(Debug, Clone)]
-pub struct RequestContext {
+#[derive(Debug)]
+pub struct RequestContext<TMeta = ()> {
     pub kind: ProcedureKind,
     pub path: String, // TODO: String slice??
+    pub meta: Option<Arc<TMeta>>,
+    pub(crate) typed: Option<Arc<TypedCall>>,
+}
+
+// This can't be derived as it would require `TMeta: Clone`
+impl<TMeta> Clone for RequestContext<TMeta> {
+    fn clone(&self) -> Self {
+        Self {
+            kind: self.kind.clone(),
+            path: self.path.clone(),
+            meta: self.meta.clone(),
+            typed: self.typed.clone(),
+        }
+    }
+}
+
+/// holds the input and result of a typed in-process call so they can be passed between the caller and the resolver without being serialized.
+#[derive(Debug)]
+pub(crate) struct TypedCall {
+    pub(crate) input: std::sync::Mutex<Option<Box<dyn Any + Send>>>,
+    pub(crate) result: std::sync::Mutex<Option<Box<dyn Any + Send>>>,
+    /// serializes the input, this is only used to validate it when the argument type declares constraints.
+    pub(crate) serialize_input: fn(&(dyn Any + Send)) -> Result<Value, ExecError>,
+}
+
+impl<TMeta> RequestContext<TMeta> {
+    /// returns true if the procedure is being called in-process using [`Router::call`](crate::Router::call).
+    /// The input and result of a typed call aren't serialized so the middleware is given `null` in place of them.
+    pub fn is_typed_call(&self) -> bool {
+        self.typed.is_some()
+    }
+
+    /// returns the metadata attached to the procedure being executed or `None` if the procedure has no metadata.
+    pub fn meta(&self) -> Option<&TMeta> {
+        self.meta.as_deref()
+    }
 }
 
 pub type ExecResult<T> = Result<T, ExecError>;
@@ -228,13 +294,13 @@ where
     pub fn to_request_future(self) -> RequestFuture {
         match self {
             Self::Ready(val) => RequestFuture::Ready({
-                val.map(|v| serde_json::to_value(v).map_err(ExecError::DeserializingArgErr))
+                val.map(|v| serde_json::to_value(v).map_err(ExecError::SerializingResultErr))
                     .and_then(|v| v)
             }),
             Self::Future(fut) => RequestFuture::Future(Box::pin(async move {
                 fut.await
                     .map(serde_json::to_value)?
-                    .map_err(ExecError::DeserializingArgErr)
+                    .map_err(ExecError::SerializingResultErr)
             })),
         }
     }
@@ -271,6 +337,57 @@ impl LayerFuture {
             }
         })
     }
+
+    /// is the same as `into_layer_return` but will return a `Timeout` error if the procedure doesn't resolve within the timeout.
+    /// If the procedure returns a stream it will also be stopped with a `Timeout` error if it doesn't yield an item within the timeout.
+    pub fn into_layer_return_with_timeout(
+        self,
+        timeout: Option<Duration>,
+    ) -> BoxFuture<'static, ExecResult<LayerReturn>> {
+        let timeout = match timeout {
+            Some(timeout) => timeout,
+            None => return self.into_layer_return(),
+        };
+
+        Box::pin(async move {
+            match tokio::time::timeout(timeout, self.into_layer_return()).await {
+                Ok(Ok(LayerReturn::Stream(mut stream))) => {
+                    Ok(LayerReturn::Stream(Box::pin(async_stream::stream! {
+                        loop {
+                            match tokio::time::timeout(timeout, stream.next()).await {
+                                Ok(Some(v)) => yield v,
+                                Ok(None) => break,
+                                Err(_) => {
+                                    yield Err(ExecError::Timeout);
+                                    break;
+                                }
+                            }
+                        }
+                    })))
+                }
+                Ok(result) => result,
+                Err(_) => Err(ExecError::Timeout),
+            }
+        })
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl LayerFuture {
+    /// runs the procedure inside a span so everything it logs is attributed to it.
+    pub(crate) fn instrument(self, span: tracing::Span) -> Self {
+        use tracing::Instrument;
+
+        Self::Wrapped(Box::pin(
+            async move {
+                Ok(match self.into_layer_return().await? {
+                    LayerReturn::Request(v) => RequestFuture::Ready(Ok(v)).into(),
+                    LayerReturn::Stream(stream) => stream.into(),
+                })
+            }
+            .instrument(span),
+        ))
+    }
 }
 
 impl From<RequestFuture> for LayerFuture {
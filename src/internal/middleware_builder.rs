@@ -7,35 +7,38 @@ use crate::{
     ExecError,
 };
 
-pub trait MiddlewareLike<TLayerCtx>: Clone {
+pub trait MiddlewareLike<TLayerCtx, TMeta = ()>: Clone {
     type State: Clone + Send + Sync + 'static;
     type NewCtx: Send + 'static;
 
-    fn handle<TMiddleware: Layer<Self::NewCtx> + 'static>(
+    fn handle<TMiddleware: Layer<Self::NewCtx, TMeta> + 'static>(
         &self,
         ctx: TLayerCtx,
         input: Value,
-        req: RequestContext,
+        req: RequestContext<TMeta>,
         next: Arc<TMiddleware>,
     ) -> LayerFuture;
 }
-pub struct MiddlewareState<TLayerCtx, TNewCtx = TLayerCtx, TState = ()>
+pub struct MiddlewareState<TLayerCtx, TNewCtx = TLayerCtx, TState = (), TMeta = ()>
 where
     TState: Send,
 {
     pub state: TState,
     pub input: Value,
     pub ctx: TNewCtx,
-    pub req: RequestContext,
+    pub req: RequestContext<TMeta>,
     pub phantom: PhantomData<TLayerCtx>,
 }
 
 // This will match were TState is the default (`()`) so it shouldn't let you call it if you've already swapped the generic
-impl<TLayerCtx, TNewCtx> MiddlewareState<TLayerCtx, TNewCtx, ()>
+impl<TLayerCtx, TNewCtx, TMeta> MiddlewareState<TLayerCtx, TNewCtx, (), TMeta>
 where
     TLayerCtx: Send,
 {
-    pub fn with_state<TState>(self, state: TState) -> MiddlewareState<TLayerCtx, TNewCtx, TState>
+    pub fn with_state<TState>(
+        self,
+        state: TState,
+    ) -> MiddlewareState<TLayerCtx, TNewCtx, TState, TMeta>
     where
         TState: Send,
     {
@@ -50,7 +53,7 @@ where
 }
 
 // This will match were TNewCtx is the default (`TCtx`) so it shouldn't let you call it if you've already swapped the generic
-impl<TLayerCtx, TState> MiddlewareState<TLayerCtx, TLayerCtx, TState>
+impl<TLayerCtx, TState, TMeta> MiddlewareState<TLayerCtx, TLayerCtx, TState, TMeta>
 where
     TLayerCtx: Send,
     TState: Send,
@@ -58,7 +61,7 @@ where
     pub fn with_ctx<TNewCtx>(
         self,
         new_ctx: TNewCtx,
-    ) -> MiddlewareState<TLayerCtx, TNewCtx, TState> {
+    ) -> MiddlewareState<TLayerCtx, TNewCtx, TState, TMeta> {
         MiddlewareState {
             state: self.state,
             input: self.input,
@@ -69,26 +72,26 @@ where
     }
 }
 
-pub struct Middleware<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut>
+pub struct Middleware<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut, TMeta = ()>
 where
     TState: Send,
     TLayerCtx: Send,
-    THandlerFunc: Fn(MiddlewareState<TLayerCtx, TLayerCtx, ()>) -> THandlerFut + Clone,
-    THandlerFut: Future<Output = Result<MiddlewareState<TLayerCtx, TNewCtx, TState>, crate::Error>>
+    THandlerFunc: Fn(MiddlewareState<TLayerCtx, TLayerCtx, (), TMeta>) -> THandlerFut + Clone,
+    THandlerFut: Future<Output = Result<MiddlewareState<TLayerCtx, TNewCtx, TState, TMeta>, crate::Error>>
         + Send
         + 'static,
 {
     handler: THandlerFunc,
-    phantom: PhantomData<(TState, TLayerCtx)>,
+    phantom: PhantomData<(TState, TLayerCtx, TMeta)>,
 }
 
-impl<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut> Clone
-    for Middleware<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut>
+impl<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut, TMeta> Clone
+    for Middleware<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut, TMeta>
 where
     TState: Send,
     TLayerCtx: Send,
-    THandlerFunc: Fn(MiddlewareState<TLayerCtx, TLayerCtx, ()>) -> THandlerFut + Clone,
-    THandlerFut: Future<Output = Result<MiddlewareState<TLayerCtx, TNewCtx, TState>, crate::Error>>
+    THandlerFunc: Fn(MiddlewareState<TLayerCtx, TLayerCtx, (), TMeta>) -> THandlerFut + Clone,
+    THandlerFut: Future<Output = Result<MiddlewareState<TLayerCtx, TNewCtx, TState, TMeta>, crate::Error>>
         + Send
         + 'static,
 {
@@ -100,23 +103,24 @@ where
     }
 }
 
-pub struct MiddlewareBuilder<TLayerCtx>(pub PhantomData<TLayerCtx>)
+pub struct MiddlewareBuilder<TLayerCtx, TMeta = ()>(pub PhantomData<(TLayerCtx, TMeta)>)
 where
     TLayerCtx: Send;
 
-impl<TLayerCtx> MiddlewareBuilder<TLayerCtx>
+impl<TLayerCtx, TMeta> MiddlewareBuilder<TLayerCtx, TMeta>
 where
     TLayerCtx: Send,
 {
     pub fn middleware<TState, TNewCtx, THandlerFunc, THandlerFut>(
         &self,
         handler: THandlerFunc,
-    ) -> Middleware<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut>
+    ) -> Middleware<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut, TMeta>
     where
         TState: Send,
-        THandlerFunc: Fn(MiddlewareState<TLayerCtx, TLayerCtx, ()>) -> THandlerFut + Clone,
-        THandlerFut: Future<Output = Result<MiddlewareState<TLayerCtx, TNewCtx, TState>, crate::Error>>
-            + Send
+        THandlerFunc: Fn(MiddlewareState<TLayerCtx, TLayerCtx, (), TMeta>) -> THandlerFut + Clone,
+        THandlerFut: Future<
+                Output = Result<MiddlewareState<TLayerCtx, TNewCtx, TState, TMeta>, crate::Error>,
+            > + Send
             + 'static,
     {
         Middleware {
@@ -126,13 +130,13 @@ where
     }
 }
 
-impl<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut>
-    Middleware<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut>
+impl<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut, TMeta>
+    Middleware<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut, TMeta>
 where
     TState: Send,
     TLayerCtx: Send,
-    THandlerFunc: Fn(MiddlewareState<TLayerCtx, TLayerCtx, ()>) -> THandlerFut + Clone,
-    THandlerFut: Future<Output = Result<MiddlewareState<TLayerCtx, TNewCtx, TState>, crate::Error>>
+    THandlerFunc: Fn(MiddlewareState<TLayerCtx, TLayerCtx, (), TMeta>) -> THandlerFut + Clone,
+    THandlerFut: Future<Output = Result<MiddlewareState<TLayerCtx, TNewCtx, TState, TMeta>, crate::Error>>
         + Send
         + 'static,
 {
@@ -147,6 +151,7 @@ where
         THandlerFut,
         TRespHandlerFunc,
         TRespHandlerFut,
+        TMeta,
     >
     where
         TRespHandlerFunc: Fn(TState, Value) -> TRespHandlerFut + Clone + Sync + Send + 'static,
@@ -170,21 +175,31 @@ pub struct MiddlewareWithResponseHandler<
     THandlerFut,
     TRespHandlerFunc,
     TRespHandlerFut,
+    TMeta = (),
 > where
     TState: Send,
     TLayerCtx: Send,
-    THandlerFunc: Fn(MiddlewareState<TLayerCtx, TLayerCtx, ()>) -> THandlerFut + Clone,
-    THandlerFut: Future<Output = Result<MiddlewareState<TLayerCtx, TNewCtx, TState>, crate::Error>>
+    THandlerFunc: Fn(MiddlewareState<TLayerCtx, TLayerCtx, (), TMeta>) -> THandlerFut + Clone,
+    THandlerFut: Future<Output = Result<MiddlewareState<TLayerCtx, TNewCtx, TState, TMeta>, crate::Error>>
         + Send
         + 'static,
     TRespHandlerFunc: Fn(TState, Value) -> TRespHandlerFut + Clone + Sync + Send + 'static,
     TRespHandlerFut: Future<Output = Result<Value, crate::Error>> + Send + 'static,
 {
-    inner: Middleware<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut>,
+    inner: Middleware<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut, TMeta>,
     resp_handler: TRespHandlerFunc,
 }
 
-impl<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut, TRespHandlerFunc, TRespHandlerFut> Clone
+impl<
+        TState,
+        TLayerCtx,
+        TNewCtx,
+        THandlerFunc,
+        THandlerFut,
+        TRespHandlerFunc,
+        TRespHandlerFut,
+        TMeta,
+    > Clone
     for MiddlewareWithResponseHandler<
         TState,
         TLayerCtx,
@@ -193,12 +208,13 @@ impl<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut, TRespHandlerFunc, TR
         THandlerFut,
         TRespHandlerFunc,
         TRespHandlerFut,
+        TMeta,
     >
 where
     TState: Send,
     TLayerCtx: Send,
-    THandlerFunc: Fn(MiddlewareState<TLayerCtx, TLayerCtx, ()>) -> THandlerFut + Clone,
-    THandlerFut: Future<Output = Result<MiddlewareState<TLayerCtx, TNewCtx, TState>, crate::Error>>
+    THandlerFunc: Fn(MiddlewareState<TLayerCtx, TLayerCtx, (), TMeta>) -> THandlerFut + Clone,
+    THandlerFut: Future<Output = Result<MiddlewareState<TLayerCtx, TNewCtx, TState, TMeta>, crate::Error>>
         + Send
         + 'static,
     TRespHandlerFunc: Fn(TState, Value) -> TRespHandlerFut + Clone + Sync + Send + 'static,
@@ -212,25 +228,26 @@ where
     }
 }
 
-impl<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut> MiddlewareLike<TLayerCtx>
-    for Middleware<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut>
+impl<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut, TMeta> MiddlewareLike<TLayerCtx, TMeta>
+    for Middleware<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut, TMeta>
 where
     TState: Clone + Send + Sync + 'static,
     TLayerCtx: Send,
     TNewCtx: Send + 'static,
-    THandlerFunc: Fn(MiddlewareState<TLayerCtx, TLayerCtx, ()>) -> THandlerFut + Clone,
-    THandlerFut: Future<Output = Result<MiddlewareState<TLayerCtx, TNewCtx, TState>, crate::Error>>
+    TMeta: Send + Sync + 'static,
+    THandlerFunc: Fn(MiddlewareState<TLayerCtx, TLayerCtx, (), TMeta>) -> THandlerFut + Clone,
+    THandlerFut: Future<Output = Result<MiddlewareState<TLayerCtx, TNewCtx, TState, TMeta>, crate::Error>>
         + Send
         + 'static,
 {
     type State = TState;
     type NewCtx = TNewCtx;
 
-    fn handle<TMiddleware: Layer<Self::NewCtx> + 'static>(
+    fn handle<TMiddleware: Layer<Self::NewCtx, TMeta> + 'static>(
         &self,
         ctx: TLayerCtx,
         input: Value,
-        req: RequestContext,
+        req: RequestContext<TMeta>,
         next: Arc<TMiddleware>,
     ) -> LayerFuture {
         let handler = (self.handler)(MiddlewareState {
@@ -248,8 +265,16 @@ where
     }
 }
 
-impl<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut, TRespHandlerFunc, TRespHandlerFut>
-    MiddlewareLike<TLayerCtx>
+impl<
+        TState,
+        TLayerCtx,
+        TNewCtx,
+        THandlerFunc,
+        THandlerFut,
+        TRespHandlerFunc,
+        TRespHandlerFut,
+        TMeta,
+    > MiddlewareLike<TLayerCtx, TMeta>
     for MiddlewareWithResponseHandler<
         TState,
         TLayerCtx,
@@ -258,13 +283,15 @@ impl<TState, TLayerCtx, TNewCtx, THandlerFunc, THandlerFut, TRespHandlerFunc, TR
         THandlerFut,
         TRespHandlerFunc,
         TRespHandlerFut,
+        TMeta,
     >
 where
     TState: Clone + Send + Sync + 'static,
     TLayerCtx: Send + 'static,
     TNewCtx: Send + 'static,
-    THandlerFunc: Fn(MiddlewareState<TLayerCtx, TLayerCtx, ()>) -> THandlerFut + Clone,
-    THandlerFut: Future<Output = Result<MiddlewareState<TLayerCtx, TNewCtx, TState>, crate::Error>>
+    TMeta: Send + Sync + 'static,
+    THandlerFunc: Fn(MiddlewareState<TLayerCtx, TLayerCtx, (), TMeta>) -> THandlerFut + Clone,
+    THandlerFut: Future<Output = Result<MiddlewareState<TLayerCtx, TNewCtx, TState, TMeta>, crate::Error>>
         + Send
         + 'static,
     TRespHandlerFunc: Fn(TState, Value) -> TRespHandlerFut + Clone + Sync + Send + 'static,
@@ -273,11 +300,11 @@ where
     type State = TState;
     type NewCtx = TNewCtx;
 
-    fn handle<TMiddleware: Layer<Self::NewCtx> + 'static>(
+    fn handle<TMiddleware: Layer<Self::NewCtx, TMeta> + 'static>(
         &self,
         ctx: TLayerCtx,
         input: Value,
-        req: RequestContext,
+        req: RequestContext<TMeta>,
         next: Arc<TMiddleware>,
     ) -> LayerFuture {
         let handler = (self.inner.handler)(MiddlewareState {
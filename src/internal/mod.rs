@@ -1,5 +1,11 @@
 //! Internal types which power rspc. The module provides no guarantee of compatibility between updates, so you should be careful rely on types from it.
 
+#[cfg(any(
+    feature = "tauri",
+    all(feature = "httpz", feature = "axum"),
+    feature = "testing"
+))]
+mod connection;
 pub mod jsonrpc;
 mod jsonrpc_exec;
 mod middleware;
@@ -8,12 +14,22 @@ mod procedure_builder;
 mod procedure_store;
 mod resolver;
 mod resolver_result;
+mod trace_context;
+mod validation;
 
+#[cfg(any(
+    feature = "tauri",
+    all(feature = "httpz", feature = "axum"),
+    feature = "testing"
+))]
+pub(crate) use connection::*;
 pub use middleware::*;
 pub use middleware_builder::*;
 pub use procedure_builder::*;
 pub use procedure_store::*;
 pub use resolver::*;
 pub use resolver_result::*;
+pub use trace_context::*;
+pub use validation::*;
 
 pub use specta;
@@ -4,108 +4,241 @@ use std::{
     marker::PhantomData,
     ops::Deref,
     sync::{Arc, RwLock},
+    time::Duration,
 };
 
-use super::{ProcedureDataType, ProcedureKind};
+use specta::{DataType, DefOpts, Type, TypeDefs};
+
+use super::{Deprecation, ProcedureDataType, ProcedureKind};
 
 pub type GlobalData = Arc<RwLock<HashMap<TypeId, Box<dyn Any + Send + Sync + 'static>>>>;
 
+/// returns the type of the data attached to a procedure's errors. This is a function so the type can be added to the router's type store when the procedure is registered.
+pub type ErrorTypeFn = fn(&mut TypeDefs) -> DataType;
+
+fn error_type<TError: Type>(defs: &mut TypeDefs) -> DataType {
+    TError::reference(
+        DefOpts {
+            parent_inline: false,
+            type_map: defs,
+        },
+        &[],
+    )
+}
+
+/// the options of a procedure which are copied into the [`BuiltProcedureBuilder`] once its resolver is provided.
+struct ProcedureOptions<TMeta> {
+    name: String,
+    kind: ProcedureKind,
+    typedef: ProcedureDataType,
+    data: GlobalData,
+    meta: Option<Arc<TMeta>>,
+    timeout: Option<Duration>,
+    error: Option<ErrorTypeFn>,
+}
+
+// This is manually implemented so `TMeta` doesn't have to be `Clone`.
+impl<TMeta> Clone for ProcedureOptions<TMeta> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            kind: self.kind.clone(),
+            typedef: self.typedef.clone(),
+            data: self.data.clone(),
+            meta: self.meta.clone(),
+            timeout: self.timeout,
+            error: self.error,
+        }
+    }
+}
+
+impl<TMeta> ProcedureOptions<TMeta> {
+    fn build<TResolver>(self, resolver: TResolver) -> BuiltProcedureBuilder<TResolver, TMeta> {
+        BuiltProcedureBuilder {
+            name: self.name,
+            kind: self.kind,
+            typedef: self.typedef,
+            data: self.data,
+            meta: self.meta,
+            timeout: self.timeout,
+            error: self.error,
+            resolver,
+        }
+    }
+}
+
 // TODO: Remove `TResolver` and put it into bounds on this type
-pub struct UnbuiltProcedureBuilder<TLayerCtx, TResolver> {
-    pub name: &'static str,
+pub struct UnbuiltProcedureBuilder<TLayerCtx, TResolver, TMeta = ()> {
+    pub name: String,
     pub kind: ProcedureKind,
     pub typedef: ProcedureDataType,
     pub data: GlobalData,
+    pub meta: Option<Arc<TMeta>>,
+    pub timeout: Option<Duration>,
+    pub error: Option<ErrorTypeFn>,
     // This can't be generic or a function pointer so boxing is a requirement in stable Rust. It's done at schema-build time so it should be ok.
     // For this to be done without boxing we would need `fn_traits` - https://doc.rust-lang.org/beta/unstable-book/library-features/fn-traits.html
-    deref_handler: Box<dyn Fn(TResolver) -> BuiltProcedureBuilder<TResolver>>,
+    deref_handler: Box<dyn Fn(TResolver) -> BuiltProcedureBuilder<TResolver, TMeta>>,
     phantom: PhantomData<TLayerCtx>,
 }
 
-impl<TLayerCtx, TResolver> UnbuiltProcedureBuilder<TLayerCtx, TResolver> {
+impl<TLayerCtx, TResolver, TMeta> UnbuiltProcedureBuilder<TLayerCtx, TResolver, TMeta>
+where
+    TMeta: 'static,
+{
     pub fn new(
-        name: &'static str,
+        name: String,
         kind: ProcedureKind,
         typedef: ProcedureDataType,
         data: GlobalData,
     ) -> Self {
-        Self {
+        Self::new_with_options(ProcedureOptions {
             name,
-            kind: kind.clone(),
-            typedef: typedef.clone(),
-            data: data.clone(),
-            // TODO: Make it so this is only boxed in the `Deref` impl so it's a zero cost abstraction!
-            deref_handler: Box::new(move |resolver| BuiltProcedureBuilder {
-                name,
-                kind: kind.clone(),
-                typedef: typedef.clone(),
-                data: data.clone(),
-                resolver,
-            }),
-            phantom: PhantomData,
-        }
+            kind,
+            typedef,
+            data,
+            meta: None,
+            timeout: None,
+            error: None,
+        })
     }
 
-    pub fn from_builder<T>(builder: &UnbuiltProcedureBuilder<TLayerCtx, T>) -> Self {
-        let (name, kind, typedef, data) = (
-            builder.name.clone(),
-            builder.kind.clone(),
-            builder.typedef.clone(),
-            builder.data.clone(),
-        );
-
+    fn new_with_options(options: ProcedureOptions<TMeta>) -> Self {
         Self {
-            name,
-            kind: kind.clone(),
-            typedef: typedef.clone(),
-            data: data.clone(),
-            deref_handler: Box::new(move |resolver| BuiltProcedureBuilder {
-                name: name.clone(),
-                kind: kind.clone(),
-                typedef: typedef.clone(),
-                data: data.clone(),
-                resolver,
-            }),
+            name: options.name.clone(),
+            kind: options.kind.clone(),
+            typedef: options.typedef.clone(),
+            data: options.data.clone(),
+            meta: options.meta.clone(),
+            timeout: options.timeout,
+            error: options.error,
+            // TODO: Make it so this is only boxed in the `Deref` impl so it's a zero cost abstraction!
+            deref_handler: Box::new(move |resolver| options.clone().build(resolver)),
             phantom: PhantomData,
         }
     }
 
-    pub fn resolver(self, resolver: TResolver) -> BuiltProcedureBuilder<TResolver> {
-        BuiltProcedureBuilder {
-            name: self.name,
-            kind: self.kind,
-            typedef: self.typedef,
-            data: self.data,
-            resolver,
+    fn options(&self) -> ProcedureOptions<TMeta> {
+        ProcedureOptions {
+            name: self.name.clone(),
+            kind: self.kind.clone(),
+            typedef: self.typedef.clone(),
+            data: self.data.clone(),
+            meta: self.meta.clone(),
+            timeout: self.timeout,
+            error: self.error,
         }
     }
 
+    pub fn from_builder<T>(builder: &UnbuiltProcedureBuilder<TLayerCtx, T, TMeta>) -> Self {
+        Self::new_with_options(builder.options())
+    }
+
+    /// attach metadata to the procedure. The value is of the router's `TMeta` type and can be read by middleware using [`RequestContext::meta`](crate::internal::RequestContext::meta).
+    pub fn meta(mut self, meta: TMeta) -> Self {
+        self.meta = Some(Arc::new(meta));
+        Self::from_builder(&self)
+    }
+
+    /// overrides the router's default timeout for this procedure. For queries and mutations this is how long the resolver can run for, for subscriptions it is how long the stream can go without yielding an item.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        Self::from_builder(&self)
+    }
+
+    /// declares the type of the data attached to errors returned by the procedure using [`Error::with_data`](crate::Error::with_data). This type is exported to Typescript.
+    pub fn error<TError: Type>(mut self) -> Self {
+        self.error = Some(error_type::<TError>);
+        Self::from_builder(&self)
+    }
+
+    /// describes what the procedure does. This is exported as JSDoc in the Typescript bindings and as the operation's description in OpenAPI.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.typedef.description = Some(description.into());
+        Self::from_builder(&self)
+    }
+
+    /// marks the procedure as deprecated. This accepts a message or a [`Deprecation`] with a replacement and sunset date.
+    pub fn deprecated(mut self, deprecation: impl Into<Deprecation>) -> Self {
+        self.typedef.deprecation = Some(deprecation.into());
+        Self::from_builder(&self)
+    }
+
+    /// sets the version of the API the procedure was introduced in. This is exported as `@since` in the Typescript bindings.
+    pub fn since(mut self, version: impl Into<String>) -> Self {
+        self.typedef.since = Some(version.into());
+        Self::from_builder(&self)
+    }
+
+    pub fn resolver(self, resolver: TResolver) -> BuiltProcedureBuilder<TResolver, TMeta> {
+        self.options().build(resolver)
+    }
+
     pub fn data(&self) -> GlobalData {
         self.data.clone()
     }
 }
 
-impl<TLayerCtx, TResolver> Deref for UnbuiltProcedureBuilder<TLayerCtx, TResolver> {
-    type Target = Box<dyn Fn(TResolver) -> BuiltProcedureBuilder<TResolver>>;
+impl<TLayerCtx, TResolver, TMeta> Deref for UnbuiltProcedureBuilder<TLayerCtx, TResolver, TMeta> {
+    type Target = Box<dyn Fn(TResolver) -> BuiltProcedureBuilder<TResolver, TMeta>>;
 
     fn deref(&self) -> &Self::Target {
         &self.deref_handler
     }
 }
 
-pub struct BuiltProcedureBuilder<TResolver> {
-    pub name: &'static str,
+pub struct BuiltProcedureBuilder<TResolver, TMeta = ()> {
+    pub name: String,
     pub kind: ProcedureKind,
     pub typedef: ProcedureDataType,
     pub data: GlobalData,
+    pub meta: Option<Arc<TMeta>>,
+    pub timeout: Option<Duration>,
+    pub error: Option<ErrorTypeFn>,
     pub resolver: TResolver,
 }
 
-impl<TResolver> BuiltProcedureBuilder<TResolver> {
+impl<TResolver, TMeta> BuiltProcedureBuilder<TResolver, TMeta> {
     pub fn map<TOutResolver>(
         self,
-        func: impl Fn(Self) -> BuiltProcedureBuilder<TOutResolver>,
-    ) -> BuiltProcedureBuilder<TOutResolver> {
+        func: impl Fn(Self) -> BuiltProcedureBuilder<TOutResolver, TMeta>,
+    ) -> BuiltProcedureBuilder<TOutResolver, TMeta> {
         func(self)
     }
+
+    /// attach metadata to the procedure. The value is of the router's `TMeta` type and can be read by middleware using [`RequestContext::meta`](crate::internal::RequestContext::meta).
+    pub fn meta(mut self, meta: TMeta) -> Self {
+        self.meta = Some(Arc::new(meta));
+        self
+    }
+
+    /// overrides the router's default timeout for this procedure. For queries and mutations this is how long the resolver can run for, for subscriptions it is how long the stream can go without yielding an item.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// declares the type of the data attached to errors returned by the procedure using [`Error::with_data`](crate::Error::with_data). This type is exported to Typescript.
+    pub fn error<TError: Type>(mut self) -> Self {
+        self.error = Some(error_type::<TError>);
+        self
+    }
+
+    /// describes what the procedure does. This is exported as JSDoc in the Typescript bindings and as the operation's description in OpenAPI.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.typedef.description = Some(description.into());
+        self
+    }
+
+    /// marks the procedure as deprecated. This accepts a message or a [`Deprecation`] with a replacement and sunset date.
+    pub fn deprecated(mut self, deprecation: impl Into<Deprecation>) -> Self {
+        self.typedef.deprecation = Some(deprecation.into());
+        self
+    }
+
+    /// sets the version of the API the procedure was introduced in. This is exported as `@since` in the Typescript bindings.
+    pub fn since(mut self, version: impl Into<String>) -> Self {
+        self.typedef.since = Some(version.into());
+        self
+    }
 }
@@ -1,6 +1,13 @@
-use std::collections::BTreeMap;
+use std::{
+    any::TypeId,
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
-use specta::DataType;
+use serde::Serialize;
+use serde_path_to_error::Segment;
+use specta::{DataType, TypeDefs};
 
 use crate::is_valid_procedure_name;
 
@@ -10,23 +17,152 @@ use super::Layer;
 pub struct ProcedureDataType {
     pub arg_ty: DataType,
     pub result_ty: DataType,
+    /// the type of the data attached to errors returned by the procedure. This is set using `.error::<T>()` on the procedure builder.
+    pub error_ty: Option<DataType>,
+    /// set using `.description()` on the procedure builder.
+    pub description: Option<String>,
+    /// set using `.deprecated()` on the procedure builder.
+    pub deprecation: Option<Deprecation>,
+    /// the version of the API the procedure was introduced in. This is set using `.since()` on the procedure builder.
+    pub since: Option<String>,
     /// TODO: Remove these
     pub inline_arg_ty: DataType,
     pub inline_result_ty: DataType,
 }
 
+/// marks a procedure as deprecated. This is exported to Typescript and OpenAPI and sent as the `Deprecation` and `Sunset` headers by the HTTP integration.
+#[derive(Debug, Clone, Serialize)]
+pub struct Deprecation {
+    pub message: String,
+    /// the key of the procedure which should be used instead.
+    pub replaced_by: Option<String>,
+    /// the date after which the procedure may be removed, formatted as an HTTP-date.
+    pub sunset: Option<String>,
+}
+
+impl Deprecation {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            replaced_by: None,
+            sunset: None,
+        }
+    }
+
+    pub fn replaced_by(mut self, key: impl Into<String>) -> Self {
+        self.replaced_by = Some(key.into());
+        self
+    }
+
+    pub fn sunset(mut self, date: SystemTime) -> Self {
+        self.sunset = Some(httpdate::fmt_http_date(date));
+        self
+    }
+}
+
+impl From<&str> for Deprecation {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<String> for Deprecation {
+    fn from(message: String) -> Self {
+        Self::new(message)
+    }
+}
+
+impl ProcedureDataType {
+    /// returns the type expected at the given path within the procedure's input.
+    /// `defs` must contain the definitions of the types referenced by the input, see [`referenced_types`].
+    pub fn arg_ty_at(&self, path: &serde_path_to_error::Path, defs: &TypeDefs) -> Option<DataType> {
+        let mut ty = self.inline_arg_ty.clone();
+        for segment in path.iter() {
+            loop {
+                ty = match ty {
+                    DataType::Nullable(inner) => *inner,
+                    DataType::Reference { name, .. } => defs.get(name.as_str())?.clone(),
+                    _ => break,
+                };
+            }
+
+            ty = match (ty, segment) {
+                (DataType::Object(obj), Segment::Map { key }) => {
+                    obj.fields.into_iter().find(|field| &field.name == key)?.ty
+                }
+                (DataType::List(inner), Segment::Seq { .. }) => *inner,
+                (DataType::Tuple(tuple), Segment::Seq { index }) => {
+                    tuple.fields.into_iter().nth(*index)?
+                }
+                (DataType::Record(record), Segment::Map { .. }) => record.1,
+                (DataType::Enum(e), Segment::Enum { variant }) => e
+                    .variants
+                    .into_iter()
+                    .find(|v| v.name() == variant)?
+                    .data_type(),
+                _ => return None,
+            };
+        }
+
+        Some(ty)
+    }
+}
+
+/// collects the definitions of all the types referenced by `ty` from `defs` into `out`.
+pub fn referenced_types(ty: &DataType, defs: &TypeDefs, out: &mut TypeDefs) {
+    match ty {
+        DataType::Any | DataType::Primitive(_) | DataType::Generic(_) => {}
+        DataType::List(ty) | DataType::Nullable(ty) => referenced_types(ty, defs, out),
+        DataType::Record(record) => {
+            referenced_types(&record.0, defs, out);
+            referenced_types(&record.1, defs, out);
+        }
+        DataType::Tuple(tuple) => {
+            for ty in &tuple.fields {
+                referenced_types(ty, defs, out);
+            }
+        }
+        DataType::Object(obj) => {
+            for field in &obj.fields {
+                referenced_types(&field.ty, defs, out);
+            }
+        }
+        DataType::Enum(e) => {
+            for variant in &e.variants {
+                referenced_types(&variant.data_type(), defs, out);
+            }
+        }
+        DataType::Reference { name, generics, .. } => {
+            for ty in generics {
+                referenced_types(ty, defs, out);
+            }
+
+            if let Some((name, def)) = defs.get_key_value(name.as_str()) {
+                if out.insert(name, def.clone()).is_none() {
+                    referenced_types(def, defs, out);
+                }
+            }
+        }
+    }
+}
+
 // TODO: Make private
-pub struct Procedure<TCtx> {
-    pub exec: Box<dyn Layer<TCtx>>,
+pub struct Procedure<TCtx, TMeta = ()> {
+    pub exec: Box<dyn Layer<TCtx, TMeta>>,
     pub ty: ProcedureDataType,
+    pub meta: Option<Arc<TMeta>>,
+    /// for queries and mutations this is the maximum execution time, for subscriptions it is the maximum time between items.
+    pub timeout: Option<Duration>,
+    /// the `TypeId`s of the argument and result types of the procedure's resolver. This is used to skip serialization when calling the procedure from Rust.
+    pub type_ids: Option<(TypeId, TypeId)>,
 }
 
-pub struct ProcedureStore<TCtx> {
+pub struct ProcedureStore<TCtx, TMeta = ()> {
     name: &'static str,
-    pub store: BTreeMap<String, Procedure<TCtx>>,
+    pub store: BTreeMap<String, Procedure<TCtx, TMeta>>,
 }
 
-impl<TCtx> ProcedureStore<TCtx> {
+impl<TCtx, TMeta> ProcedureStore<TCtx, TMeta> {
     pub fn new(name: &'static str) -> Self {
         Self {
             name,
@@ -34,7 +170,15 @@ impl<TCtx> ProcedureStore<TCtx> {
         }
     }
 
-    pub fn append(&mut self, key: String, exec: Box<dyn Layer<TCtx>>, ty: ProcedureDataType) {
+    pub fn append(
+        &mut self,
+        key: String,
+        exec: Box<dyn Layer<TCtx, TMeta>>,
+        ty: ProcedureDataType,
+        meta: Option<Arc<TMeta>>,
+        timeout: Option<Duration>,
+        type_ids: Option<(TypeId, TypeId)>,
+    ) {
         #[allow(clippy::panic)]
         if is_valid_procedure_name(&key) {
             panic!(
@@ -52,6 +196,15 @@ impl<TCtx> ProcedureStore<TCtx> {
             );
         }
 
-        self.store.insert(key, Procedure { exec, ty });
+        self.store.insert(
+            key,
+            Procedure {
+                exec,
+                ty,
+                meta,
+                timeout,
+                type_ids,
+            },
+        );
     }
 }
@@ -7,7 +7,7 @@ use specta::{DefOpts, Type, TypeDefs};
 
 use crate::{
     internal::{ProcedureDataType, RequestResult, StreamFuture, TypedRequestFuture},
-    ExecError,
+    DynamicStreamResolver, ExecError,
 };
 
 pub trait RequestResolver<TCtx, TMarker, TResultMarker>: Send + Sync + 'static {
@@ -62,6 +62,10 @@ where
                 },
                 &[],
             ),
+            error_ty: None,
+            description: None,
+            deprecation: None,
+            since: None,
             inline_arg_ty: <TArg as Type>::inline(
                 DefOpts {
                     parent_inline: true,
@@ -98,7 +102,8 @@ where
     TResult: Serialize + Type,
 {
     fn exec(&self, ctx: TCtx, input: Value) -> Result<StreamFuture, ExecError> {
-        let input = serde_json::from_value(input).map_err(ExecError::DeserializingArgErr)?;
+        let input = serde_path_to_error::deserialize(input)
+            .map_err(|err| ExecError::DeserializingArgErr(err, None))?;
 
         Ok(Box::pin(self(ctx, input).map(|v| {
             serde_json::to_value(&v).map_err(ExecError::SerializingResultErr)
@@ -121,6 +126,10 @@ where
                 },
                 &[],
             ),
+            error_ty: None,
+            description: None,
+            deprecation: None,
+            since: None,
             inline_arg_ty: <TArg as Type>::inline(
                 DefOpts {
                     parent_inline: true,
@@ -138,3 +147,49 @@ where
         }
     }
 }
+
+pub struct DynamicStreamMarker(/* private */ PhantomData<()>);
+impl<TCtx> StreamResolver<TCtx, DynamicStreamMarker> for DynamicStreamResolver<TCtx> {
+    fn exec(&self, ctx: TCtx, input: Value) -> Result<StreamFuture, ExecError> {
+        Ok(Box::pin(
+            self(ctx, input).map(|v| v.map_err(ExecError::ErrResolverError)),
+        ))
+    }
+
+    fn typedef(defs: &mut TypeDefs) -> ProcedureDataType {
+        ProcedureDataType {
+            arg_ty: <Value as Type>::reference(
+                DefOpts {
+                    parent_inline: false,
+                    type_map: defs,
+                },
+                &[],
+            ),
+            result_ty: <Value as Type>::reference(
+                DefOpts {
+                    parent_inline: false,
+                    type_map: defs,
+                },
+                &[],
+            ),
+            error_ty: None,
+            description: None,
+            deprecation: None,
+            since: None,
+            inline_arg_ty: <Value as Type>::inline(
+                DefOpts {
+                    parent_inline: true,
+                    type_map: defs,
+                },
+                &[],
+            ),
+            inline_result_ty: <Value as Type>::inline(
+                DefOpts {
+                    parent_inline: true,
+                    type_map: defs,
+                },
+                &[],
+            ),
+        }
+    }
+}
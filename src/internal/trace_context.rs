@@ -0,0 +1,103 @@
+use std::fmt;
+
+/// the trace context of a request parsed from a W3C `traceparent` header - https://www.w3.org/TR/trace-context/#traceparent-header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceParent {
+    /// the 32 character hex id of the trace the request is part of.
+    pub trace_id: String,
+    /// the 16 character hex id of the span which made the request.
+    pub parent_id: String,
+    pub sampled: bool,
+}
+
+impl TraceParent {
+    /// parses a `traceparent` header. Returns `None` if the header is invalid so the request starts a new trace.
+    pub fn parse(header: &str) -> Option<Self> {
+        let mut parts = header.trim().split('-');
+        let (version, trace_id, parent_id, flags) =
+            (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+
+        // Future versions may append fields so they're only rejected for version `00`
+        if !is_hex(version, 2) || version == "ff" || (version == "00" && parts.next().is_some()) {
+            return None;
+        }
+
+        if !is_hex(trace_id, 32) || !is_hex(parent_id, 16) || !is_hex(flags, 2) {
+            return None;
+        }
+
+        if trace_id.bytes().all(|b| b == b'0') || parent_id.bytes().all(|b| b == b'0') {
+            return None;
+        }
+
+        Some(Self {
+            trace_id: trace_id.to_string(),
+            parent_id: parent_id.to_string(),
+            sampled: u8::from_str_radix(flags, 16).ok()? & 1 == 1,
+        })
+    }
+
+    /// creates a span for a request made with this trace context which all the procedures it executes are children of.
+    ///
+    /// The trace context is always recorded in the span's `trace_id`, `parent_span_id` and `trace_sampled` fields.
+    /// With the `opentelemetry` feature the span's OpenTelemetry parent is also set to the remote span so the request continues the caller's trace
+    /// when spans are exported using `tracing-opentelemetry`.
+    #[cfg(feature = "tracing")]
+    pub fn span(trace_parent: Option<&Self>, name: &'static str) -> tracing::Span {
+        let span = tracing::info_span!(
+            "rspc.request",
+            rspc.transport = name,
+            trace_id = tracing::field::Empty,
+            parent_span_id = tracing::field::Empty,
+            trace_sampled = tracing::field::Empty,
+        );
+
+        if let Some(trace_parent) = trace_parent {
+            span.record("trace_id", trace_parent.trace_id.as_str());
+            span.record("parent_span_id", trace_parent.parent_id.as_str());
+            span.record("trace_sampled", trace_parent.sampled);
+
+            #[cfg(feature = "opentelemetry")]
+            if let Some(cx) = trace_parent.otel_context() {
+                use tracing_opentelemetry::OpenTelemetrySpanExt;
+                span.set_parent(cx);
+            }
+        }
+
+        span
+    }
+
+    /// the OpenTelemetry context of the remote span which made the request.
+    #[cfg(feature = "opentelemetry")]
+    pub fn otel_context(&self) -> Option<opentelemetry::Context> {
+        use opentelemetry::trace::{
+            SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+        };
+
+        let span_context = SpanContext::new(
+            TraceId::from_hex(&self.trace_id).ok()?,
+            SpanId::from_hex(&self.parent_id).ok()?,
+            match self.sampled {
+                true => TraceFlags::SAMPLED,
+                false => TraceFlags::default(),
+            },
+            true,
+            TraceState::default(),
+        );
+        Some(opentelemetry::Context::new().with_remote_span_context(span_context))
+    }
+}
+
+impl fmt::Display for TraceParent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "00-{}-{}-{:02x}",
+            self.trace_id, self.parent_id, self.sampled as u8
+        )
+    }
+}
+
+fn is_hex(s: &str, len: usize) -> bool {
+    s.len() == len && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
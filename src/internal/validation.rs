@@ -0,0 +1,247 @@
+use std::collections::{HashMap, HashSet};
+
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use specta::{Constraints, DataType, EnumRepr, EnumType, ObjectType, TypeDefs};
+
+/// a constraint declared on a procedure's input which was not met.
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationError {
+    /// the path to the invalid value, eg. `user.tags[1]`.
+    pub path: String,
+    /// the name of the constraint, eg. `minLength`.
+    pub constraint: &'static str,
+    pub message: String,
+}
+
+/// checks a procedure's input against the constraints declared on its fields before it is deserialized.
+pub(crate) struct InputValidator {
+    ty: DataType,
+    defs: TypeDefs,
+    patterns: HashMap<String, Regex>,
+}
+
+impl InputValidator {
+    /// returns `None` if the input has no constraints so procedures without them skip validation.
+    pub fn new(ty: &DataType, defs: &TypeDefs) -> Option<Self> {
+        let mut constraints = Vec::new();
+        collect_constraints(ty, defs, &mut HashSet::new(), &mut constraints);
+        if constraints.is_empty() {
+            return None;
+        }
+
+        let patterns = constraints
+            .into_iter()
+            .filter_map(|constraints| constraints.pattern)
+            .map(|pattern| {
+                #[allow(clippy::panic)]
+                let regex = Regex::new(&pattern).unwrap_or_else(|err| {
+                    panic!(
+                        "rspc error: invalid pattern '{}' on procedure argument: {}",
+                        pattern, err
+                    )
+                });
+                (pattern, regex)
+            })
+            .collect();
+
+        Some(Self {
+            ty: ty.clone(),
+            defs: defs.clone(),
+            patterns,
+        })
+    }
+
+    pub fn validate(&self, input: &Value) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        self.validate_value(input, &self.ty, "", &mut errors);
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+
+    fn validate_value(
+        &self,
+        value: &Value,
+        ty: &DataType,
+        path: &str,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        match (ty, value) {
+            (DataType::Nullable(_), Value::Null) => {}
+            (DataType::Nullable(ty), value) => self.validate_value(value, ty, path, errors),
+            (DataType::Reference { name, .. }, value) => {
+                if let Some(def) = self.defs.get(name.as_str()) {
+                    self.validate_value(value, def, path, errors);
+                }
+            }
+            (DataType::List(ty), Value::Array(items)) => {
+                for (i, item) in items.iter().enumerate() {
+                    self.validate_value(item, ty, &format!("{path}[{i}]"), errors);
+                }
+            }
+            // A tuple with a single field is serialized as the field
+            (DataType::Tuple(tuple), value) if tuple.fields.len() == 1 => {
+                self.validate_value(value, &tuple.fields[0], path, errors)
+            }
+            (DataType::Tuple(tuple), Value::Array(items)) => {
+                for (i, (ty, item)) in tuple.fields.iter().zip(items).enumerate() {
+                    self.validate_value(item, ty, &format!("{path}[{i}]"), errors);
+                }
+            }
+            (DataType::Record(record), Value::Object(map)) => {
+                for (key, item) in map {
+                    self.validate_value(item, &record.1, &join_path(path, key), errors);
+                }
+            }
+            (DataType::Object(obj), Value::Object(map)) => {
+                for field in &obj.fields {
+                    if let Some(item) = map.get(&field.name) {
+                        let path = join_path(path, &field.name);
+                        self.check_constraints(item, &field.constraints, &path, errors);
+                        self.validate_value(item, &field.ty, &path, errors);
+                    }
+                }
+            }
+            (DataType::Enum(e), value) => self.validate_enum(value, e, path, errors),
+            _ => {}
+        }
+    }
+
+    fn validate_enum(
+        &self,
+        value: &Value,
+        e: &EnumType,
+        path: &str,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let variant = |name: &str| e.variants.iter().find(|v| v.name() == name);
+        match (&e.repr, value) {
+            (EnumRepr::External, Value::Object(map)) if map.len() == 1 => {
+                if let Some((name, content)) = map.iter().next() {
+                    if let Some(variant) = variant(name) {
+                        let path = join_path(path, name);
+                        self.validate_value(content, &variant.data_type(), &path, errors);
+                    }
+                }
+            }
+            (EnumRepr::Internal { tag }, Value::Object(map)) => {
+                if let Some(variant) = map.get(tag).and_then(Value::as_str).and_then(variant) {
+                    self.validate_value(value, &variant.data_type(), path, errors);
+                }
+            }
+            (EnumRepr::Adjacent { tag, content }, Value::Object(map)) => {
+                let variant = map.get(tag).and_then(Value::as_str).and_then(variant);
+                if let (Some(variant), Some(value)) = (variant, map.get(content)) {
+                    let path = join_path(path, content);
+                    self.validate_value(value, &variant.data_type(), &path, errors);
+                }
+            }
+            // The variant of an untagged enum can't be known until it's deserialized
+            _ => {}
+        }
+    }
+
+    fn check_constraints(
+        &self,
+        value: &Value,
+        constraints: &Constraints,
+        path: &str,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let mut error = |constraint, message| {
+            errors.push(ValidationError {
+                path: path.to_string(),
+                constraint,
+                message,
+            })
+        };
+
+        match value {
+            Value::String(s) => {
+                let len = s.chars().count();
+                if let Some(min) = constraints.min_length.filter(|min| len < *min) {
+                    error("minLength", format!("must be at least {min} characters"));
+                }
+                if let Some(max) = constraints.max_length.filter(|max| len > *max) {
+                    error("maxLength", format!("must be at most {max} characters"));
+                }
+                if let Some(pattern) = &constraints.pattern {
+                    if matches!(self.patterns.get(pattern), Some(re) if !re.is_match(s)) {
+                        error("pattern", format!("must match the pattern '{pattern}'"));
+                    }
+                }
+            }
+            Value::Number(n) => {
+                let n = n.as_f64().unwrap_or_default();
+                if let Some(min) = constraints.min.filter(|min| n < *min) {
+                    error("minimum", format!("must be at least {min}"));
+                }
+                if let Some(max) = constraints.max.filter(|max| n > *max) {
+                    error("maximum", format!("must be at most {max}"));
+                }
+            }
+            Value::Array(items) => {
+                if let Some(min) = constraints.min_items.filter(|min| items.len() < *min) {
+                    error("minItems", format!("must contain at least {min} items"));
+                }
+                if let Some(max) = constraints.max_items.filter(|max| items.len() > *max) {
+                    error("maxItems", format!("must contain at most {max} items"));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    match path {
+        "" => key.to_string(),
+        path => format!("{path}.{key}"),
+    }
+}
+
+/// collects the constraints on all the fields reachable from `ty`.
+fn collect_constraints(
+    ty: &DataType,
+    defs: &TypeDefs,
+    visited: &mut HashSet<String>,
+    out: &mut Vec<Constraints>,
+) {
+    match ty {
+        DataType::Any | DataType::Primitive(_) | DataType::Generic(_) => {}
+        DataType::Nullable(ty) | DataType::List(ty) => collect_constraints(ty, defs, visited, out),
+        DataType::Record(record) => collect_constraints(&record.1, defs, visited, out),
+        DataType::Tuple(tuple) => {
+            for ty in &tuple.fields {
+                collect_constraints(ty, defs, visited, out);
+            }
+        }
+        DataType::Object(ObjectType { fields, .. }) => {
+            for field in fields {
+                if !field.constraints.is_empty() {
+                    out.push(field.constraints.clone());
+                }
+                collect_constraints(&field.ty, defs, visited, out);
+            }
+        }
+        DataType::Enum(e) => {
+            for variant in &e.variants {
+                collect_constraints(&variant.data_type(), defs, visited, out);
+            }
+        }
+        DataType::Reference { name, generics, .. } => {
+            for ty in generics {
+                collect_constraints(ty, defs, visited, out);
+            }
+
+            if visited.insert(name.clone()) {
+                if let Some(def) = defs.get(name.as_str()) {
+                    collect_constraints(def, defs, visited, out);
+                }
+            }
+        }
+    }
+}
@@ -13,23 +13,34 @@
     // missing_debug_implementations
 )]
 
+mod codec;
 mod config;
 mod error;
+mod live_router;
+mod metrics;
 mod router;
 mod router_builder;
+mod schema;
 mod selection;
 
+pub use codec::*;
 pub use config::*;
 pub use error::*;
+pub use live_router::*;
+pub use metrics::Metrics;
 pub use router::*;
 pub use router_builder::*;
+pub use schema::*;
 
 pub use selection::*;
 
 pub mod integrations;
 pub mod internal;
 pub mod plugins;
+#[cfg(feature = "testing")]
+pub mod testing;
 
+pub use internal::{Deprecation, ValidationError};
 pub use specta::RSPCType as Type;
 
 #[cfg(debug_assertions)]
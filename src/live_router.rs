@@ -0,0 +1,60 @@
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::Router;
+
+/// a handle to a router which can be replaced while the server is running.
+///
+/// Every request is executed on the router which was current when it was received, so subscriptions started before a swap keep running on the old router until they end.
+pub struct LiveRouter<TCtx = (), TMeta = ()>
+where
+    TCtx: 'static,
+{
+    router: Arc<RwLock<Arc<Router<TCtx, TMeta>>>>,
+}
+
+impl<TCtx, TMeta> LiveRouter<TCtx, TMeta>
+where
+    TCtx: 'static,
+{
+    pub fn new(router: impl Into<Arc<Router<TCtx, TMeta>>>) -> Self {
+        Self {
+            router: Arc::new(RwLock::new(router.into())),
+        }
+    }
+
+    /// returns the router new requests are executed on.
+    pub fn get(&self) -> Arc<Router<TCtx, TMeta>> {
+        self.router
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// replaces the router for all new requests and returns the previous one.
+    pub fn swap(&self, router: impl Into<Arc<Router<TCtx, TMeta>>>) -> Arc<Router<TCtx, TMeta>> {
+        std::mem::replace(
+            &mut *self.router.write().unwrap_or_else(PoisonError::into_inner),
+            router.into(),
+        )
+    }
+}
+
+impl<TCtx, TMeta> Clone for LiveRouter<TCtx, TMeta>
+where
+    TCtx: 'static,
+{
+    fn clone(&self) -> Self {
+        Self {
+            router: self.router.clone(),
+        }
+    }
+}
+
+impl<TCtx, TMeta> From<Arc<Router<TCtx, TMeta>>> for LiveRouter<TCtx, TMeta>
+where
+    TCtx: 'static,
+{
+    fn from(router: Arc<Router<TCtx, TMeta>>) -> Self {
+        Self::new(router)
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::Duration,
+};
+
+use crate::{internal::ProcedureKind, ErrorCode};
+
+// The upper bounds of the procedure latency histogram buckets in seconds
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Default)]
+struct ProcedureMetrics {
+    calls: u64,
+    errors: BTreeMap<i32, u64>,
+    buckets: [u64; BUCKETS.len()], // Each bucket counts every call which took less than its bound
+    duration_sum: f64,
+    duration_count: u64,
+}
+
+// The metrics of each procedure keyed by its kind and path
+type ProcedureMap = BTreeMap<(&'static str, String), Arc<Mutex<ProcedureMetrics>>>;
+
+#[derive(Default)]
+struct MetricsInner {
+    procedures: Mutex<ProcedureMap>,
+    connections: AtomicUsize,
+    subscriptions: AtomicUsize,
+}
+
+/// collects metrics about the procedures executed by a router, the open websocket connections and the active subscriptions.
+///
+/// It's enabled using [`Config::metrics`](crate::Config::metrics). The metrics can be served in the Prometheus text format using `.endpoint()` with the `httpz` feature or encoded using [`Metrics::encode`].
+#[derive(Clone, Default)]
+pub struct Metrics(Arc<MetricsInner>);
+
+impl Metrics {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub(crate) fn procedure(&self, kind: &ProcedureKind, path: &str) -> ProcedureMetricsHandle {
+        let mut procedures = self
+            .0
+            .procedures
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        ProcedureMetricsHandle {
+            metrics: self.clone(),
+            procedure: procedures
+                .entry((kind.to_str(), path.to_string()))
+                .or_default()
+                .clone(),
+            observe_duration: !matches!(kind, ProcedureKind::Subscription),
+        }
+    }
+
+    /// counts a websocket connection as open until the returned guard is dropped.
+    #[cfg(all(feature = "httpz", feature = "axum"))]
+    pub(crate) fn connection(&self) -> GaugeGuard {
+        GaugeGuard::new(self.clone(), |inner| &inner.connections)
+    }
+
+    /// encodes the metrics in the Prometheus text exposition format - https://prometheus.io/docs/instrumenting/exposition_formats/#text-based-format
+    pub fn encode(&self) -> String {
+        let procedures = self
+            .0
+            .procedures
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|((kind, path), procedure)| {
+                let labels = format!("kind=\"{}\",path=\"{}\"", kind, escape_label(path));
+                (labels, procedure.clone())
+            })
+            .collect::<Vec<_>>();
+
+        let mut calls = String::new();
+        let mut errors = String::new();
+        let mut durations = String::new();
+        for (labels, procedure) in procedures {
+            let procedure = procedure.lock().unwrap_or_else(PoisonError::into_inner);
+            let _ = writeln!(
+                calls,
+                "rspc_procedure_calls_total{{{labels}}} {}",
+                procedure.calls
+            );
+
+            for (code, count) in &procedure.errors {
+                let code = ErrorCode::from_status_code(*code as u16)
+                    .map(|code| format!("{:?}", code))
+                    .unwrap_or_else(|| code.to_string());
+                let _ = writeln!(
+                    errors,
+                    "rspc_procedure_errors_total{{{labels},code=\"{code}\"}} {count}"
+                );
+            }
+
+            if procedure.duration_count != 0 {
+                for (bound, count) in BUCKETS.iter().zip(procedure.buckets) {
+                    let _ = writeln!(
+                        durations,
+                        "rspc_procedure_duration_seconds_bucket{{{labels},le=\"{bound}\"}} {count}"
+                    );
+                }
+                let _ = writeln!(
+                    durations,
+                    "rspc_procedure_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}\n\
+                    rspc_procedure_duration_seconds_sum{{{labels}}} {}\n\
+                    rspc_procedure_duration_seconds_count{{{labels}}} {}",
+                    procedure.duration_count, procedure.duration_sum, procedure.duration_count
+                );
+            }
+        }
+
+        format!(
+            "# HELP rspc_procedure_calls_total The number of times each procedure was called.\n\
+            # TYPE rspc_procedure_calls_total counter\n\
+            {calls}\
+            # HELP rspc_procedure_errors_total The number of calls to each procedure which returned an error.\n\
+            # TYPE rspc_procedure_errors_total counter\n\
+            {errors}\
+            # HELP rspc_procedure_duration_seconds How long queries and mutations took to execute.\n\
+            # TYPE rspc_procedure_duration_seconds histogram\n\
+            {durations}\
+            # HELP rspc_websocket_connections The number of open websocket connections.\n\
+            # TYPE rspc_websocket_connections gauge\n\
+            rspc_websocket_connections {}\n\
+            # HELP rspc_subscriptions_active The number of running subscriptions.\n\
+            # TYPE rspc_subscriptions_active gauge\n\
+            rspc_subscriptions_active {}\n",
+            self.0.connections.load(Ordering::Relaxed),
+            self.0.subscriptions.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// records the calls to a single procedure.
+pub(crate) struct ProcedureMetricsHandle {
+    metrics: Metrics,
+    procedure: Arc<Mutex<ProcedureMetrics>>,
+    observe_duration: bool,
+}
+
+impl ProcedureMetricsHandle {
+    pub fn record(&self, duration: Duration, error_code: Option<i32>) {
+        let mut procedure = self
+            .procedure
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        procedure.calls += 1;
+
+        if let Some(code) = error_code {
+            *procedure.errors.entry(code).or_default() += 1;
+        }
+
+        if self.observe_duration {
+            let duration = duration.as_secs_f64();
+            for (bound, count) in BUCKETS.iter().zip(procedure.buckets.iter_mut()) {
+                if duration <= *bound {
+                    *count += 1;
+                }
+            }
+            procedure.duration_sum += duration;
+            procedure.duration_count += 1;
+        }
+    }
+
+    /// counts an error yielded by a subscription without counting it as a call.
+    pub fn record_error(&self, error_code: i32) {
+        let mut procedure = self
+            .procedure
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        *procedure.errors.entry(error_code).or_default() += 1;
+    }
+
+    /// counts a subscription as active until the returned guard is dropped.
+    pub fn subscription(&self) -> GaugeGuard {
+        GaugeGuard::new(self.metrics.clone(), |inner| &inner.subscriptions)
+    }
+}
+
+/// decrements a gauge when it's dropped.
+pub(crate) struct GaugeGuard {
+    metrics: Metrics,
+    gauge: fn(&MetricsInner) -> &AtomicUsize,
+}
+
+impl GaugeGuard {
+    fn new(metrics: Metrics, gauge: fn(&MetricsInner) -> &AtomicUsize) -> Self {
+        gauge(&metrics.0).fetch_add(1, Ordering::Relaxed);
+        Self { metrics, gauge }
+    }
+}
+
+impl Drop for GaugeGuard {
+    fn drop(&mut self) {
+        (self.gauge)(&self.metrics.0).fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,309 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+use crate::internal::{
+    Layer, LayerFuture, LayerReturn, MiddlewareLike, ProcedureKind, RequestContext, RequestFuture,
+};
+
+use super::call_next;
+
+type KeyFn<TCtx> = Arc<dyn Fn(&TCtx) -> String + Send + Sync>;
+
+/// how long the results of a query are cached for and how many results are kept.
+#[derive(Debug, Clone, Copy)]
+pub struct CachePolicy {
+    ttl: Duration,
+    max_entries: Option<usize>,
+}
+
+impl CachePolicy {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            max_entries: None,
+        }
+    }
+
+    /// limits how many different inputs are cached for the procedure. When the limit is reached the entry closest to expiring is evicted.
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+}
+
+struct Entry {
+    value: Value,
+    expires_at: Instant,
+}
+
+/// an in-memory cache for the results of queries. It is added to a router as a middleware using `.middleware(|_| cache.clone())`.
+///
+/// Results are keyed by the procedure's path and its input so only successful queries with the same input share a result.
+/// Mutations and subscriptions are never cached.
+///
+/// **The context is not part of the key by default so a cached result is returned to every caller.** Queries which return a different
+/// result depending on the context, eg. for the current user, must be scoped using [`QueryCache::context_key`] or not be given a policy,
+/// otherwise one user's result will be served to another.
+pub struct QueryCache<TCtx = ()> {
+    default_policy: Option<CachePolicy>,
+    policies: Arc<HashMap<String, CachePolicy>>,
+    invalidations: Arc<HashMap<String, Vec<String>>>,
+    key_fn: Option<KeyFn<TCtx>>,
+    entries: Arc<Mutex<HashMap<String, HashMap<String, Entry>>>>,
+    // is incremented on every invalidation so queries which started before it don't cache a stale result
+    generation: Arc<AtomicU64>,
+}
+
+impl QueryCache {
+    /// returns the key a query's result is cached under. The input is serialized with its object keys sorted so equal inputs always have the same key.
+    pub fn key(path: &str, input: &Value) -> String {
+        let mut key = format!("{path}:");
+        write_canonical(input, &mut key);
+        key
+    }
+}
+
+impl<TCtx> QueryCache<TCtx> {
+    pub fn new() -> Self {
+        Self {
+            default_policy: None,
+            policies: Default::default(),
+            invalidations: Default::default(),
+            key_fn: None,
+            entries: Default::default(),
+            generation: Default::default(),
+        }
+    }
+
+    /// caches every query which doesn't have its own policy.
+    pub fn default_policy(mut self, policy: CachePolicy) -> Self {
+        self.default_policy = Some(policy);
+        self
+    }
+
+    /// sets the policy for a single query.
+    pub fn procedure(mut self, key: impl Into<String>, policy: CachePolicy) -> Self {
+        Arc::make_mut(&mut self.policies).insert(key.into(), policy);
+        self
+    }
+
+    /// invalidates all entries starting with `prefix` when the mutation completes successfully.
+    pub fn invalidate_on(mut self, mutation: impl Into<String>, prefix: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.invalidations)
+            .entry(mutation.into())
+            .or_default()
+            .push(prefix.into());
+        self
+    }
+
+    /// sets the function which scopes the cached results, eg. to the user's id so each user gets their own result.
+    /// Invalidating a key or prefix removes the matching entries of every scope.
+    pub fn context_key(mut self, func: impl Fn(&TCtx) -> String + Send + Sync + 'static) -> Self {
+        self.key_fn = Some(Arc::new(func));
+        self
+    }
+
+    /// removes all entries with a key starting with `prefix`. A procedure's path can be used to invalidate all of its entries.
+    pub fn invalidate(&self, prefix: &str) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        for (path, procedure) in entries.iter_mut() {
+            if path.starts_with(prefix) {
+                procedure.clear();
+            } else if prefix.starts_with(path.as_str()) {
+                procedure.retain(|key, _| !key.starts_with(prefix));
+            }
+        }
+    }
+
+    /// removes every entry from the cache.
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        entries.clear();
+    }
+
+    fn policy(&self, path: &str) -> Option<CachePolicy> {
+        self.policies.get(path).copied().or(self.default_policy)
+    }
+
+    fn get(&self, path: &str, key: &str) -> Option<Value> {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let procedure = entries.get_mut(path)?;
+        match procedure.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.value.clone()),
+            Some(_) => {
+                procedure.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(
+        &self,
+        path: String,
+        key: String,
+        value: Value,
+        policy: CachePolicy,
+        generation: u64,
+    ) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        if self.generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+
+        let procedure = entries.entry(path).or_default();
+        // Expired entries are only removed when they are read so they are swept here to stop inputs which are never requested again from piling up.
+        procedure.retain(|_, entry| entry.expires_at > now);
+
+        if let Some(max_entries) = policy.max_entries {
+            while procedure.len() >= max_entries && !procedure.contains_key(&key) {
+                let oldest = procedure
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.expires_at)
+                    .map(|(key, _)| key.clone());
+                match oldest {
+                    Some(oldest) => procedure.remove(&oldest),
+                    None => return, // `max_entries` is 0
+                };
+            }
+        }
+
+        procedure.insert(
+            key,
+            Entry {
+                value,
+                expires_at: now + policy.ttl,
+            },
+        );
+    }
+}
+
+impl<TCtx> Default for QueryCache<TCtx> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<TCtx> Clone for QueryCache<TCtx> {
+    fn clone(&self) -> Self {
+        Self {
+            default_policy: self.default_policy,
+            policies: self.policies.clone(),
+            invalidations: self.invalidations.clone(),
+            key_fn: self.key_fn.clone(),
+            entries: self.entries.clone(),
+            generation: self.generation.clone(),
+        }
+    }
+}
+
+impl<TLayerCtx, TMeta> MiddlewareLike<TLayerCtx, TMeta> for QueryCache<TLayerCtx>
+where
+    TLayerCtx: Send + 'static,
+    TMeta: Send + Sync + 'static,
+{
+    type State = ();
+    type NewCtx = TLayerCtx;
+
+    fn handle<TMiddleware: Layer<Self::NewCtx, TMeta> + 'static>(
+        &self,
+        ctx: TLayerCtx,
+        input: Value,
+        req: RequestContext<TMeta>,
+        next: Arc<TMiddleware>,
+    ) -> LayerFuture {
+        match req.kind {
+            // Typed calls aren't given the input so they can't be told apart.
+            ProcedureKind::Query if !req.is_typed_call() => {
+                let policy = match self.policy(&req.path) {
+                    Some(policy) => policy,
+                    None => return call_next(&*next, ctx, input, req),
+                };
+
+                let key = QueryCache::key(&req.path, &input);
+                // The scope is a suffix so invalidating a key or prefix applies to every scope.
+                let key = match &self.key_fn {
+                    Some(key_fn) => format!("{key}\n{}", key_fn(&ctx)),
+                    None => key,
+                };
+                if let Some(value) = self.get(&req.path, &key) {
+                    return RequestFuture::Ready(Ok(value)).into();
+                }
+
+                let cache = self.clone();
+                let path = req.path.clone();
+                let generation = self.generation.load(Ordering::SeqCst);
+                let fut = call_next(&*next, ctx, input, req);
+                LayerFuture::Wrapped(Box::pin(async move {
+                    Ok(match fut.into_layer_return().await? {
+                        LayerReturn::Request(value) => {
+                            cache.insert(path, key, value.clone(), policy, generation);
+                            RequestFuture::Ready(Ok(value)).into()
+                        }
+                        LayerReturn::Stream(stream) => stream.into(),
+                    })
+                }))
+            }
+            ProcedureKind::Mutation if self.invalidations.contains_key(&req.path) => {
+                let cache = self.clone();
+                let path = req.path.clone();
+                let fut = call_next(&*next, ctx, input, req);
+                LayerFuture::Wrapped(Box::pin(async move {
+                    let result = fut.into_layer_return().await?;
+                    for prefix in cache.invalidations.get(&path).into_iter().flatten() {
+                        cache.invalidate(prefix);
+                    }
+
+                    Ok(match result {
+                        LayerReturn::Request(value) => RequestFuture::Ready(Ok(value)).into(),
+                        LayerReturn::Stream(stream) => stream.into(),
+                    })
+                }))
+            }
+            _ => call_next(&*next, ctx, input, req),
+        }
+    }
+}
+
+/// writes the JSON for a value with its object keys sorted.
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i != 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut fields = map.iter().collect::<Vec<_>>();
+            fields.sort_by(|a, b| a.0.cmp(b.0));
+
+            out.push('{');
+            for (i, (key, value)) in fields.into_iter().enumerate() {
+                if i != 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        value => out.push_str(&value.to_string()),
+    }
+}
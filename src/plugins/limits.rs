@@ -0,0 +1,340 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use futures::StreamExt;
+use serde_json::Value;
+use tokio::sync::Semaphore;
+
+use crate::{
+    internal::{
+        Layer, LayerFuture, LayerReturn, MiddlewareLike, RequestContext, RequestFuture,
+        StreamFuture,
+    },
+    ExecError,
+};
+
+use super::call_next;
+
+type KeyFn<TCtx> = Arc<dyn Fn(&TCtx) -> String + Send + Sync>;
+
+// The state of each limit keyed by the caller's key and the procedure's path
+type LimitState<T> = Arc<Mutex<HashMap<(String, String), T>>>;
+
+// The number of buckets or semaphores which are kept before the unused ones are removed
+const PRUNE_AFTER: usize = 1024;
+
+/// allows `requests` calls every `period`. Calls can be made in bursts of up to `requests` at once.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    requests: u32,
+    period: Duration,
+}
+
+impl RateLimit {
+    /// allows `requests` calls every `period`. Panics if either of them is zero.
+    pub fn new(requests: u32, period: Duration) -> Self {
+        #[allow(clippy::panic)]
+        if requests == 0 || period.is_zero() {
+            panic!(
+                "rspc error: attempted to create a rate limit of {} requests every {:?}, however both must be greater than zero.",
+                requests, period
+            );
+        }
+
+        Self { requests, period }
+    }
+
+    pub fn per_second(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(1))
+    }
+
+    pub fn per_minute(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(60))
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, limit: RateLimit, now: Instant) {
+        let rate = limit.requests as f64 / limit.period.as_secs_f64();
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(limit.requests as f64);
+        self.updated_at = now;
+    }
+}
+
+/// a middleware which limits how often a procedure can be called using a token bucket for each key and procedure.
+///
+/// By default all callers share the same limit. Use [`RateLimiter::key`] to give each user or IP address their own limit.
+/// A call over the limit fails with a `TooManyRequests` error and `handle_http` responds with a `Retry-After` header.
+pub struct RateLimiter<TCtx> {
+    default_limit: Option<RateLimit>,
+    limits: Arc<HashMap<String, RateLimit>>,
+    key_fn: Option<KeyFn<TCtx>>,
+    buckets: LimitState<Bucket>,
+}
+
+impl<TCtx> RateLimiter<TCtx> {
+    pub fn new() -> Self {
+        Self {
+            default_limit: None,
+            limits: Default::default(),
+            key_fn: None,
+            buckets: Default::default(),
+        }
+    }
+
+    /// limits every procedure which doesn't have its own limit.
+    pub fn default_limit(mut self, limit: RateLimit) -> Self {
+        self.default_limit = Some(limit);
+        self
+    }
+
+    /// sets the limit for a single procedure.
+    pub fn procedure(mut self, key: impl Into<String>, limit: RateLimit) -> Self {
+        Arc::make_mut(&mut self.limits).insert(key.into(), limit);
+        self
+    }
+
+    /// sets the function which determines who a call is counted against, eg. the user's id or IP address.
+    pub fn key(mut self, func: impl Fn(&TCtx) -> String + Send + Sync + 'static) -> Self {
+        self.key_fn = Some(Arc::new(func));
+        self
+    }
+
+    /// takes a token from the bucket or returns how long until one is available.
+    fn acquire(&self, key: String, path: &str, limit: RateLimit) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        if buckets.len() >= PRUNE_AFTER {
+            // Full buckets are the same as ones which don't exist so they can be removed
+            buckets.retain(|(_, path), bucket| {
+                let limit = self.limits.get(path).copied().or(self.default_limit);
+                match limit {
+                    Some(limit) => {
+                        bucket.refill(limit, now);
+                        bucket.tokens < limit.requests as f64
+                    }
+                    None => false,
+                }
+            });
+        }
+
+        let bucket = buckets
+            .entry((key, path.to_string()))
+            .or_insert_with(|| Bucket {
+                tokens: limit.requests as f64,
+                updated_at: now,
+            });
+        bucket.refill(limit, now);
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let rate = limit.requests as f64 / limit.period.as_secs_f64();
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+}
+
+impl<TCtx> Default for RateLimiter<TCtx> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<TCtx> Clone for RateLimiter<TCtx> {
+    fn clone(&self) -> Self {
+        Self {
+            default_limit: self.default_limit,
+            limits: self.limits.clone(),
+            key_fn: self.key_fn.clone(),
+            buckets: self.buckets.clone(),
+        }
+    }
+}
+
+impl<TLayerCtx, TMeta> MiddlewareLike<TLayerCtx, TMeta> for RateLimiter<TLayerCtx>
+where
+    TLayerCtx: Send + 'static,
+    TMeta: Send + Sync + 'static,
+{
+    type State = ();
+    type NewCtx = TLayerCtx;
+
+    fn handle<TMiddleware: Layer<Self::NewCtx, TMeta> + 'static>(
+        &self,
+        ctx: TLayerCtx,
+        input: Value,
+        req: RequestContext<TMeta>,
+        next: Arc<TMiddleware>,
+    ) -> LayerFuture {
+        let limit = match self.limits.get(&req.path).copied().or(self.default_limit) {
+            Some(limit) => limit,
+            None => return call_next(&*next, ctx, input, req),
+        };
+
+        let key = self.key_fn.as_ref().map(|f| f(&ctx)).unwrap_or_default();
+        match self.acquire(key, &req.path, limit) {
+            Ok(()) => call_next(&*next, ctx, input, req),
+            Err(retry_after) => {
+                RequestFuture::Ready(Err(ExecError::TooManyRequests(Some(retry_after)))).into()
+            }
+        }
+    }
+}
+
+/// a middleware which limits how many calls to a procedure can run at the same time for each key.
+///
+/// By default calls over the limit are rejected with a `TooManyRequests` error instead of waiting, use [`ConcurrencyLimiter::queue`] to let them wait for a running call to finish.
+/// A subscription counts towards the limit until its stream ends.
+pub struct ConcurrencyLimiter<TCtx> {
+    default_limit: Option<usize>,
+    limits: Arc<HashMap<String, usize>>,
+    key_fn: Option<KeyFn<TCtx>>,
+    queue_timeout: Option<Duration>,
+    semaphores: LimitState<Arc<Semaphore>>,
+}
+
+/// panics if a concurrency limit is zero as no call could ever run. Limits larger than a semaphore can hold are clamped.
+pub(crate) fn check_concurrency_limit(limit: usize) -> usize {
+    #[allow(clippy::panic)]
+    if limit == 0 {
+        panic!("rspc error: attempted to create a concurrency limit of 0, however it must be greater than zero.");
+    }
+
+    limit.min(Semaphore::MAX_PERMITS)
+}
+
+impl<TCtx> ConcurrencyLimiter<TCtx> {
+    pub fn new() -> Self {
+        Self {
+            default_limit: None,
+            limits: Default::default(),
+            key_fn: None,
+            queue_timeout: None,
+            semaphores: Default::default(),
+        }
+    }
+
+    /// limits every procedure which doesn't have its own limit. Panics if the limit is zero.
+    pub fn default_limit(mut self, limit: usize) -> Self {
+        self.default_limit = Some(check_concurrency_limit(limit));
+        self
+    }
+
+    /// sets the limit for a single procedure. Panics if the limit is zero.
+    pub fn procedure(mut self, key: impl Into<String>, limit: usize) -> Self {
+        Arc::make_mut(&mut self.limits).insert(key.into(), check_concurrency_limit(limit));
+        self
+    }
+
+    /// makes calls over the limit wait up to `timeout` for a running call to finish before they are rejected with a `TooManyRequests` error.
+    pub fn queue(mut self, timeout: Duration) -> Self {
+        self.queue_timeout = Some(timeout);
+        self
+    }
+
+    /// sets the function which determines who a call is counted against, eg. the user's id or IP address.
+    pub fn key(mut self, func: impl Fn(&TCtx) -> String + Send + Sync + 'static) -> Self {
+        self.key_fn = Some(Arc::new(func));
+        self
+    }
+
+    fn semaphore(&self, key: String, path: &str, limit: usize) -> Arc<Semaphore> {
+        let mut semaphores = self
+            .semaphores
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if semaphores.len() >= PRUNE_AFTER {
+            // Semaphores which aren't held by any call can be recreated when they are next needed
+            semaphores.retain(|_, semaphore| Arc::strong_count(semaphore) > 1);
+        }
+
+        semaphores
+            .entry((key, path.to_string()))
+            .or_insert_with(|| Arc::new(Semaphore::new(limit)))
+            .clone()
+    }
+}
+
+impl<TCtx> Default for ConcurrencyLimiter<TCtx> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<TCtx> Clone for ConcurrencyLimiter<TCtx> {
+    fn clone(&self) -> Self {
+        Self {
+            default_limit: self.default_limit,
+            limits: self.limits.clone(),
+            key_fn: self.key_fn.clone(),
+            queue_timeout: self.queue_timeout,
+            semaphores: self.semaphores.clone(),
+        }
+    }
+}
+
+impl<TLayerCtx, TMeta> MiddlewareLike<TLayerCtx, TMeta> for ConcurrencyLimiter<TLayerCtx>
+where
+    TLayerCtx: Send + 'static,
+    TMeta: Send + Sync + 'static,
+{
+    type State = ();
+    type NewCtx = TLayerCtx;
+
+    fn handle<TMiddleware: Layer<Self::NewCtx, TMeta> + 'static>(
+        &self,
+        ctx: TLayerCtx,
+        input: Value,
+        req: RequestContext<TMeta>,
+        next: Arc<TMiddleware>,
+    ) -> LayerFuture {
+        let limit = match self.limits.get(&req.path).copied().or(self.default_limit) {
+            Some(limit) => limit,
+            None => return call_next(&*next, ctx, input, req),
+        };
+
+        let key = self.key_fn.as_ref().map(|f| f(&ctx)).unwrap_or_default();
+        let semaphore = self.semaphore(key, &req.path, limit);
+        let queue_timeout = self.queue_timeout;
+        LayerFuture::Wrapped(Box::pin(async move {
+            let permit = match (semaphore.clone().try_acquire_owned(), queue_timeout) {
+                (Ok(permit), _) => permit,
+                (Err(_), Some(timeout)) => {
+                    match tokio::time::timeout(timeout, semaphore.acquire_owned()).await {
+                        Ok(Ok(permit)) => permit,
+                        _ => return Err(ExecError::TooManyRequests(None)),
+                    }
+                }
+                (Err(_), None) => return Err(ExecError::TooManyRequests(None)),
+            };
+
+            Ok(
+                match call_next(&*next, ctx, input, req)
+                    .into_layer_return()
+                    .await?
+                {
+                    LayerReturn::Request(value) => RequestFuture::Ready(Ok(value)).into(),
+                    LayerReturn::Stream(stream) => {
+                        // The permit is moved into the stream so it's held until the subscription ends
+                        let stream: StreamFuture = Box::pin(stream.map(move |item| {
+                            let _permit = &permit;
+                            item
+                        }));
+                        stream.into()
+                    }
+                },
+            )
+        }))
+    }
+}
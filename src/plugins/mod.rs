@@ -1,2 +1,25 @@
+use serde_json::Value;
+
+use crate::internal::{Layer, LayerFuture, RequestContext, RequestFuture};
+
+pub mod cache;
+pub mod limits;
+pub mod single_flight;
+
 #[cfg(feature = "openapi")]
 pub mod openapi;
+
+/// calls the next layer of the middleware stack, converting an error into a failed request.
+pub(crate) fn call_next<
+    TLayerCtx: 'static,
+    TMeta: 'static,
+    TMiddleware: Layer<TLayerCtx, TMeta>,
+>(
+    next: &TMiddleware,
+    ctx: TLayerCtx,
+    input: Value,
+    req: RequestContext<TMeta>,
+) -> LayerFuture {
+    next.call(ctx, input, req)
+        .unwrap_or_else(|err| RequestFuture::Ready(Err(err)).into())
+}
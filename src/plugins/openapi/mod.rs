@@ -25,7 +25,7 @@ static SWAGGER_UI: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/src/plugins/opena
 
 #[derive(Debug, Clone)]
 pub struct OpenAPIEndpoint {
-    pub procedure_name: &'static str,
+    pub procedure_name: String,
     pub procedure_type: ExecKind,
     pub arg_schema: ReferenceOr<Schema>,
     pub result_schema: ReferenceOr<Schema>,
@@ -44,11 +44,11 @@ pub struct OpenAPIConfig {
     pub base_url: Cow<'static, str>,
 }
 
-pub trait OpenAPI<TResolver> {
-    fn openapi(self, method: Method, url: &'static str) -> BuiltProcedureBuilder<TResolver>;
+pub trait OpenAPI<TResolver, TMeta = ()> {
+    fn openapi(self, method: Method, url: &'static str) -> BuiltProcedureBuilder<TResolver, TMeta>;
 }
 
-impl<TResolver> OpenAPI<TResolver> for BuiltProcedureBuilder<TResolver> {
+impl<TResolver, TMeta> OpenAPI<TResolver, TMeta> for BuiltProcedureBuilder<TResolver, TMeta> {
     fn openapi(self, method: Method, url: &'static str) -> Self {
         {
             let mut data = self.data.write().unwrap();
@@ -77,7 +77,7 @@ impl<TResolver> OpenAPI<TResolver> for BuiltProcedureBuilder<TResolver> {
             ctx.endpoints.insert(
                 (method, url),
                 OpenAPIEndpoint {
-                    procedure_name: self.name,
+                    procedure_name: self.name.clone(),
                     procedure_type: match self.kind {
                         ProcedureKind::Query => ExecKind::Query,
                         ProcedureKind::Mutation => ExecKind::Mutation,
@@ -140,7 +140,19 @@ where
                             (
                                 url.to_string(),
                                 ReferenceOr::Item({
+                                    let procedure = match procedure_type {
+                                        ExecKind::Query => self.queries.store.get(procedure_name),
+                                        ExecKind::Mutation => {
+                                            self.mutations.store.get(procedure_name)
+                                        }
+                                    };
+
                                     let operation = Operation {
+                                        description: procedure
+                                            .and_then(|procedure| procedure.ty.description.clone()),
+                                        deprecated: procedure
+                                            .map(|procedure| procedure.ty.deprecation.is_some())
+                                            .unwrap_or(false),
                                         operation_id: Some(format!(
                                             "{}-{}",
                                             match procedure_type {
@@ -0,0 +1,195 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+};
+
+use futures::{
+    future::{BoxFuture, Shared, WeakShared},
+    FutureExt,
+};
+use serde_json::Value;
+
+use crate::{
+    internal::{
+        Layer, LayerFuture, LayerReturn, MiddlewareLike, ProcedureKind, RequestContext,
+        RequestFuture,
+    },
+    Error, ExecError,
+};
+
+use super::{cache::QueryCache, call_next};
+
+type KeyFn<TCtx> = Arc<dyn Fn(&TCtx) -> String + Send + Sync>;
+
+type Flight = Shared<BoxFuture<'static, Result<Value, Error>>>;
+
+type Flights =
+    Arc<Mutex<HashMap<String, (u64, WeakShared<BoxFuture<'static, Result<Value, Error>>>)>>>;
+
+static NEXT_FLIGHT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// removes a flight from the in-flight calls once it has finished or every call waiting on it has been dropped.
+struct FlightGuard {
+    flights: Flights,
+    key: String,
+    id: u64,
+}
+
+impl Drop for FlightGuard {
+    fn drop(&mut self) {
+        let mut flights = self.flights.lock().unwrap_or_else(PoisonError::into_inner);
+        // A new flight for the same key may have already replaced this one
+        if flights.get(&self.key).map(|(id, _)| *id) == Some(self.id) {
+            flights.remove(&self.key);
+        }
+    }
+}
+
+/// a middleware which shares one execution of a query between all of the identical calls made while it's running.
+///
+/// Calls are identical when they have the same path and input. Queries which return a different result depending on the context
+/// should either be excluded using [`SingleFlight::exclude`] or scoped using [`SingleFlight::key`] so calls are only shared between the same user.
+/// Mutations and subscriptions are never shared.
+pub struct SingleFlight<TCtx> {
+    all: bool,
+    procedures: Arc<HashSet<String>>,
+    excluded: Arc<HashSet<String>>,
+    key_fn: Option<KeyFn<TCtx>>,
+    in_flight: Flights,
+}
+
+impl<TCtx> SingleFlight<TCtx> {
+    /// creates a middleware which shares every query which isn't excluded.
+    pub fn new() -> Self {
+        Self {
+            all: true,
+            procedures: Default::default(),
+            excluded: Default::default(),
+            key_fn: None,
+            in_flight: Default::default(),
+        }
+    }
+
+    /// creates a middleware which only shares the queries added using [`SingleFlight::procedure`].
+    pub fn only() -> Self {
+        Self {
+            all: false,
+            ..Self::new()
+        }
+    }
+
+    /// shares the calls to a single query.
+    pub fn procedure(mut self, key: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.procedures).insert(key.into());
+        self
+    }
+
+    /// never shares the calls to a query, eg. because its result depends on the context.
+    pub fn exclude(mut self, key: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.excluded).insert(key.into());
+        self
+    }
+
+    /// sets the function which scopes the calls which can be shared, eg. to the user's id so each user gets their own result.
+    pub fn key(mut self, func: impl Fn(&TCtx) -> String + Send + Sync + 'static) -> Self {
+        self.key_fn = Some(Arc::new(func));
+        self
+    }
+
+    fn is_enabled(&self, path: &str) -> bool {
+        (self.all || self.procedures.contains(path)) && !self.excluded.contains(path)
+    }
+}
+
+impl<TCtx> Default for SingleFlight<TCtx> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<TCtx> Clone for SingleFlight<TCtx> {
+    fn clone(&self) -> Self {
+        Self {
+            all: self.all,
+            procedures: self.procedures.clone(),
+            excluded: self.excluded.clone(),
+            key_fn: self.key_fn.clone(),
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+impl<TLayerCtx, TMeta> MiddlewareLike<TLayerCtx, TMeta> for SingleFlight<TLayerCtx>
+where
+    TLayerCtx: Send + 'static,
+    TMeta: Send + Sync + 'static,
+{
+    type State = ();
+    type NewCtx = TLayerCtx;
+
+    fn handle<TMiddleware: Layer<Self::NewCtx, TMeta> + 'static>(
+        &self,
+        ctx: TLayerCtx,
+        input: Value,
+        req: RequestContext<TMeta>,
+        next: Arc<TMiddleware>,
+    ) -> LayerFuture {
+        // Typed calls aren't given the input so they can't be told apart.
+        if !matches!(req.kind, ProcedureKind::Query)
+            || req.is_typed_call()
+            || !self.is_enabled(&req.path)
+        {
+            return call_next(&*next, ctx, input, req);
+        }
+
+        let scope = self.key_fn.as_ref().map(|f| f(&ctx)).unwrap_or_default();
+        let key = format!("{scope}\n{}", QueryCache::key(&req.path, &input));
+
+        // The flight is claimed while holding the lock so identical calls which arrive together can't both start an execution.
+        // It doesn't run until it's first polled so no middleware or resolver is called while the lock is held.
+        let flight = {
+            let mut in_flight = self
+                .in_flight
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            match in_flight.get(&key).and_then(|(_, flight)| flight.upgrade()) {
+                Some(flight) => flight,
+                None => {
+                    let guard = FlightGuard {
+                        flights: self.in_flight.clone(),
+                        key: key.clone(),
+                        id: NEXT_FLIGHT_ID.fetch_add(1, Ordering::Relaxed),
+                    };
+                    let id = guard.id;
+                    let flight: Flight = async move {
+                        // New calls start their own execution once the result is available
+                        let _guard = guard;
+                        match call_next(&*next, ctx, input, req).into_layer_return().await {
+                            Ok(LayerReturn::Request(value)) => Ok(value),
+                            Ok(LayerReturn::Stream(_)) => {
+                                Err(ExecError::UnsupportedMethod("Subscription".to_string()).into())
+                            }
+                            Err(err) => Err(err.into()),
+                        }
+                    }
+                    .boxed()
+                    .shared();
+
+                    // Only a weak reference is kept so a flight which every call has stopped waiting on is dropped
+                    if let Some(weak) = flight.downgrade() {
+                        in_flight.insert(key, (id, weak));
+                    }
+                    flight
+                }
+            }
+        };
+
+        RequestFuture::Future(Box::pin(async move {
+            flight.await.map_err(ExecError::ErrResolverError)
+        }))
+        .into()
+    }
+}
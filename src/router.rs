@@ -1,20 +1,28 @@
 use std::{
+    any::{Any, TypeId},
     collections::BTreeMap,
     fs::{self, File},
     io::Write,
     marker::PhantomData,
     path::{Path, PathBuf},
     pin::Pin,
-    sync::Arc,
+    sync::{Arc, Mutex},
 };
 
 use futures::Stream;
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
-use specta::{to_ts, to_ts_export, DataType, TypeDefs};
+use specta::{
+    rust_inline_defs, snake_case_ident, to_rust, to_rust_export, to_ts, to_ts_export, DataType,
+    TypeDefs,
+};
 
 use crate::{
-    internal::{GlobalData, LayerReturn, Procedure, ProcedureKind, ProcedureStore, RequestContext},
-    Config, ExecError, ExportError,
+    internal::{
+        GlobalData, LayerReturn, Procedure, ProcedureDataType, ProcedureKind, ProcedureStore,
+        RequestContext, TypedCall,
+    },
+    Config, ExecError, ExportError, RouterSchema,
 };
 
 /// TODO
@@ -24,9 +32,9 @@ where
 {
     pub data: GlobalData,
     pub(crate) config: Config,
-    pub(crate) queries: ProcedureStore<TCtx>,
-    pub(crate) mutations: ProcedureStore<TCtx>,
-    pub(crate) subscriptions: ProcedureStore<TCtx>,
+    pub(crate) queries: ProcedureStore<TCtx, TMeta>,
+    pub(crate) mutations: ProcedureStore<TCtx, TMeta>,
+    pub(crate) subscriptions: ProcedureStore<TCtx, TMeta>,
     pub(crate) typ_store: TypeDefs,
     pub(crate) phantom: PhantomData<TMeta>,
 }
@@ -39,9 +47,51 @@ pub enum ExecKind {
     Mutation,
 }
 
+/// a handle to a query or mutation which is returned by [`Router::query_handle`] or [`Router::mutation_handle`].
+/// It carries the argument and result types of the procedure's resolver so it can be called using [`Router::call`].
+pub struct ProcedureHandle<TArg, TResult> {
+    kind: ExecKind,
+    key: String,
+    phantom: PhantomData<fn(TArg) -> TResult>,
+}
+
+impl<TArg, TResult> ProcedureHandle<TArg, TResult> {
+    pub(crate) fn new(kind: ExecKind, key: String) -> Self {
+        Self {
+            kind,
+            key,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn kind(&self) -> ExecKind {
+        self.kind
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl<TArg, TResult> Clone for ProcedureHandle<TArg, TResult> {
+    fn clone(&self) -> Self {
+        Self::new(self.kind, self.key.clone())
+    }
+}
+
+impl<TArg, TResult> std::fmt::Debug for ProcedureHandle<TArg, TResult> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProcedureHandle")
+            .field("kind", &self.kind)
+            .field("key", &self.key)
+            .finish()
+    }
+}
+
 impl<TCtx, TMeta> Router<TCtx, TMeta>
 where
     TCtx: 'static,
+    TMeta: 'static,
 {
     pub async fn exec(
         &self,
@@ -55,9 +105,11 @@ where
             ExecKind::Mutation => (&self.mutations.store, ProcedureKind::Mutation),
         };
 
-        match operations
+        let procedure = operations
             .get(&key)
-            .ok_or_else(|| ExecError::OperationNotFound(key.clone()))?
+            .ok_or_else(|| ExecError::OperationNotFound(key.clone()))?;
+
+        match procedure
             .exec
             .call(
                 ctx,
@@ -65,9 +117,11 @@ where
                 RequestContext {
                     kind,
                     path: key.clone(),
+                    meta: procedure.meta.clone(),
+                    typed: None,
                 },
             )?
-            .into_layer_return()
+            .into_layer_return_with_timeout(procedure.timeout)
             .await?
         {
             LayerReturn::Request(v) => Ok(v),
@@ -75,17 +129,127 @@ where
         }
     }
 
+    /// returns a handle to the query registered under `key` which can be used to call it from Rust using [`Router::call`].
+    /// This fails if the router has no query with the key or its resolver doesn't take `TArg` and return `TResult`.
+    pub fn query_handle<TArg, TResult>(
+        &self,
+        key: impl Into<String>,
+    ) -> Result<ProcedureHandle<TArg, TResult>, ExecError>
+    where
+        TArg: 'static,
+        TResult: 'static,
+    {
+        let handle = ProcedureHandle::new(ExecKind::Query, key.into());
+        self.typed_procedure(&handle)?;
+        Ok(handle)
+    }
+
+    /// returns a handle to the mutation registered under `key` which can be used to call it from Rust using [`Router::call`].
+    /// This fails if the router has no mutation with the key or its resolver doesn't take `TArg` and return `TResult`.
+    pub fn mutation_handle<TArg, TResult>(
+        &self,
+        key: impl Into<String>,
+    ) -> Result<ProcedureHandle<TArg, TResult>, ExecError>
+    where
+        TArg: 'static,
+        TResult: 'static,
+    {
+        let handle = ProcedureHandle::new(ExecKind::Mutation, key.into());
+        self.typed_procedure(&handle)?;
+        Ok(handle)
+    }
+
+    /// returns the procedure a handle refers to. The types are checked again as the handle could be from a different router.
+    fn typed_procedure<TArg, TResult>(
+        &self,
+        handle: &ProcedureHandle<TArg, TResult>,
+    ) -> Result<(&Procedure<TCtx, TMeta>, ProcedureKind), ExecError>
+    where
+        TArg: 'static,
+        TResult: 'static,
+    {
+        let (operations, kind) = match handle.kind {
+            ExecKind::Query => (&self.queries.store, ProcedureKind::Query),
+            ExecKind::Mutation => (&self.mutations.store, ProcedureKind::Mutation),
+        };
+
+        let procedure = operations
+            .get(&handle.key)
+            .ok_or_else(|| ExecError::OperationNotFound(handle.key.clone()))?;
+
+        match procedure.type_ids {
+            Some(type_ids) if type_ids == (TypeId::of::<TArg>(), TypeId::of::<TResult>()) => {
+                Ok((procedure, kind))
+            }
+            _ => Err(ExecError::ProcedureTypeMismatch(handle.key.clone())),
+        }
+    }
+
+    /// calls a query or mutation from Rust using a handle from [`Router::query_handle`] or [`Router::mutation_handle`].
+    /// The procedure is executed through the router's middleware the same as any other request, however the resolver is given `input` as is
+    /// and its result is returned without being serialized. The middleware is given `null` in place of the input and result.
+    pub async fn call<TArg, TResult>(
+        &self,
+        ctx: TCtx,
+        handle: &ProcedureHandle<TArg, TResult>,
+        input: TArg,
+    ) -> Result<TResult, ExecError>
+    where
+        TArg: Serialize + Send + 'static,
+        TResult: DeserializeOwned + 'static,
+    {
+        let (procedure, kind) = self.typed_procedure(handle)?;
+        let typed = Arc::new(TypedCall {
+            input: Mutex::new(Some(Box::new(input))),
+            result: Mutex::new(None),
+            serialize_input: serialize_input::<TArg>,
+        });
+
+        let result = match procedure
+            .exec
+            .call(
+                ctx,
+                Value::Null,
+                RequestContext {
+                    kind,
+                    path: handle.key.clone(),
+                    meta: procedure.meta.clone(),
+                    typed: Some(typed.clone()),
+                },
+            )?
+            .into_layer_return_with_timeout(procedure.timeout)
+            .await?
+        {
+            LayerReturn::Request(v) => v,
+            LayerReturn::Stream(_) => return Err(ExecError::UnsupportedMethod(handle.key.clone())),
+        };
+
+        // A middleware which returns a value of its own instead of the resolver's result has it deserialized.
+        let typed_result = typed.result.lock().ok().and_then(|mut v| v.take());
+        match (result, typed_result) {
+            (Value::Null, Some(v)) => v
+                .downcast::<TResult>()
+                .map(|v| *v)
+                .map_err(|_| ExecError::ProcedureTypeMismatch(handle.key.clone())),
+            (result, _) => {
+                serde_json::from_value(result).map_err(ExecError::DeserializingResultErr)
+            }
+        }
+    }
+
     pub async fn exec_subscription(
         &self,
         ctx: TCtx,
         key: String,
         input: Option<Value>,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<Value, ExecError>> + Send>>, ExecError> {
-        match self
+        let procedure = self
             .subscriptions
             .store
             .get(&key)
-            .ok_or_else(|| ExecError::OperationNotFound(key.clone()))?
+            .ok_or_else(|| ExecError::OperationNotFound(key.clone()))?;
+
+        match procedure
             .exec
             .call(
                 ctx,
@@ -93,9 +257,11 @@ where
                 RequestContext {
                     kind: ProcedureKind::Subscription,
                     path: key.clone(),
+                    meta: procedure.meta.clone(),
+                    typed: None,
                 },
             )?
-            .into_layer_return()
+            .into_layer_return_with_timeout(procedure.timeout)
             .await?
         {
             LayerReturn::Request(_) => Err(ExecError::UnsupportedMethod(key)),
@@ -111,18 +277,28 @@ where
         self.typ_store.clone()
     }
 
-    pub fn queries(&self) -> &BTreeMap<String, Procedure<TCtx>> {
+    pub fn queries(&self) -> &BTreeMap<String, Procedure<TCtx, TMeta>> {
         &self.queries.store
     }
 
-    pub fn mutations(&self) -> &BTreeMap<String, Procedure<TCtx>> {
+    pub fn mutations(&self) -> &BTreeMap<String, Procedure<TCtx, TMeta>> {
         &self.mutations.store
     }
 
-    pub fn subscriptions(&self) -> &BTreeMap<String, Procedure<TCtx>> {
+    pub fn subscriptions(&self) -> &BTreeMap<String, Procedure<TCtx, TMeta>> {
         &self.subscriptions.store
     }
 
+    /// returns a serializable description of all the procedures on the router and the types they reference.
+    pub fn schema(&self) -> RouterSchema {
+        RouterSchema::new(
+            &self.queries.store,
+            &self.mutations.store,
+            &self.subscriptions.store,
+            &self.typ_store,
+        )
+    }
+
     pub fn export_ts<TPath: AsRef<Path>>(&self, export_path: TPath) -> Result<(), ExportError> {
         let export_path = PathBuf::from(export_path.as_ref());
         if let Some(export_dir) = export_path.parent() {
@@ -155,10 +331,93 @@ export type Procedures = {{
 
         Ok(())
     }
+
+    /// exports the types of the router as Rust structs and enums along with a typed client built on the `rspc-client` crate.
+    /// This allows another Rust program to call the router without depending on the crate it is defined in.
+    pub fn export_rust<TPath: AsRef<Path>>(&self, export_path: TPath) -> Result<(), ExportError> {
+        let export_path = PathBuf::from(export_path.as_ref());
+        if let Some(export_dir) = export_path.parent() {
+            fs::create_dir_all(export_dir)?;
+        }
+        let mut file = File::create(export_path)?;
+        writeln!(file, "// This file was generated by [rspc](https://github.com/oscartbeaumont/rspc). Do not edit this file manually.")?;
+
+        let queries_rs = generate_procedures_rust("Queries", "query", &self.queries.store)?;
+        let mutations_rs =
+            generate_procedures_rust("Mutations", "mutation", &self.mutations.store)?;
+        let subscriptions_rs =
+            generate_procedures_rust("Subscriptions", "subscription", &self.subscriptions.store)?;
+
+        writeln!(
+            file,
+            r#"
+pub struct Client<T: rspc_client::Transport>(pub rspc_client::Client<T>);
+
+impl<T: rspc_client::Transport> Client<T> {{
+    pub fn new(transport: T) -> Self {{
+        Self(rspc_client::Client::new(transport))
+    }}
+
+    pub fn queries(&self) -> Queries<'_, T> {{
+        Queries(&self.0)
+    }}
+
+    pub fn mutations(&self) -> Mutations<'_, T> {{
+        Mutations(&self.0)
+    }}
+
+    pub fn subscriptions(&self) -> Subscriptions<'_, T> {{
+        Subscriptions(&self.0)
+    }}
+}}
+{queries_rs}
+{mutations_rs}
+{subscriptions_rs}"#
+        )?;
+
+        // Types which are inlined into a procedure or another type aren't in the type store but Rust needs a definition for them.
+        let mut inline_defs = BTreeMap::new();
+        for procedure in self
+            .queries
+            .store
+            .values()
+            .chain(self.mutations.store.values())
+            .chain(self.subscriptions.store.values())
+        {
+            rust_inline_defs(&procedure.ty.arg_ty, &mut inline_defs);
+            rust_inline_defs(&procedure.ty.result_ty, &mut inline_defs);
+        }
+        for def in self.typ_store.values() {
+            rust_inline_defs(def, &mut inline_defs);
+        }
+
+        for def in self.typ_store.values().chain(
+            inline_defs
+                .iter()
+                .filter(|(name, _)| !self.typ_store.contains_key(name.as_str()))
+                .map(|(_, def)| def),
+        ) {
+            let export = to_rust_export(def).map_err(ExportError::RustTypeErr)?;
+            writeln!(file, "\n{}", export)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn serialize_input<TArg: Serialize + 'static>(
+    input: &(dyn Any + Send),
+) -> Result<Value, ExecError> {
+    match input.downcast_ref::<TArg>() {
+        Some(input) => serde_json::to_value(input).map_err(ExecError::SerializingArgErr),
+        None => Ok(Value::Null),
+    }
 }
 
 // TODO: Move this out into a Specta API
-fn generate_procedures_ts<Ctx>(procedures: &BTreeMap<String, Procedure<Ctx>>) -> String {
+fn generate_procedures_ts<Ctx, TMeta>(
+    procedures: &BTreeMap<String, Procedure<Ctx, TMeta>>,
+) -> String {
     match procedures.len() {
         0 => "never".to_string(),
         _ => procedures
@@ -174,14 +433,104 @@ fn generate_procedures_ts<Ctx>(procedures: &BTreeMap<String, Procedure<Ctx>>) ->
                     ty => to_ts(ty),
                 };
                 let result_ts = to_ts(&operation.ty.result_ty);
+                let error_ts = match &operation.ty.error_ty {
+                    Some(ty) => format!(", error: {}", to_ts(ty)),
+                    None => "".into(),
+                };
+
+                let docs = procedure_jsdoc(&operation.ty);
 
                 // TODO: Specta API
                 format!(
                     r#"
-        {{ key: "{key}", input: {input}, result: {result_ts} }}"#
+        {docs}{{ key: "{key}", input: {input}, result: {result_ts}{error_ts} }}"#
                 )
             })
             .collect::<Vec<_>>()
             .join(" | "),
     }
 }
+
+fn procedure_jsdoc(ty: &ProcedureDataType) -> String {
+    let mut tags = Vec::new();
+    if let Some(description) = &ty.description {
+        tags.extend(description.lines().map(|line| line.to_string()));
+    }
+    if let Some(deprecation) = &ty.deprecation {
+        let mut tag = format!("@deprecated {}", deprecation.message);
+        if let Some(key) = &deprecation.replaced_by {
+            tag.push_str(&format!(" Use `{key}` instead."));
+        }
+        if let Some(sunset) = &deprecation.sunset {
+            tag.push_str(&format!(" This procedure may be removed after {sunset}."));
+        }
+        tags.push(tag);
+    }
+    if let Some(since) = &ty.since {
+        tags.push(format!("@since {since}"));
+    }
+
+    match tags.len() {
+        0 => "".into(),
+        _ => format!(
+            "/**\n{}         */\n        ",
+            tags.iter()
+                .map(|tag| format!("         * {}\n", tag.replace("*/", "*\\/")))
+                .collect::<String>()
+        ),
+    }
+}
+
+// TODO: Move this out into a Specta API
+fn generate_procedures_rust<Ctx, TMeta>(
+    name: &str,
+    method: &str,
+    procedures: &BTreeMap<String, Procedure<Ctx, TMeta>>,
+) -> Result<String, ExportError> {
+    // Different keys such as `user.get` and `userGet` can map to the same method name so we must reject them.
+    let mut idents = BTreeMap::<String, &str>::new();
+    let methods = procedures
+        .iter()
+        .map(|(key, operation)| {
+            let ident = snake_case_ident(key);
+            if let Some(existing) = idents.insert(ident.clone(), key) {
+                return Err(ExportError::RustIdentCollision(
+                    existing.into(),
+                    key.clone(),
+                    ident,
+                ));
+            }
+            let (arg, input) = match &operation.ty.arg_ty {
+                DataType::Tuple(def)
+                    // This condition is met with an empty enum or `()`.
+                    if def.fields.is_empty() =>
+                {
+                    ("".to_string(), "()")
+                }
+                ty => (format!(", input: {}", to_rust(ty)), "input"),
+            };
+            let result = match method {
+                "subscription" => format!(
+                    "impl rspc_client::Stream<Item = Result<{}, rspc_client::Error>> + Send + 'static",
+                    to_rust(&operation.ty.result_ty)
+                ),
+                _ => to_rust(&operation.ty.result_ty),
+            };
+
+            Ok(format!(
+                r#"
+    pub async fn {ident}(&self{arg}) -> Result<{result}, rspc_client::Error> {{
+        self.0.{method}("{key}", {input}).await
+    }}
+"#
+            ))
+        })
+        .collect::<Result<String, _>>()?;
+
+    Ok(format!(
+        r#"
+pub struct {name}<'a, T: rspc_client::Transport>(&'a rspc_client::Client<T>);
+
+impl<T: rspc_client::Transport> {name}<'_, T> {{{methods}}}"#
+    ))
+}
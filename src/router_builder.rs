@@ -1,24 +1,33 @@
-use std::marker::PhantomData;
+use std::{
+    any::{Any, TypeId},
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+};
 
 use futures::Stream;
 use serde::{de::DeserializeOwned, Serialize};
-use specta::{Type, TypeDefs};
+use serde_json::Value;
+use specta::{to_ts, DefOpts, Type, TypeDefs};
 
 use crate::{
     internal::{
-        BaseMiddleware, BuiltProcedureBuilder, MiddlewareBuilderLike, MiddlewareLayerBuilder,
-        MiddlewareMerger, ProcedureStore, ResolverLayer, UnbuiltProcedureBuilder,
+        referenced_types, BaseMiddleware, BuiltProcedureBuilder, ErrorTypeFn, InputValidator,
+        LayerFuture, MiddlewareBuilderLike, MiddlewareLayerBuilder, MiddlewareMerger, Procedure,
+        ProcedureDataType, ProcedureStore, RequestContext, RequestFuture, ResolverLayer, TypedCall,
+        UnbuiltProcedureBuilder,
     },
     internal::{
-        DoubleArgStreamMarker, GlobalData, MiddlewareBuilder, MiddlewareLike, ProcedureKind,
-        RequestResolver, RequestResult, StreamResolver,
+        DoubleArgStreamMarker, DynamicStreamMarker, GlobalData, MiddlewareBuilder, MiddlewareLike,
+        ProcedureKind, RequestResolver, RequestResult, StreamResolver,
     },
-    Config, ExecError, Router,
+    Config, Error, ExecError, Router, RouterSchema,
 };
 
 pub(crate) fn is_valid_procedure_name(s: &str) -> bool {
     s.is_empty()
         || s == "ws"
+        || s == "_batch"
         || s.starts_with("rpc.")
         || s.starts_with("rspc.")
         || !s
@@ -26,21 +35,156 @@ pub(crate) fn is_valid_procedure_name(s: &str) -> bool {
             .all(|c| c.is_alphabetic() || c.is_numeric() || c == '.' || c == '_')
 }
 
+/// a query or mutation resolver which is built at runtime, eg. from a config file or a plugin. Its input and result are JSON so they are exported as `any`.
+pub type DynamicResolver<TLayerCtx> = Box<
+    dyn Fn(TLayerCtx, Value) -> Pin<Box<dyn Future<Output = Result<Value, Error>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// a subscription resolver which is built at runtime. Its input and events are JSON so they are exported as `any`, an `Err` event is sent to the client as an error.
+pub type DynamicStreamResolver<TLayerCtx> = Box<
+    dyn Fn(TLayerCtx, Value) -> Pin<Box<dyn Stream<Item = Result<Value, Error>> + Send + Sync>>
+        + Send
+        + Sync,
+>;
+
+/// the argument type of a procedure, used to validate its input and explain deserialization errors.
+struct ArgType {
+    typedef: ProcedureDataType,
+    defs: TypeDefs,
+    validator: Option<InputValidator>,
+}
+
+impl ArgType {
+    fn new(typedef: &ProcedureDataType, typ_store: &TypeDefs) -> Self {
+        let mut defs = TypeDefs::new();
+        referenced_types(&typedef.inline_arg_ty, typ_store, &mut defs);
+        Self {
+            validator: InputValidator::new(&typedef.inline_arg_ty, &defs),
+            typedef: typedef.clone(),
+            defs,
+        }
+    }
+
+    /// checks the input against the constraints declared on the argument type.
+    fn validate(&self, input: &Value) -> Result<(), ExecError> {
+        match &self.validator {
+            Some(validator) => validator.validate(input).map_err(ExecError::ValidationErr),
+            None => Ok(()),
+        }
+    }
+
+    /// checks the input of a typed in-process call against the constraints declared on the argument type.
+    /// The input is only serialized if the argument type declares constraints.
+    fn validate_typed(&self, input: &(dyn Any + Send), typed: &TypedCall) -> Result<(), ExecError> {
+        match &self.validator {
+            Some(validator) => validator
+                .validate(&(typed.serialize_input)(input)?)
+                .map_err(ExecError::ValidationErr),
+            None => Ok(()),
+        }
+    }
+
+    /// adds the Typescript type expected at the path of a deserialization error so the client can show it.
+    fn with_expected_ty(&self, err: ExecError) -> ExecError {
+        match err {
+            ExecError::DeserializingArgErr(err, None) => {
+                let expected = self
+                    .typedef
+                    .arg_ty_at(err.path(), &self.defs)
+                    .map(|ty| to_ts(&ty));
+                ExecError::DeserializingArgErr(err, expected)
+            }
+            err => err,
+        }
+    }
+
+    fn deserialize<T: DeserializeOwned>(&self, input: Value) -> Result<T, ExecError> {
+        self.validate(&input)?;
+        serde_path_to_error::deserialize(input)
+            .map_err(|err| self.with_expected_ty(ExecError::DeserializingArgErr(err, None)))
+    }
+}
+
+/// executes a query or mutation resolver. If the request is a typed in-process call the resolver is given the caller's value instead of deserializing it
+/// and its result is handed back to the caller without being serialized, so the middleware sees `null` in place of both.
+fn exec_resolver<TLayerCtx, TMeta, TResolver, TMarker, TResultMarker>(
+    resolver: &TResolver,
+    ctx: TLayerCtx,
+    input: Value,
+    req: &RequestContext<TMeta>,
+    arg_ty: &ArgType,
+) -> Result<LayerFuture, ExecError>
+where
+    TResolver: RequestResolver<TLayerCtx, TMarker, TResultMarker>,
+    TResolver::Arg: 'static,
+{
+    let typed = match &req.typed {
+        Some(typed) => typed.clone(),
+        None => {
+            return resolver
+                .exec(ctx, arg_ty.deserialize(input)?)
+                .map(|v| v.to_request_future().into())
+        }
+    };
+
+    // The input is moved into the resolver so if a middleware passes on an input of its own, or calls the resolver again, it's deserialized like any other request.
+    let typed_input = match input {
+        Value::Null => typed
+            .input
+            .lock()
+            .ok()
+            .and_then(|mut input| input.take())
+            .and_then(|input| input.downcast::<TResolver::Arg>().ok()),
+        _ => None,
+    };
+    let arg = match typed_input {
+        Some(arg) => {
+            arg_ty.validate_typed(&*arg, &typed)?;
+            *arg
+        }
+        None => arg_ty.deserialize(input)?,
+    };
+
+    let fut = resolver.exec(ctx, arg)?;
+    Ok(RequestFuture::Future(Box::pin(async move {
+        let result = fut.exec().await?;
+        if let Ok(mut v) = typed.result.lock() {
+            *v = Some(Box::new(result));
+        }
+        Ok(Value::Null)
+    }))
+    .into())
+}
+
+fn with_error_type(
+    mut typedef: ProcedureDataType,
+    error: Option<ErrorTypeFn>,
+    typ_store: &mut TypeDefs,
+) -> ProcedureDataType {
+    if let Some(error) = error {
+        typedef.error_ty = Some(error(typ_store));
+    }
+
+    typedef
+}
+
 pub struct RouterBuilder<
     TCtx = (), // The is the context the current router was initialised with
     TMeta = (),
     TMiddleware = BaseMiddleware<TCtx>,
 > where
     TCtx: Send + Sync + 'static,
-    TMeta: Send + 'static,
-    TMiddleware: MiddlewareBuilderLike<TCtx> + Send + 'static,
+    TMeta: Send + Sync + 'static,
+    TMiddleware: MiddlewareBuilderLike<TCtx, TMeta> + Send + 'static,
 {
     data: GlobalData,
     config: Config,
     middleware: TMiddleware,
-    queries: ProcedureStore<TCtx>,
-    mutations: ProcedureStore<TCtx>,
-    subscriptions: ProcedureStore<TCtx>,
+    queries: ProcedureStore<TCtx, TMeta>,
+    mutations: ProcedureStore<TCtx, TMeta>,
+    subscriptions: ProcedureStore<TCtx, TMeta>,
     typ_store: TypeDefs,
     phantom: PhantomData<TMeta>,
 }
@@ -49,7 +193,7 @@ pub struct RouterBuilder<
 impl<TCtx, TMeta> Router<TCtx, TMeta>
 where
     TCtx: Send + Sync + 'static,
-    TMeta: Send + 'static,
+    TMeta: Send + Sync + 'static,
 {
     pub fn new() -> RouterBuilder<TCtx, TMeta, BaseMiddleware<TCtx>> {
         RouterBuilder::new()
@@ -60,7 +204,7 @@ where
 impl<TCtx, TMeta> RouterBuilder<TCtx, TMeta, BaseMiddleware<TCtx>>
 where
     TCtx: Send + Sync + 'static,
-    TMeta: Send + 'static,
+    TMeta: Send + Sync + 'static,
 {
     pub fn new() -> Self {
         Self {
@@ -79,9 +223,9 @@ where
 impl<TCtx, TLayerCtx, TMeta, TMiddleware> RouterBuilder<TCtx, TMeta, TMiddleware>
 where
     TCtx: Send + Sync + 'static,
-    TMeta: Send + 'static,
+    TMeta: Send + Sync + 'static,
     TLayerCtx: Send + Sync + 'static,
-    TMiddleware: MiddlewareBuilderLike<TCtx, LayerContext = TLayerCtx> + Send + 'static,
+    TMiddleware: MiddlewareBuilderLike<TCtx, TMeta, LayerContext = TLayerCtx> + Send + 'static,
 {
     /// Attach a configuration to the router. Calling this multiple times will overwrite the previous config.
     pub fn config(mut self, config: Config) -> Self {
@@ -91,15 +235,16 @@ where
 
     pub fn middleware<TNewMiddleware, TNewLayerCtx>(
         self,
-        builder: impl Fn(MiddlewareBuilder<TLayerCtx>) -> TNewMiddleware,
+        builder: impl Fn(MiddlewareBuilder<TLayerCtx, TMeta>) -> TNewMiddleware,
     ) -> RouterBuilder<
         TCtx,
         TMeta,
-        MiddlewareLayerBuilder<TCtx, TLayerCtx, TNewLayerCtx, TMiddleware, TNewMiddleware>,
+        MiddlewareLayerBuilder<TCtx, TLayerCtx, TNewLayerCtx, TMiddleware, TNewMiddleware, TMeta>,
     >
     where
         TNewLayerCtx: Send + Sync + 'static,
-        TNewMiddleware: MiddlewareLike<TLayerCtx, NewCtx = TNewLayerCtx> + Send + Sync + 'static,
+        TNewMiddleware:
+            MiddlewareLike<TLayerCtx, TMeta, NewCtx = TNewLayerCtx> + Send + Sync + 'static,
     {
         let Self {
             data,
@@ -138,39 +283,45 @@ where
         TBuiltResultMarker,
     >(
         mut self,
-        key: &'static str,
+        key: impl Into<String>,
         builder: impl FnOnce(
-            UnbuiltProcedureBuilder<TLayerCtx, TUnbuiltResolver>,
-        ) -> BuiltProcedureBuilder<TBuiltResolver>,
+            UnbuiltProcedureBuilder<TLayerCtx, TUnbuiltResolver, TMeta>,
+        ) -> BuiltProcedureBuilder<TBuiltResolver, TMeta>,
     ) -> Self
     where
         TUnbuiltResolver: Fn(TLayerCtx, TBuiltResolver::Arg) -> TUnbuiltResult,
         TUnbuiltResult: RequestResult<TUnbuiltResultMarker>,
         TBuiltResolver: RequestResolver<TLayerCtx, TBuiltResultMarker, TBuiltResolverMarker>,
+        TBuiltResolver::Arg: 'static,
     {
+        let key = key.into();
         let built_procedure = builder(UnbuiltProcedureBuilder::new(
-            key,
+            key.clone(),
             ProcedureKind::Query,
             TBuiltResolver::typedef(&mut self.typ_store),
             self.data.clone(),
         ));
         let resolver = built_procedure.resolver;
+        let arg_ty = ArgType::new(&built_procedure.typedef, &self.typ_store);
 
         self.queries.append(
-            key.into(),
+            key,
             self.middleware.build(ResolverLayer {
-                func: move |ctx, input, _| {
-                    resolver
-                        .exec(
-                            ctx,
-                            serde_json::from_value(input)
-                                .map_err(ExecError::DeserializingArgErr)?,
-                        )
-                        .map(|v| v.to_request_future().into())
-                },
+                func: move |ctx, input, req| exec_resolver(&resolver, ctx, input, &req, &arg_ty),
                 phantom: PhantomData,
             }),
-            built_procedure.typedef,
+            with_error_type(
+                built_procedure.typedef,
+                built_procedure.error,
+                &mut self.typ_store,
+            ),
+            built_procedure.meta,
+            built_procedure.timeout,
+            Some((
+                TypeId::of::<TBuiltResolver::Arg>(),
+                TypeId::of::<<TBuiltResolver::Result as RequestResult<TBuiltResolverMarker>>::Data>(
+                ),
+            )),
         );
         self
     }
@@ -184,48 +335,53 @@ where
         TBuiltResultMarker,
     >(
         mut self,
-        key: &'static str,
+        key: impl Into<String>,
         builder: impl FnOnce(
-            UnbuiltProcedureBuilder<TLayerCtx, TUnbuiltResolver>,
-        ) -> BuiltProcedureBuilder<TBuiltResolver>,
+            UnbuiltProcedureBuilder<TLayerCtx, TUnbuiltResolver, TMeta>,
+        ) -> BuiltProcedureBuilder<TBuiltResolver, TMeta>,
     ) -> Self
     where
         TUnbuiltResolver: Fn(TLayerCtx, TBuiltResolver::Arg) -> TUnbuiltResult,
         TUnbuiltResult: RequestResult<TUnbuiltResultMarker>,
         TBuiltResolver: RequestResolver<TLayerCtx, TBuiltResolverMarker, TBuiltResultMarker>,
+        TBuiltResolver::Arg: 'static,
     {
+        let key = key.into();
         let built_procedure = builder(UnbuiltProcedureBuilder::new(
-            key,
+            key.clone(),
             ProcedureKind::Mutation,
             TBuiltResolver::typedef(&mut self.typ_store),
             self.data.clone(),
         ));
         let resolver = built_procedure.resolver;
+        let arg_ty = ArgType::new(&built_procedure.typedef, &self.typ_store);
         self.mutations.append(
-            key.into(),
+            key,
             self.middleware.build(ResolverLayer {
-                func: move |ctx, input, _| {
-                    resolver
-                        .exec(
-                            ctx,
-                            serde_json::from_value(input)
-                                .map_err(ExecError::DeserializingArgErr)?,
-                        )
-                        .map(|v| v.to_request_future().into())
-                },
+                func: move |ctx, input, req| exec_resolver(&resolver, ctx, input, &req, &arg_ty),
                 phantom: PhantomData,
             }),
-            built_procedure.typedef,
+            with_error_type(
+                built_procedure.typedef,
+                built_procedure.error,
+                &mut self.typ_store,
+            ),
+            built_procedure.meta,
+            built_procedure.timeout,
+            Some((
+                TypeId::of::<TBuiltResolver::Arg>(),
+                TypeId::of::<<TBuiltResolver::Result as RequestResult<TBuiltResultMarker>>::Data>(),
+            )),
         );
         self
     }
 
     pub fn subscription<TResolver, TArg, TStream, TResult, TResultMarker>(
-        mut self,
-        key: &'static str,
+        self,
+        key: impl Into<String>,
         builder: impl FnOnce(
-            UnbuiltProcedureBuilder<TLayerCtx, TResolver>,
-        ) -> BuiltProcedureBuilder<TResolver>,
+            UnbuiltProcedureBuilder<TLayerCtx, TResolver, TMeta>,
+        ) -> BuiltProcedureBuilder<TResolver, TMeta>,
     ) -> Self
     where
         TArg: DeserializeOwned + Type,
@@ -237,48 +393,96 @@ where
             + Sync
             + 'static,
     {
+        self.append_subscription(key, builder)
+    }
+
+    fn append_subscription<TResolver, TMarker>(
+        mut self,
+        key: impl Into<String>,
+        builder: impl FnOnce(
+            UnbuiltProcedureBuilder<TLayerCtx, TResolver, TMeta>,
+        ) -> BuiltProcedureBuilder<TResolver, TMeta>,
+    ) -> Self
+    where
+        TResolver: StreamResolver<TLayerCtx, TMarker> + Send + Sync + 'static,
+    {
+        let key = key.into();
         let built_procedure = builder(UnbuiltProcedureBuilder::new(
-            key,
+            key.clone(),
             ProcedureKind::Subscription,
             TResolver::typedef(&mut self.typ_store),
             self.data.clone(),
         ));
         let resolver = built_procedure.resolver;
+        let arg_ty = ArgType::new(&built_procedure.typedef, &self.typ_store);
         self.subscriptions.append(
-            key.into(),
+            key.clone(),
             self.middleware.build(ResolverLayer {
                 func: move |ctx, input, _| {
+                    arg_ty.validate(&input)?;
                     resolver
-                        .exec(
-                            ctx,
-                            serde_json::from_value(input)
-                                .map_err(ExecError::DeserializingArgErr)?,
-                        )
+                        .exec(ctx, input)
                         .map(Into::into)
+                        .map_err(|err| arg_ty.with_expected_ty(err))
                 },
                 phantom: PhantomData,
             }),
-            built_procedure.typedef,
+            with_error_type(
+                built_procedure.typedef,
+                built_procedure.error,
+                &mut self.typ_store,
+            ),
+            built_procedure.meta,
+            built_procedure.timeout,
+            None,
         );
         self
     }
 
+    /// registers a query with a resolver which is built at runtime.
+    pub fn dynamic_query(
+        self,
+        key: impl Into<String>,
+        resolver: DynamicResolver<TLayerCtx>,
+    ) -> Self {
+        self.query(key, |t| t(resolver))
+    }
+
+    /// registers a mutation with a resolver which is built at runtime.
+    pub fn dynamic_mutation(
+        self,
+        key: impl Into<String>,
+        resolver: DynamicResolver<TLayerCtx>,
+    ) -> Self {
+        self.mutation(key, |t| t(resolver))
+    }
+
+    /// registers a subscription with a resolver which is built at runtime.
+    pub fn dynamic_subscription(
+        self,
+        key: impl Into<String>,
+        resolver: DynamicStreamResolver<TLayerCtx>,
+    ) -> Self {
+        self.append_subscription::<_, DynamicStreamMarker>(key, |t| t(resolver))
+    }
+
     pub fn merge<TNewLayerCtx, TIncomingMiddleware>(
         self,
-        prefix: &'static str,
+        prefix: impl Into<String>,
         router: RouterBuilder<TLayerCtx, TMeta, TIncomingMiddleware>,
     ) -> RouterBuilder<
         TCtx,
         TMeta,
-        MiddlewareMerger<TCtx, TLayerCtx, TNewLayerCtx, TMiddleware, TIncomingMiddleware>,
+        MiddlewareMerger<TCtx, TLayerCtx, TNewLayerCtx, TMiddleware, TIncomingMiddleware, TMeta>,
     >
     where
         TNewLayerCtx: 'static,
         TIncomingMiddleware:
-            MiddlewareBuilderLike<TLayerCtx, LayerContext = TNewLayerCtx> + Send + 'static,
+            MiddlewareBuilderLike<TLayerCtx, TMeta, LayerContext = TNewLayerCtx> + Send + 'static,
     {
+        let prefix = prefix.into();
         #[allow(clippy::panic)]
-        if is_valid_procedure_name(prefix) {
+        if is_valid_procedure_name(&prefix) {
             panic!(
                 "rspc error: attempted to merge a router with the prefix '{}', however this name is not allowed.",
                 prefix
@@ -303,6 +507,9 @@ where
                 format!("{}{}", prefix, key),
                 middleware.build(query.exec),
                 query.ty,
+                query.meta,
+                query.timeout,
+                query.type_ids,
             );
         }
 
@@ -311,6 +518,9 @@ where
                 format!("{}{}", prefix, key),
                 middleware.build(mutation.exec),
                 mutation.ty,
+                mutation.meta,
+                mutation.timeout,
+                mutation.type_ids,
             );
         }
 
@@ -319,6 +529,9 @@ where
                 format!("{}{}", prefix, key),
                 middleware.build(subscription.exec),
                 subscription.ty,
+                subscription.meta,
+                subscription.timeout,
+                subscription.type_ids,
             );
         }
 
@@ -346,14 +559,87 @@ where
         let Self {
             data,
             config,
-            queries,
-            mutations,
-            subscriptions,
-            typ_store,
+            middleware,
+            mut queries,
+            mut mutations,
+            mut subscriptions,
+            mut typ_store,
             ..
         } = self;
 
+        if config.introspection {
+            #[allow(clippy::unwrap_used)] // The schema only has string keys so this can't fail.
+            let schema = serde_json::to_value(RouterSchema::new(
+                &queries.store,
+                &mutations.store,
+                &subscriptions.store,
+                &typ_store,
+            ))
+            .unwrap();
+
+            // This is inserted directly into the store because `rspc.` is a reserved prefix for user defined procedures.
+            queries.store.insert(
+                "rspc.introspect".into(),
+                Procedure {
+                    exec: middleware.build(ResolverLayer {
+                        func: move |_, _, _| Ok(RequestFuture::Ready(Ok(schema.clone())).into()),
+                        phantom: PhantomData,
+                    }),
+                    ty: ProcedureDataType {
+                        arg_ty: <() as Type>::reference(
+                            DefOpts {
+                                parent_inline: false,
+                                type_map: &mut typ_store,
+                            },
+                            &[],
+                        ),
+                        result_ty: <Value as Type>::reference(
+                            DefOpts {
+                                parent_inline: false,
+                                type_map: &mut typ_store,
+                            },
+                            &[],
+                        ),
+                        error_ty: None,
+                        description: None,
+                        deprecation: None,
+                        since: None,
+                        inline_arg_ty: <() as Type>::inline(
+                            DefOpts {
+                                parent_inline: true,
+                                type_map: &mut typ_store,
+                            },
+                            &[],
+                        ),
+                        inline_result_ty: <Value as Type>::inline(
+                            DefOpts {
+                                parent_inline: true,
+                                type_map: &mut typ_store,
+                            },
+                            &[],
+                        ),
+                    },
+                    meta: None,
+                    timeout: None,
+                    type_ids: None,
+                },
+            );
+        }
+
+        // Procedures without their own timeout fallback to the router's defaults.
+        for procedure in queries
+            .store
+            .values_mut()
+            .chain(mutations.store.values_mut())
+        {
+            procedure.timeout = procedure.timeout.or(config.default_timeout);
+        }
+        for procedure in subscriptions.store.values_mut() {
+            procedure.timeout = procedure.timeout.or(config.subscription_idle_timeout);
+        }
+
         let export_path = config.export_bindings_on_build.clone();
+        let export_rust_path = config.export_rust_bindings_on_build.clone();
         let router = Router {
             data,
             config,
@@ -370,6 +656,12 @@ where
             router.export_ts(export_path).unwrap();
         }
 
+        #[cfg(debug_assertions)]
+        #[allow(clippy::unwrap_used)]
+        if let Some(export_path) = export_rust_path {
+            router.export_rust(export_path).unwrap();
+        }
+
         router
     }
 }
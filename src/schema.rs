@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use specta::{DataType, TypeDefs};
+
+use crate::internal::{Deprecation, Procedure, ProcedureKind};
+
+/// a serializable description of a procedure on the router.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcedureSchema {
+    pub kind: ProcedureKind,
+    pub key: String,
+    pub input: DataType,
+    pub result: DataType,
+    pub error: Option<DataType>,
+    pub description: Option<String>,
+    pub deprecation: Option<Deprecation>,
+    pub since: Option<String>,
+}
+
+/// a serializable description of the router's API. This is returned by [`Router::schema`](crate::Router::schema) and the `rspc.introspect` query.
+#[derive(Debug, Clone, Serialize)]
+pub struct RouterSchema {
+    pub procedures: Vec<ProcedureSchema>,
+    /// the definitions of all the types referenced by the procedures.
+    pub types: TypeDefs,
+}
+
+impl RouterSchema {
+    pub(crate) fn new<TCtx, TMeta>(
+        queries: &BTreeMap<String, Procedure<TCtx, TMeta>>,
+        mutations: &BTreeMap<String, Procedure<TCtx, TMeta>>,
+        subscriptions: &BTreeMap<String, Procedure<TCtx, TMeta>>,
+        typ_store: &TypeDefs,
+    ) -> Self {
+        let procedures = [
+            (ProcedureKind::Query, queries),
+            (ProcedureKind::Mutation, mutations),
+            (ProcedureKind::Subscription, subscriptions),
+        ]
+        .into_iter()
+        .flat_map(|(kind, procedures)| {
+            procedures
+                .iter()
+                .map(move |(key, procedure)| ProcedureSchema {
+                    kind: kind.clone(),
+                    key: key.clone(),
+                    input: procedure.ty.arg_ty.clone(),
+                    result: procedure.ty.result_ty.clone(),
+                    error: procedure.ty.error_ty.clone(),
+                    description: procedure.ty.description.clone(),
+                    deprecation: procedure.ty.deprecation.clone(),
+                    since: procedure.ty.since.clone(),
+                })
+        })
+        .collect();
+
+        Self {
+            procedures,
+            types: typ_store.clone(),
+        }
+    }
+}
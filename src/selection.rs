@@ -27,8 +27,11 @@ macro_rules! selection {
                                     &[]
                                 ),
                                 optional: false,
+                                description: None,
+                                constraints: Default::default(),
                             }
                         ),*],
+                        description: None,
                         type_id: Some(std::any::TypeId::of::<Self>()),
                     })
                 }
@@ -73,8 +76,11 @@ macro_rules! selection {
                                     &[]
                                 ),
                                 optional: false,
+                                description: None,
+                                constraints: Default::default(),
                             }
                         ),*],
+                        description: None,
                         type_id: Some(std::any::TypeId::of::<Self>())
                     })
                 }
@@ -0,0 +1,253 @@
+//! helpers for testing a router without starting a webserver. This module requires the `testing` feature.
+
+use std::{collections::VecDeque, sync::Arc, time::Duration};
+
+use futures::future::join_all;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::{sync::mpsc, time::Instant};
+
+use crate::{
+    internal::{
+        jsonrpc::{
+            self, handle_batched_json_rpc, JsonRPCError, Request, RequestId, RequestInner,
+            ResponseInner, Sender2,
+        },
+        Connection,
+    },
+    ErrorCode, Router,
+};
+
+/// an in-memory connection to a router which behaves the same as a websocket connection.
+/// Requests are spawned so they run concurrently, within the router's `concurrency_limit`, and can be cancelled or stopped while they are running.
+/// Their responses and subscription events are received in the order they are sent.
+pub struct TestConnection<TCtx, TMeta = ()>
+where
+    TCtx: 'static,
+{
+    router: Arc<Router<TCtx, TMeta>>,
+    ctx_fn: Box<dyn Fn() -> TCtx + Send + Sync>,
+    conn: Connection,
+    tx: mpsc::UnboundedSender<jsonrpc::Response>,
+    rx: mpsc::UnboundedReceiver<jsonrpc::Response>,
+    buffered: VecDeque<jsonrpc::Response>,
+    next_id: u32,
+    timeout: Duration,
+}
+
+impl<TCtx, TMeta> TestConnection<TCtx, TMeta>
+where
+    TCtx: Send + 'static,
+    TMeta: Send + Sync + 'static,
+{
+    pub fn new(
+        router: Arc<Router<TCtx, TMeta>>,
+        ctx_fn: impl Fn() -> TCtx + Send + Sync + 'static,
+    ) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            conn: Connection::new(router.config.concurrency_limit),
+            router,
+            ctx_fn: Box::new(ctx_fn),
+            tx,
+            rx,
+            buffered: VecDeque::new(),
+            next_id: 0,
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// sets how long to wait for a message before failing the test. Defaults to 5 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// sends a request with a new id and returns the id without waiting for the response.
+    pub async fn send(&mut self, inner: RequestInner) -> RequestId {
+        let id = RequestId::Number(self.next_id);
+        self.next_id += 1;
+        self.send_with_id(id.clone(), inner).await;
+        id
+    }
+
+    /// sends a request with a specific id. This is useful for testing how null or duplicate ids are handled.
+    pub async fn send_with_id(&mut self, id: RequestId, inner: RequestInner) {
+        self.conn
+            .handle(
+                (self.ctx_fn)(),
+                Request {
+                    jsonrpc: None,
+                    id,
+                    inner,
+                },
+                &self.router,
+                Sender2::ResponseChannel(self.tx.clone()),
+            )
+            .await;
+    }
+
+    pub async fn query(&mut self, key: &str, input: impl Serialize) -> TestResponse {
+        let id = self
+            .send(RequestInner::Query {
+                path: key.into(),
+                input: Some(to_value(input)),
+            })
+            .await;
+        self.next(&id).await
+    }
+
+    pub async fn mutation(&mut self, key: &str, input: impl Serialize) -> TestResponse {
+        let id = self
+            .send(RequestInner::Mutation {
+                path: key.into(),
+                input: Some(to_value(input)),
+            })
+            .await;
+        self.next(&id).await
+    }
+
+    /// starts a subscription and returns its id. Use [`TestConnection::next`] to receive its events.
+    pub async fn subscribe(&mut self, key: &str, input: impl Serialize) -> RequestId {
+        self.send(RequestInner::Subscription {
+            path: key.into(),
+            input: Some(to_value(input)),
+        })
+        .await
+    }
+
+    pub async fn unsubscribe(&mut self, id: &RequestId) {
+        self.send_with_id(id.clone(), RequestInner::SubscriptionStop)
+            .await;
+    }
+
+    /// executes the requests the same as the HTTP batch endpoint. The requests are run concurrently and the responses are returned in order.
+    pub async fn batch(
+        &mut self,
+        requests: impl IntoIterator<Item = RequestInner>,
+    ) -> Vec<TestResponse> {
+        let requests = requests
+            .into_iter()
+            .map(|inner| {
+                let id = RequestId::Number(self.next_id);
+                self.next_id += 1;
+                Request {
+                    jsonrpc: None,
+                    id,
+                    inner,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let this = &*self;
+        join_all(requests.into_iter().map(|request| async move {
+            TestResponse(handle_batched_json_rpc((this.ctx_fn)(), request, &this.router).await)
+        }))
+        .await
+    }
+
+    /// waits for the next message for the request with the given id. Messages for other requests are kept until they are asked for.
+    #[allow(clippy::panic)] // The assertions are meant to panic and fail the test
+    pub async fn next(&mut self, id: &RequestId) -> TestResponse {
+        if let Some(resp) = self
+            .buffered
+            .iter()
+            .position(|resp| &resp.id == id)
+            .and_then(|i| self.buffered.remove(i))
+        {
+            return TestResponse(resp);
+        }
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            match tokio::time::timeout_at(deadline, self.rx.recv()).await {
+                Ok(Some(resp)) if &resp.id == id => return TestResponse(resp),
+                Ok(Some(resp)) => self.buffered.push_back(resp),
+                Ok(None) => panic!("the connection was closed"),
+                Err(_) => panic!(
+                    "timed out after {:?} waiting for a message for request {:?}",
+                    self.timeout, id
+                ),
+            }
+        }
+    }
+
+    /// asserts there are no messages waiting to be received for the request with the given id.
+    #[track_caller]
+    #[allow(clippy::panic)]
+    pub fn assert_no_messages(&mut self, id: &RequestId) {
+        while let Ok(resp) = self.rx.try_recv() {
+            self.buffered.push_back(resp);
+        }
+
+        if let Some(resp) = self.buffered.iter().find(|resp| &resp.id == id) {
+            panic!("expected no messages for request {:?}, got {:?}", id, resp);
+        }
+    }
+}
+
+/// a response or subscription event received from a [`TestConnection`].
+#[derive(Debug, Clone)]
+pub struct TestResponse(pub jsonrpc::Response);
+
+#[allow(clippy::panic)]
+impl TestResponse {
+    pub fn id(&self) -> &RequestId {
+        &self.0.id
+    }
+
+    pub fn result(&self) -> &ResponseInner {
+        &self.0.result
+    }
+
+    /// asserts the request succeeded and returns its result.
+    #[track_caller]
+    pub fn assert_ok<T: DeserializeOwned>(self) -> T {
+        match self.0.result {
+            ResponseInner::Response(v) => from_value(v),
+            result => panic!("expected a response, got {:?}", result),
+        }
+    }
+
+    /// asserts this is a subscription event and returns its data.
+    #[track_caller]
+    pub fn assert_event<T: DeserializeOwned>(self) -> T {
+        match self.0.result {
+            ResponseInner::Event(v) => from_value(v),
+            result => panic!("expected an event, got {:?}", result),
+        }
+    }
+
+    /// asserts the request failed with the given error code and returns the error.
+    #[track_caller]
+    pub fn assert_error(self, code: ErrorCode) -> JsonRPCError {
+        match self.0.result {
+            ResponseInner::Error(err) if err.code == code.to_status_code() as i32 => err,
+            result => panic!("expected an error with code {:?}, got {:?}", code, result),
+        }
+    }
+
+    /// asserts the subscription has completed.
+    #[track_caller]
+    pub fn assert_complete(self) {
+        if !matches!(self.0.result, ResponseInner::Complete) {
+            panic!(
+                "expected the subscription to complete, got {:?}",
+                self.0.result
+            );
+        }
+    }
+}
+
+#[track_caller]
+#[allow(clippy::panic)]
+fn to_value(input: impl Serialize) -> serde_json::Value {
+    serde_json::to_value(input)
+        .unwrap_or_else(|err| panic!("error serializing procedure input: {}", err))
+}
+
+#[track_caller]
+#[allow(clippy::panic)]
+fn from_value<T: DeserializeOwned>(value: serde_json::Value) -> T {
+    serde_json::from_value(value.clone())
+        .unwrap_or_else(|err| panic!("error deserializing {}: {}", value, err))
+}
@@ -0,0 +1,36 @@
+use rspc::{testing::TestConnection, ErrorCode, Router, Type};
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Deserialize, Type)]
+#[allow(dead_code)]
+struct User {
+    name: String,
+    tags: Vec<Tag>,
+}
+
+#[derive(Deserialize, Type)]
+#[allow(dead_code)]
+struct Tag {
+    id: i32,
+}
+
+#[tokio::test]
+async fn deserialization_error_path() {
+    let router = <Router>::new()
+        .mutation("createUser", |t| t(|_, user: User| user.name))
+        .build()
+        .arced();
+    let mut conn = TestConnection::new(router, || ());
+
+    let err = conn
+        .mutation(
+            "createUser",
+            json!({ "name": "Monty", "tags": [{ "id": "one" }] }),
+        )
+        .await
+        .assert_error(ErrorCode::BadRequest);
+    let data = err.data.unwrap();
+    assert_eq!(data["path"], "tags[0].id");
+    assert_eq!(data["expected"], "number");
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use rspc::{Error, ErrorCode, Router};
+use serde_json::{json, Value};
+
+fn router() -> Router {
+    <Router>::new()
+        // Sleeps for `ms` so a request sent first can finish last
+        .query("sleep", |t| {
+            t(|_, ms: u64| async move {
+                tokio::time::sleep(Duration::from_millis(ms)).await;
+                ms
+            })
+        })
+        .mutation("fail", |t| {
+            t(|_, _: ()| Err(Error::new(ErrorCode::Conflict, "nope".into())) as Result<(), _>)
+        })
+        .subscription("count", |t| t(|_, to: i32| futures::stream::iter(0..to)))
+        .build()
+}
+
+/// posts `requests` to the batch endpoint and returns the array of responses.
+async fn batch(requests: Value) -> Vec<Value> {
+    let addr = common::serve(router().arced());
+    let resp = reqwest::Client::new()
+        .post(format!("http://{}/rspc/_batch", addr))
+        .body(requests.to_string())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    serde_json::from_slice(&resp.bytes().await.unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn responses_are_in_the_order_of_the_requests() {
+    let resps = batch(json!([
+        { "id": 1, "method": "query", "params": { "path": "sleep", "input": 100 } },
+        { "id": 2, "method": "query", "params": { "path": "sleep", "input": 50 } },
+        { "id": 3, "method": "query", "params": { "path": "sleep", "input": 0 } },
+    ]))
+    .await;
+
+    assert_eq!(
+        resps
+            .iter()
+            .map(|resp| (resp["id"].clone(), resp["result"]["data"].clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (json!(1), json!(100)),
+            (json!(2), json!(50)),
+            (json!(3), json!(0))
+        ]
+    );
+}
+
+#[tokio::test]
+async fn successes_and_errors_can_be_mixed() {
+    let resps = batch(json!([
+        { "id": 1, "method": "query", "params": { "path": "sleep", "input": 0 } },
+        { "id": 2, "method": "mutation", "params": { "path": "fail", "input": null } },
+        { "id": 3, "method": "query", "params": { "path": "unknown", "input": null } },
+        { "id": 4, "method": "query", "params": { "path": "sleep", "input": 1 } },
+    ]))
+    .await;
+
+    assert_eq!(resps.len(), 4);
+    assert_eq!(resps[0]["result"], json!({ "type": "response", "data": 0 }));
+    assert_eq!(resps[1]["result"]["type"], json!("error"));
+    assert_eq!(resps[1]["result"]["data"]["code"], json!(409));
+    assert_eq!(resps[1]["result"]["data"]["message"], json!("nope"));
+    assert_eq!(resps[2]["result"]["type"], json!("error"));
+    assert_eq!(resps[2]["result"]["data"]["code"], json!(404));
+    assert_eq!(resps[3]["result"], json!({ "type": "response", "data": 1 }));
+}
+
+#[tokio::test]
+async fn subscriptions_are_rejected() {
+    let resps = batch(json!([
+        { "id": 1, "method": "subscription", "params": { "path": "count", "input": 3 } },
+        { "id": 2, "method": "query", "params": { "path": "sleep", "input": 0 } },
+    ]))
+    .await;
+
+    assert_eq!(resps[0]["id"], json!(1));
+    assert_eq!(resps[0]["result"]["type"], json!("error"));
+    assert_eq!(resps[0]["result"]["data"]["code"], json!(400));
+    // The rest of the batch is still executed
+    assert_eq!(resps[1]["result"], json!({ "type": "response", "data": 0 }));
+}
@@ -0,0 +1,98 @@
+use std::{
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use rspc::{
+    plugins::cache::{CachePolicy, QueryCache},
+    testing::TestConnection,
+    Router,
+};
+use serde_json::{json, Value};
+
+fn router(cache: QueryCache<i32>) -> Arc<Router<i32>> {
+    let count = Arc::new(AtomicI32::new(0));
+    let count2 = count.clone();
+    Router::<i32>::new()
+        .middleware(move |_| cache.clone())
+        .query("count", move |t| {
+            t(move |_, _: Value| count.fetch_add(1, Ordering::SeqCst))
+        })
+        .mutation("bump", move |t| {
+            t(move |_, _: ()| count2.fetch_add(1, Ordering::SeqCst))
+        })
+        .build()
+        .arced()
+}
+
+#[tokio::test]
+async fn query_cache() {
+    let cache = QueryCache::new()
+        .procedure("count", CachePolicy::new(Duration::from_secs(60)))
+        .invalidate_on("bump", "count");
+    let mut conn = TestConnection::new(router(cache.clone()), || 0);
+
+    // Inputs with the same fields in a different order share a result
+    let a = conn
+        .query("count", json!({ "a": 1, "b": 2 }))
+        .await
+        .assert_ok::<i32>();
+    let b = conn
+        .query("count", json!({ "b": 2, "a": 1 }))
+        .await
+        .assert_ok::<i32>();
+    assert_eq!(a, b);
+    assert_ne!(conn.query("count", 1).await.assert_ok::<i32>(), a);
+
+    // Mutations are never cached and invalidate the query
+    let m1 = conn.mutation("bump", ()).await.assert_ok::<i32>();
+    let m2 = conn.mutation("bump", ()).await.assert_ok::<i32>();
+    assert_ne!(m1, m2);
+    let c = conn
+        .query("count", json!({ "a": 1, "b": 2 }))
+        .await
+        .assert_ok::<i32>();
+    assert_ne!(a, c);
+
+    cache.invalidate(&QueryCache::key("count", &json!({ "b": 2, "a": 1 })));
+    assert_ne!(
+        conn.query("count", json!({ "a": 1, "b": 2 }))
+            .await
+            .assert_ok::<i32>(),
+        c
+    );
+}
+
+#[tokio::test]
+async fn entries_expire() {
+    let cache = QueryCache::new().procedure("count", CachePolicy::new(Duration::from_millis(50)));
+    let mut conn = TestConnection::new(router(cache), || 0);
+
+    let a = conn.query("count", ()).await.assert_ok::<i32>();
+    assert_eq!(conn.query("count", ()).await.assert_ok::<i32>(), a);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_ne!(conn.query("count", ()).await.assert_ok::<i32>(), a);
+}
+
+#[tokio::test]
+async fn context_key() {
+    let cache = QueryCache::new()
+        .procedure("count", CachePolicy::new(Duration::from_secs(60)))
+        .context_key(|user: &i32| user.to_string());
+    let router = router(cache.clone());
+    let mut user1 = TestConnection::new(router.clone(), || 1);
+    let mut user2 = TestConnection::new(router, || 2);
+
+    let a = user1.query("count", ()).await.assert_ok::<i32>();
+    let b = user2.query("count", ()).await.assert_ok::<i32>();
+    assert_ne!(a, b);
+    assert_eq!(user1.query("count", ()).await.assert_ok::<i32>(), a);
+
+    // Invalidating a key removes the entries of every user
+    cache.invalidate(&QueryCache::key("count", &Value::Null));
+    assert_ne!(user1.query("count", ()).await.assert_ok::<i32>(), a);
+    assert_ne!(user2.query("count", ()).await.assert_ok::<i32>(), b);
+}
@@ -0,0 +1,243 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use rspc::{
+    internal::{Layer, LayerFuture, LayerReturn, MiddlewareLike, RequestContext, RequestFuture},
+    ExecError, Router, Type,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+// Isn't `Clone` so the resolver's result can only be returned to the caller by moving it
+#[derive(Serialize, Deserialize, Type, PartialEq, Debug)]
+struct Sum {
+    value: i32,
+}
+
+/// records the input and result of every request and calls the rest of the middleware stack `calls` times.
+#[derive(Clone)]
+struct Recorder {
+    calls: usize,
+    rewrite: Option<Value>,
+    seen: Arc<Mutex<Vec<(Value, Value)>>>,
+}
+
+impl Recorder {
+    fn new(calls: usize, rewrite: Option<Value>) -> Self {
+        Self {
+            calls,
+            rewrite,
+            seen: Default::default(),
+        }
+    }
+}
+
+impl MiddlewareLike<()> for Recorder {
+    type State = ();
+    type NewCtx = ();
+
+    fn handle<TMiddleware: Layer<Self::NewCtx> + 'static>(
+        &self,
+        ctx: (),
+        input: Value,
+        req: RequestContext,
+        next: Arc<TMiddleware>,
+    ) -> LayerFuture {
+        let this = self.clone();
+        RequestFuture::Future(Box::pin(async move {
+            let next_input = this.rewrite.clone().unwrap_or_else(|| input.clone());
+            let mut result = Value::Null;
+            for _ in 0..this.calls {
+                result = match next
+                    .call(ctx, next_input.clone(), req.clone())?
+                    .into_layer_return()
+                    .await?
+                {
+                    LayerReturn::Request(v) => v,
+                    LayerReturn::Stream(_) => unreachable!(),
+                };
+            }
+            this.seen.lock().unwrap().push((input, result.clone()));
+            Ok(result)
+        }))
+        .into()
+    }
+}
+
+#[tokio::test]
+async fn middleware_sees_null_in_place_of_the_input_and_result() {
+    let recorder = Recorder::new(1, None);
+    let router = <Router>::new()
+        .middleware({
+            let recorder = recorder.clone();
+            move |_| recorder.clone()
+        })
+        .query("add", |t| t(|_, (a, b): (i32, i32)| Sum { value: a + b }))
+        .build();
+    let add = router.query_handle::<(i32, i32), Sum>("add").unwrap();
+
+    assert_eq!(add.key(), "add");
+    assert_eq!(
+        router.call((), &add, (1, 2)).await.unwrap(),
+        Sum { value: 3 }
+    );
+    assert_eq!(
+        *recorder.seen.lock().unwrap(),
+        vec![(Value::Null, Value::Null)]
+    );
+}
+
+/// fails to serialize so the test fails if the value is serialized.
+#[derive(Deserialize, Type, PartialEq, Debug)]
+struct Unserializable(i32);
+
+impl Serialize for Unserializable {
+    fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("serialized"))
+    }
+}
+
+#[tokio::test]
+async fn input_and_result_are_not_serialized() {
+    let router = <Router>::new()
+        .query("double", |t| {
+            t(|_, Unserializable(v): Unserializable| Unserializable(v * 2))
+        })
+        .build();
+    let double = router
+        .query_handle::<Unserializable, Unserializable>("double")
+        .unwrap();
+
+    assert_eq!(
+        router.call((), &double, Unserializable(21)).await.unwrap(),
+        Unserializable(42)
+    );
+}
+
+#[tokio::test]
+async fn middleware_can_call_the_resolver_more_than_once() {
+    // The input is moved into the resolver so the later calls must be given an input
+    let recorder = Recorder::new(2, Some(json!([1, 2])));
+    let count = Arc::new(AtomicUsize::new(0));
+    let router = <Router>::new()
+        .middleware({
+            let recorder = recorder.clone();
+            move |_| recorder.clone()
+        })
+        .mutation("add", {
+            let count = count.clone();
+            move |t| {
+                t(move |_, (a, b): (i32, i32)| {
+                    count.fetch_add(1, Ordering::SeqCst);
+                    Sum { value: a + b }
+                })
+            }
+        })
+        .build();
+    let add = router.mutation_handle::<(i32, i32), Sum>("add").unwrap();
+
+    assert_eq!(
+        router.call((), &add, (1, 2)).await.unwrap(),
+        Sum { value: 3 }
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn middleware_can_change_the_input() {
+    let recorder = Recorder::new(1, Some(json!([10, 20])));
+    let router = <Router>::new()
+        .middleware({
+            let recorder = recorder.clone();
+            move |_| recorder.clone()
+        })
+        .query("add", |t| t(|_, (a, b): (i32, i32)| Sum { value: a + b }))
+        .build();
+    let add = router.query_handle::<(i32, i32), Sum>("add").unwrap();
+
+    assert_eq!(
+        router.call((), &add, (1, 2)).await.unwrap(),
+        Sum { value: 30 }
+    );
+}
+
+#[tokio::test]
+async fn handles_use_the_key_of_a_merged_procedure() {
+    let router = <Router>::new()
+        .query("add", |t| t(|_, (a, b): (i32, i32)| a - b))
+        .merge(
+            "math.",
+            <Router>::new().query("add", |t| t(|_, (a, b): (i32, i32)| Sum { value: a + b })),
+        )
+        .build();
+    let add = router.query_handle::<(i32, i32), Sum>("math.add").unwrap();
+
+    assert_eq!(add.key(), "math.add");
+    assert_eq!(
+        router.call((), &add, (1, 2)).await.unwrap(),
+        Sum { value: 3 }
+    );
+    // The un-prefixed key is a different procedure with a different result type
+    assert!(matches!(
+        router.query_handle::<(i32, i32), Sum>("add"),
+        Err(ExecError::ProcedureTypeMismatch(_))
+    ));
+    assert!(matches!(
+        router.query_handle::<(i32, i32), Sum>("sub"),
+        Err(ExecError::OperationNotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn handles_are_checked_against_the_router_they_are_called_on() {
+    let router = <Router>::new()
+        .query("add", |t| t(|_, (a, b): (i32, i32)| Sum { value: a + b }))
+        .build();
+    let add = router.query_handle::<(i32, i32), Sum>("add").unwrap();
+
+    let other = <Router>::new()
+        .query("add", |t| t(|_, (a, b): (i32, i32)| a + b))
+        .build();
+    assert!(matches!(
+        other.call((), &add, (1, 2)).await,
+        Err(ExecError::ProcedureTypeMismatch(_))
+    ));
+}
+
+#[derive(Serialize, Deserialize, Type)]
+struct Username {
+    #[specta(min_length = 3)]
+    name: String,
+}
+
+#[tokio::test]
+async fn typed_input_is_validated() {
+    let router = <Router>::new()
+        .query("greet", |t| {
+            t(|_, input: Username| format!("Hello {}", input.name))
+        })
+        .build();
+    let greet = router.query_handle::<Username, String>("greet").unwrap();
+
+    assert_eq!(
+        router
+            .call(
+                (),
+                &greet,
+                Username {
+                    name: "Oscar".into()
+                }
+            )
+            .await
+            .unwrap(),
+        "Hello Oscar"
+    );
+    assert!(matches!(
+        router
+            .call((), &greet, Username { name: "Al".into() })
+            .await,
+        Err(ExecError::ValidationErr(_))
+    ));
+}
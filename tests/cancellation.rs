@@ -0,0 +1,167 @@
+use std::{sync::Arc, time::Duration};
+
+use async_stream::stream;
+use rspc::{
+    internal::jsonrpc::{RequestId, RequestInner, ResponseInner},
+    testing::TestConnection,
+    ErrorCode, Router,
+};
+use tokio::sync::Notify;
+
+fn router() -> Arc<Router<Arc<Notify>>> {
+    <Router<Arc<Notify>>>::new()
+        .query("pending", |t| {
+            t(|_, _: ()| async {
+                futures::future::pending::<()>().await;
+                "unreachable"
+            })
+        })
+        .query("version", |t| t(|_, _: ()| "0.1.0"))
+        .query("notify", |t| {
+            t(|ctx, _: ()| {
+                ctx.notify_one();
+                "ok"
+            })
+        })
+        .subscription("notified", |t| {
+            t(|ctx, _: ()| {
+                stream! {
+                    loop {
+                        ctx.notified().await;
+                        yield "notified";
+                    }
+                }
+            })
+        })
+        .build()
+        .arced()
+}
+
+fn connection() -> TestConnection<Arc<Notify>> {
+    let notify = Arc::new(Notify::new());
+    TestConnection::new(router(), move || notify.clone())
+}
+
+#[tokio::test]
+async fn cancel_in_flight_query() {
+    let mut conn = connection();
+
+    // The cancel is sent straight after the query so it has to be handled before the query has started running
+    let id = conn
+        .send(RequestInner::Query {
+            path: "pending".into(),
+            input: None,
+        })
+        .await;
+    conn.send_with_id(id.clone(), RequestInner::Cancel).await;
+
+    conn.next(&id)
+        .await
+        .assert_error(ErrorCode::ClientClosedRequest);
+}
+
+#[tokio::test]
+async fn stop_subscription() {
+    let mut conn = connection();
+
+    let id = conn.subscribe("notified", ()).await;
+    conn.query("notify", ()).await.assert_ok::<String>();
+    assert_eq!(conn.next(&id).await.assert_event::<String>(), "notified");
+
+    conn.unsubscribe(&id).await;
+    conn.query("notify", ()).await.assert_ok::<String>();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    conn.assert_no_messages(&id);
+}
+
+#[tokio::test]
+async fn stop_subscription_before_it_starts() {
+    let mut conn = connection();
+
+    let id = conn.subscribe("notified", ()).await;
+    conn.unsubscribe(&id).await;
+    conn.query("notify", ()).await.assert_ok::<String>();
+
+    while let Ok(resp) = tokio::time::timeout(Duration::from_millis(20), conn.next(&id)).await {
+        assert!(
+            !matches!(resp.result(), ResponseInner::Event(_)),
+            "the stopped subscription sent an event"
+        );
+    }
+}
+
+#[tokio::test]
+async fn duplicate_ids_are_rejected() {
+    let mut conn = connection();
+
+    let id = conn.subscribe("notified", ()).await;
+    conn.send_with_id(
+        id.clone(),
+        RequestInner::Query {
+            path: "version".into(),
+            input: None,
+        },
+    )
+    .await;
+    conn.next(&id).await.assert_error(ErrorCode::BadRequest);
+
+    conn.send_with_id(
+        id.clone(),
+        RequestInner::Subscription {
+            path: "notified".into(),
+            input: None,
+        },
+    )
+    .await;
+    conn.next(&id).await.assert_error(ErrorCode::BadRequest);
+
+    // The running subscription wasn't affected by the rejected requests
+    conn.query("notify", ()).await.assert_ok::<String>();
+    assert_eq!(conn.next(&id).await.assert_event::<String>(), "notified");
+}
+
+#[tokio::test]
+async fn null_id_subscriptions_are_rejected() {
+    let mut conn = connection();
+
+    conn.send_with_id(
+        RequestId::Null,
+        RequestInner::Subscription {
+            path: "notified".into(),
+            input: None,
+        },
+    )
+    .await;
+    conn.next(&RequestId::Null)
+        .await
+        .assert_error(ErrorCode::BadRequest);
+}
+
+#[tokio::test]
+async fn ids_can_be_reused_once_finished() {
+    let mut conn = connection();
+
+    conn.send_with_id(
+        RequestId::Number(100),
+        RequestInner::Query {
+            path: "version".into(),
+            input: None,
+        },
+    )
+    .await;
+    conn.next(&RequestId::Number(100))
+        .await
+        .assert_ok::<String>();
+
+    conn.send_with_id(
+        RequestId::Number(100),
+        RequestInner::Query {
+            path: "version".into(),
+            input: None,
+        },
+    )
+    .await;
+    conn.next(&RequestId::Number(100))
+        .await
+        .assert_ok::<String>();
+}
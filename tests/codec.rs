@@ -0,0 +1,184 @@
+mod common;
+
+use futures::{SinkExt, StreamExt};
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use rspc::{
+    internal::jsonrpc::{RequestId, RequestInner, Response, ResponseInner},
+    CborCodec, Codec, Config, ExecError, JsonCodec, MsgpackCodec, Router,
+};
+use serde_json::{json, Value};
+use tokio_tungstenite::tungstenite::Message;
+
+/// checks a codec can decode single and batched requests and encodes responses with the same shape as JSON.
+fn round_trip(codec: &dyn Codec, encode: fn(&Value) -> Vec<u8>, decode: fn(&[u8]) -> Value) {
+    let batch = json!([{ "id": 1, "method": "query", "params": { "path": "samples", "input": [1.5, 2, 3] } }]);
+
+    let requests = codec.decode_requests(&encode(&batch)).unwrap().0;
+    assert_eq!(requests.len(), 1);
+    assert!(matches!(
+        &requests[0].inner,
+        RequestInner::Query { path, input: Some(input) } if path == "samples" && *input == json!([1.5, 2, 3])
+    ));
+    assert_eq!(
+        codec.decode_requests(&encode(&batch[0])).unwrap().0.len(),
+        1
+    );
+    assert_eq!(
+        codec.decode_input(&encode(&json!([1.5, 2, 3]))).unwrap(),
+        json!([1.5, 2, 3])
+    );
+
+    let response = Response {
+        jsonrpc: "2.0",
+        id: RequestId::Null,
+        result: ResponseInner::Error(ExecError::Timeout.into()),
+    };
+    let expected = serde_json::to_value(&response).unwrap();
+    assert_eq!(decode(&codec.encode_response(&response).unwrap()), expected);
+    assert_eq!(
+        decode(&codec.encode_responses(&[response.clone()]).unwrap()),
+        json!([expected])
+    );
+}
+
+fn msgpack_encode(value: &Value) -> Vec<u8> {
+    rmp_serde::to_vec(value).unwrap()
+}
+
+fn msgpack_decode(bytes: &[u8]) -> Value {
+    rmp_serde::from_slice(bytes).unwrap()
+}
+
+fn cbor_encode(value: &Value) -> Vec<u8> {
+    let mut buf = Vec::new();
+    ciborium::ser::into_writer(value, &mut buf).unwrap();
+    buf
+}
+
+fn cbor_decode(bytes: &[u8]) -> Value {
+    ciborium::de::from_reader(bytes).unwrap()
+}
+
+#[test]
+fn json() {
+    round_trip(
+        &JsonCodec,
+        |v| serde_json::to_vec(v).unwrap(),
+        |v| serde_json::from_slice(v).unwrap(),
+    );
+}
+
+#[test]
+fn msgpack() {
+    round_trip(&MsgpackCodec, msgpack_encode, msgpack_decode);
+}
+
+#[test]
+fn cbor() {
+    round_trip(&CborCodec, cbor_encode, cbor_decode);
+}
+
+fn router() -> Router {
+    <Router>::new()
+        .config(Config::new().codec(MsgpackCodec).codec(CborCodec))
+        .mutation("add", |t| t(|_, (a, b): (i32, i32)| a + b))
+        .build()
+}
+
+#[tokio::test]
+async fn http_negotiation() {
+    let addr = common::serve(router().arced());
+    let client = reqwest::Client::new();
+    let url = format!("http://{}/rspc/add", addr);
+
+    // The response uses the codec of the request
+    let resp = client
+        .post(&url)
+        .header(CONTENT_TYPE, "application/msgpack")
+        .body(msgpack_encode(&json!([1, 2])))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()[CONTENT_TYPE], "application/msgpack");
+    assert_eq!(
+        msgpack_decode(&resp.bytes().await.unwrap())["result"],
+        json!({ "type": "response", "data": 3 })
+    );
+
+    // unless the `Accept` header asks for another one
+    let resp = client
+        .post(&url)
+        .header(CONTENT_TYPE, "application/msgpack")
+        .header(ACCEPT, "application/cbor, application/json")
+        .body(msgpack_encode(&json!([2, 2])))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()[CONTENT_TYPE], "application/cbor");
+    assert_eq!(
+        cbor_decode(&resp.bytes().await.unwrap())["result"]["data"],
+        json!(4)
+    );
+
+    // Unknown codecs fallback to JSON
+    let resp = client
+        .post(&url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(ACCEPT, "application/xml")
+        .body(serde_json::to_vec(&json!([3, 2])).unwrap())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()[CONTENT_TYPE], "application/json");
+    assert_eq!(
+        serde_json::from_slice::<Value>(&resp.bytes().await.unwrap()).unwrap()["result"]["data"],
+        json!(5)
+    );
+}
+
+#[tokio::test]
+async fn websocket_negotiation() {
+    let addr = common::serve(router().arced());
+    let request =
+        json!({ "id": 1, "method": "mutation", "params": { "path": "add", "input": [1, 2] } });
+
+    // The first supported subprotocol is chosen and binary messages use its codec
+    let (mut socket, subprotocol) = common::connect(addr, Some("rspc.xml, rspc.msgpack")).await;
+    assert_eq!(subprotocol.as_deref(), Some("rspc.msgpack"));
+    socket
+        .send(Message::Binary(msgpack_encode(&request)))
+        .await
+        .unwrap();
+    match socket.next().await.unwrap().unwrap() {
+        Message::Binary(bytes) => assert_eq!(
+            msgpack_decode(&bytes)["result"],
+            json!({ "type": "response", "data": 3 })
+        ),
+        msg => panic!("expected a binary message, got {:?}", msg),
+    }
+
+    // Text messages are always JSON
+    socket
+        .send(Message::Text(request.to_string()))
+        .await
+        .unwrap();
+    match socket.next().await.unwrap().unwrap() {
+        Message::Binary(bytes) => assert_eq!(msgpack_decode(&bytes)["result"]["data"], json!(3)),
+        msg => panic!("expected a binary message, got {:?}", msg),
+    }
+
+    // Without a subprotocol the connection uses JSON
+    let (mut socket, subprotocol) = common::connect(addr, None).await;
+    assert_eq!(subprotocol, None);
+    socket
+        .send(Message::Text(request.to_string()))
+        .await
+        .unwrap();
+    match socket.next().await.unwrap().unwrap() {
+        Message::Text(text) => assert_eq!(
+            serde_json::from_str::<Value>(&text).unwrap()["result"]["data"],
+            json!(3)
+        ),
+        msg => panic!("expected a text message, got {:?}", msg),
+    }
+}
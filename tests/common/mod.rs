@@ -0,0 +1,49 @@
+// Each test file only uses some of these helpers
+#![allow(dead_code)]
+
+use std::{
+    net::{SocketAddr, TcpListener},
+    sync::Arc,
+};
+
+use rspc::Router;
+use tokio_tungstenite::{
+    tungstenite::{client::IntoClientRequest, http::HeaderValue},
+    MaybeTlsStream, WebSocketStream,
+};
+
+pub type Websocket = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// serves the router's endpoint at `/rspc` on a random port and returns the address it's listening on.
+pub fn serve<TMeta: Send + Sync + 'static>(router: Arc<Router<(), TMeta>>) -> SocketAddr {
+    let app = axum::Router::new().route("/rspc/:id", router.endpoint(|| ()).axum());
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    addr
+}
+
+/// opens a websocket to the router served at `addr`, asking for `subprotocol` if there is one.
+/// Returns the socket and the subprotocol the server chose.
+pub async fn connect(addr: SocketAddr, subprotocol: Option<&str>) -> (Websocket, Option<String>) {
+    let mut req = format!("ws://{}/rspc/ws", addr)
+        .into_client_request()
+        .unwrap();
+    if let Some(subprotocol) = subprotocol {
+        req.headers_mut().insert(
+            "Sec-WebSocket-Protocol",
+            HeaderValue::from_str(subprotocol).unwrap(),
+        );
+    }
+
+    let (socket, resp) = tokio_tungstenite::connect_async(req).await.unwrap();
+    let subprotocol = resp
+        .headers()
+        .get("Sec-WebSocket-Protocol")
+        .map(|v| v.to_str().unwrap().to_string());
+    (socket, subprotocol)
+}
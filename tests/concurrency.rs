@@ -0,0 +1,106 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use rspc::{internal::jsonrpc::RequestInner, testing::TestConnection, Config, Router};
+use tokio::sync::Notify;
+
+#[derive(Default)]
+struct Ctx {
+    released: Notify,
+    running: AtomicUsize,
+    max_running: AtomicUsize,
+}
+
+fn router(config: Config) -> Arc<Router<Arc<Ctx>>> {
+    <Router<Arc<Ctx>>>::new()
+        .config(config)
+        .query("wait", |t| {
+            t(|ctx, _: ()| async move {
+                ctx.released.notified().await;
+                "released"
+            })
+        })
+        .query("release", |t| {
+            t(|ctx, _: ()| {
+                ctx.released.notify_one();
+                "ok"
+            })
+        })
+        .query("slow", |t| {
+            t(|ctx, _: ()| async move {
+                let running = ctx.running.fetch_add(1, Ordering::SeqCst) + 1;
+                ctx.max_running.fetch_max(running, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                ctx.running.fetch_sub(1, Ordering::SeqCst);
+                "done"
+            })
+        })
+        .build()
+        .arced()
+}
+
+#[tokio::test]
+async fn requests_run_concurrently() {
+    let ctx = Arc::new(Ctx::default());
+    let mut conn = TestConnection::new(router(Config::new()), {
+        let ctx = ctx.clone();
+        move || ctx.clone()
+    });
+
+    // The first request can only finish once the second one has run
+    let wait = conn
+        .send(RequestInner::Query {
+            path: "wait".into(),
+            input: None,
+        })
+        .await;
+    assert_eq!(conn.query("release", ()).await.assert_ok::<String>(), "ok");
+    assert_eq!(conn.next(&wait).await.assert_ok::<String>(), "released");
+}
+
+#[tokio::test]
+async fn concurrency_limit() {
+    let ctx = Arc::new(Ctx::default());
+    let mut conn = TestConnection::new(router(Config::new().concurrency_limit(2)), {
+        let ctx = ctx.clone();
+        move || ctx.clone()
+    });
+
+    let mut ids = Vec::new();
+    for _ in 0..6 {
+        ids.push(
+            conn.send(RequestInner::Query {
+                path: "slow".into(),
+                input: None,
+            })
+            .await,
+        );
+    }
+    for id in ids {
+        assert_eq!(conn.next(&id).await.assert_ok::<String>(), "done");
+    }
+
+    assert_eq!(ctx.max_running.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+#[should_panic(expected = "must be greater than zero")]
+fn zero_concurrency_limit() {
+    Config::new().concurrency_limit(0);
+}
+
+#[tokio::test]
+async fn concurrency_limit_larger_than_a_semaphore() {
+    let ctx = Arc::new(Ctx::default());
+    let mut conn = TestConnection::new(router(Config::new().concurrency_limit(usize::MAX)), {
+        let ctx = ctx.clone();
+        move || ctx.clone()
+    });
+
+    assert_eq!(conn.query("slow", ()).await.assert_ok::<String>(), "done");
+}
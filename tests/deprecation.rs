@@ -0,0 +1,89 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use rspc::{Deprecation, Router, Type};
+use serde::Serialize;
+
+#[derive(Serialize, Type)]
+struct Gone {
+    id: i32,
+}
+
+fn router() -> Router {
+    <Router>::new()
+        .query("old", |t| {
+            t.deprecated(
+                Deprecation::new("Old & slow.")
+                    .replaced_by("new")
+                    .sunset(UNIX_EPOCH + Duration::from_secs(1735689600)),
+            )
+            .since("1.0")
+            .error::<Gone>()(|_, _: ()| 1)
+        })
+        .query("new", |t| {
+            t(|_, _: ()| 1)
+                .since("2.0")
+                .description("gets the new\nthing")
+        })
+        .query("plain", |t| t(|_, _: ()| 1).deprecated("gone"))
+        .build()
+}
+
+#[test]
+fn deprecation_jsdoc() {
+    let path = std::env::temp_dir().join("rspc-deprecation.ts");
+    router().export_ts(&path).unwrap();
+    let bindings = std::fs::read_to_string(path).unwrap();
+
+    assert!(bindings.contains(
+        r#"
+        /**
+         * @deprecated Old & slow. Use `new` instead. This procedure may be removed after Wed, 01 Jan 2025 00:00:00 GMT.
+         * @since 1.0
+         */
+        { key: "old", input: never, result: number, error: Gone }"#
+    ));
+    assert!(bindings.contains(
+        r#"
+        /**
+         * gets the new
+         * thing
+         * @since 2.0
+         */
+        { key: "new", input: never, result: number }"#
+    ));
+    assert!(bindings.contains(
+        r#"
+        /**
+         * @deprecated gone
+         */
+        { key: "plain", input: never, result: number }"#
+    ));
+}
+
+#[test]
+fn deprecation_schema() {
+    let schema = serde_json::to_value(router().schema()).unwrap();
+    let procedure = |key: &str| {
+        schema["procedures"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|p| p["key"] == key)
+            .unwrap()
+            .clone()
+    };
+
+    let old = procedure("old");
+    assert_eq!(old["deprecation"]["message"], "Old & slow.");
+    assert_eq!(old["deprecation"]["replaced_by"], "new");
+    assert_eq!(
+        old["deprecation"]["sunset"],
+        "Wed, 01 Jan 2025 00:00:00 GMT"
+    );
+    assert_eq!(old["since"], "1.0");
+    assert!(old["error"].is_object());
+
+    let new = procedure("new");
+    assert!(new["deprecation"].is_null());
+    assert_eq!(new["description"], "gets the new\nthing");
+}
@@ -0,0 +1,55 @@
+use rspc::{
+    testing::TestConnection, DynamicResolver, DynamicStreamResolver, Error, ErrorCode, Router,
+};
+use serde_json::{json, Value};
+
+fn router() -> Router {
+    let mut router = <Router>::new();
+    for name in ["plugins.a", "plugins.b"] {
+        let resolver: DynamicResolver<()> = Box::new(move |_, input: Value| {
+            Box::pin(async move { Ok(json!({ "name": name, "input": input })) })
+        });
+        router = router.dynamic_query(name.to_string(), resolver);
+    }
+
+    let events: DynamicStreamResolver<()> = Box::new(|_, input| {
+        Box::pin(futures::stream::iter(vec![
+            Ok(input),
+            Err(Error::new(ErrorCode::BadRequest, "bad event".into())),
+        ]))
+    });
+
+    router
+        .dynamic_subscription(String::from("plugins.events"), events)
+        .merge(
+            String::from("merged."),
+            <Router>::new().query("version", |t| t(|_, _: ()| "1.0.0")),
+        )
+        .build()
+}
+
+#[tokio::test]
+async fn dynamic_procedures() {
+    let mut conn = TestConnection::new(router().arced(), || ());
+    assert_eq!(
+        conn.query("plugins.b", 5).await.assert_ok::<Value>(),
+        json!({ "name": "plugins.b", "input": 5 })
+    );
+    assert_eq!(
+        conn.query("merged.version", ()).await.assert_ok::<String>(),
+        "1.0.0"
+    );
+
+    let id = conn.subscribe("plugins.events", 3).await;
+    assert_eq!(conn.next(&id).await.assert_event::<i32>(), 3);
+    conn.next(&id).await.assert_error(ErrorCode::BadRequest);
+}
+
+#[test]
+#[should_panic(expected = "already has resolver")]
+fn duplicate_dynamic_key() {
+    let key = String::from("version");
+    <Router>::new()
+        .query(key.clone(), |t| t(|_, _: ()| 1))
+        .query(key, |t| t(|_, _: ()| 1));
+}
@@ -0,0 +1,60 @@
+use rspc::{testing::TestConnection, Error, ErrorCode, Router, Type};
+use serde::{Serialize, Serializer};
+
+#[derive(Serialize, Type)]
+struct ConflictingEntity {
+    id: i32,
+}
+
+#[derive(Type)]
+struct Unserializable;
+
+impl Serialize for Unserializable {
+    fn serialize<S: Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("can't be serialized"))
+    }
+}
+
+fn router() -> Router {
+    <Router>::new()
+        .mutation("create", |t| {
+            t.error::<ConflictingEntity>()(|_, id: i32| {
+                Err(Error::new(ErrorCode::Conflict, "already exists".into())
+                    .with_data(ConflictingEntity { id })) as Result<(), _>
+            })
+        })
+        .mutation("broken", |t| {
+            t(|_, _: i32| {
+                Err(Error::new(ErrorCode::Conflict, "already exists".into())
+                    .with_data(Unserializable)) as Result<(), _>
+            })
+        })
+        .build()
+}
+
+#[tokio::test]
+async fn error_data() {
+    let mut conn = TestConnection::new(router().arced(), || ());
+
+    let err = conn
+        .mutation("create", 5)
+        .await
+        .assert_error(ErrorCode::Conflict);
+    assert_eq!(err.data, Some(serde_json::json!({ "id": 5 })));
+
+    let err = conn
+        .mutation("broken", 5)
+        .await
+        .assert_error(ErrorCode::InternalServerError);
+    assert_eq!(err.data, None);
+}
+
+#[test]
+fn error_type_is_exported() {
+    let path = std::env::temp_dir().join("rspc-error-data.ts");
+    router().export_ts(&path).unwrap();
+    let bindings = std::fs::read_to_string(path).unwrap();
+
+    assert!(bindings.contains("error: ConflictingEntity"));
+    assert!(bindings.contains("export interface ConflictingEntity { id: number }"));
+}
@@ -0,0 +1,48 @@
+use rspc::{ExportError, Router, Type};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Type)]
+pub struct User {
+    id: i32,
+    name: String,
+}
+
+fn export(router: Router, name: &str) -> Result<String, ExportError> {
+    let path = std::env::temp_dir().join(name);
+    router.export_rust(&path)?;
+    Ok(std::fs::read_to_string(path).unwrap())
+}
+
+#[test]
+fn export_rust() {
+    let router = <Router>::new()
+        .query("user.get", |t| {
+            t(|_, id: i32| User {
+                id,
+                name: "Monty".into(),
+            })
+        })
+        .mutation("version", |t| t(|_, _: ()| "1.0.0"))
+        .build();
+    let bindings = export(router, "rspc-export-rust.rs").unwrap();
+
+    assert!(bindings.contains("pub struct User {"));
+    assert!(bindings.contains(
+        "pub async fn user_get(&self, input: i32) -> Result<User, rspc_client::Error> {"
+    ));
+    assert!(bindings.contains(r#"self.0.query("user.get", input).await"#));
+    assert!(bindings.contains(r#"self.0.mutation("version", ()).await"#));
+}
+
+#[test]
+fn colliding_method_names() {
+    let router = <Router>::new()
+        .query("user.get", |t| t(|_, _: ()| ()))
+        .query("userGet", |t| t(|_, _: ()| ()))
+        .build();
+
+    assert!(matches!(
+        export(router, "rspc-export-rust-collision.rs"),
+        Err(ExportError::RustIdentCollision(_, _, ident)) if ident == "user_get"
+    ));
+}
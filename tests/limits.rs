@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+use rspc::{
+    internal::jsonrpc::ResponseInner,
+    plugins::limits::{ConcurrencyLimiter, RateLimit, RateLimiter},
+    testing::TestConnection,
+    ErrorCode, Router,
+};
+
+fn router() -> Router<String> {
+    let rate_limiter = RateLimiter::new()
+        .procedure("login", RateLimit::per_minute(2))
+        .key(|user: &String| user.clone());
+    let concurrency_limiter = ConcurrencyLimiter::new().procedure("export", 1);
+
+    Router::<String>::new()
+        .middleware(move |_| rate_limiter.clone())
+        .middleware(move |_| concurrency_limiter.clone())
+        .mutation("login", |t| t(|_, _: ()| ()))
+        .query("export", |t| {
+            t(|_, _: ()| async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            })
+        })
+        .build()
+}
+
+#[tokio::test]
+async fn rate_limit() {
+    let router = router().arced();
+    let mut user1 = TestConnection::new(router.clone(), || "user1".to_string());
+    let mut user2 = TestConnection::new(router, || "user2".to_string());
+
+    user1.mutation("login", ()).await.assert_ok::<()>();
+    user1.mutation("login", ()).await.assert_ok::<()>();
+    user1
+        .mutation("login", ())
+        .await
+        .assert_error(ErrorCode::TooManyRequests);
+
+    // Each key has its own limit
+    user2.mutation("login", ()).await.assert_ok::<()>();
+}
+
+#[tokio::test]
+async fn concurrency_limit() {
+    let router = router().arced();
+    let mut conn1 = TestConnection::new(router.clone(), || "user1".to_string());
+    let mut conn2 = TestConnection::new(router, || "user1".to_string());
+
+    let (a, b) = tokio::join!(conn1.query("export", ()), conn2.query("export", ()));
+    let limited = [a, b]
+        .iter()
+        .filter(|resp| matches!(resp.result(), ResponseInner::Error(err) if err.code == 429))
+        .count();
+    assert_eq!(limited, 1);
+
+    conn1.query("export", ()).await.assert_ok::<()>();
+}
+
+#[test]
+#[should_panic(expected = "must be greater than zero")]
+fn zero_requests() {
+    RateLimit::per_second(0);
+}
+
+#[test]
+#[should_panic(expected = "must be greater than zero")]
+fn zero_period() {
+    RateLimit::new(1, Duration::ZERO);
+}
+
+#[test]
+#[should_panic(expected = "must be greater than zero")]
+fn zero_concurrency() {
+    ConcurrencyLimiter::<()>::new().procedure("export", 0);
+}
+
+#[tokio::test]
+async fn queued_concurrency_limit() {
+    let concurrency_limiter = ConcurrencyLimiter::new()
+        .procedure("export", 1)
+        .queue(Duration::from_secs(1));
+    let router = Router::<()>::new()
+        .middleware(move |_| concurrency_limiter.clone())
+        .query("export", |t| {
+            t(|_, _: ()| async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            })
+        })
+        .build()
+        .arced();
+    let mut conn1 = TestConnection::new(router.clone(), || ());
+    let mut conn2 = TestConnection::new(router, || ());
+
+    // The second call waits for the first to finish instead of being rejected
+    let (a, b) = tokio::join!(conn1.query("export", ()), conn2.query("export", ()));
+    a.assert_ok::<()>();
+    b.assert_ok::<()>();
+}
@@ -0,0 +1,26 @@
+use rspc::{testing::TestConnection, LiveRouter, Router};
+
+fn router(version: &'static str) -> Router {
+    <Router>::new()
+        .query("version", move |t| t(move |_, _: ()| version))
+        .build()
+}
+
+#[tokio::test]
+async fn swap() {
+    let live = LiveRouter::new(router("1.0.0"));
+    let old = live.swap(router("2.0.0"));
+
+    let mut conn = TestConnection::new(old, || ());
+    assert_eq!(
+        conn.query("version", ()).await.assert_ok::<String>(),
+        "1.0.0"
+    );
+
+    // Clones share the same router
+    let mut conn = TestConnection::new(live.clone().get(), || ());
+    assert_eq!(
+        conn.query("version", ()).await.assert_ok::<String>(),
+        "2.0.0"
+    );
+}
@@ -0,0 +1,50 @@
+use rspc::{testing::TestConnection, Error, ErrorCode, Router};
+
+#[derive(Debug)]
+struct Meta {
+    admin_only: bool,
+}
+
+fn router() -> Router<(), Meta> {
+    let admin = Router::<(), Meta>::new().query("stats", |t| {
+        t.meta(Meta { admin_only: true })
+            .resolver(|_, _: ()| "secret")
+    });
+
+    Router::<(), Meta>::new()
+        .middleware(|mw| {
+            mw.middleware(|mw| async move {
+                match mw.req.meta() {
+                    Some(meta) if meta.admin_only => {
+                        Err(Error::new(ErrorCode::Forbidden, "Admins only!".into()))
+                    }
+                    _ => Ok(mw),
+                }
+            })
+        })
+        .query("version", |t| t(|_, _: ()| "1.0.0"))
+        .query("adminStats", |t| {
+            t(|_, _: ()| "secret").meta(Meta { admin_only: true })
+        })
+        .query("public", |t| {
+            t.meta(Meta { admin_only: false })
+                .resolver(|_, _: ()| "public")
+        })
+        .merge("admin.", admin)
+        .build()
+}
+
+#[tokio::test]
+async fn middleware_reads_metadata() {
+    let mut conn = TestConnection::new(router().arced(), || ());
+
+    conn.query("version", ()).await.assert_ok::<String>();
+    conn.query("public", ()).await.assert_ok::<String>();
+    conn.query("adminStats", ())
+        .await
+        .assert_error(ErrorCode::Forbidden);
+    // Metadata is kept when a router is merged
+    conn.query("admin.stats", ())
+        .await
+        .assert_error(ErrorCode::Forbidden);
+}
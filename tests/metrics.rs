@@ -0,0 +1,37 @@
+use rspc::{testing::TestConnection, Config, Error, ErrorCode, Metrics, Router};
+
+#[tokio::test]
+async fn metrics() {
+    let metrics = Metrics::new();
+    let router = <Router>::new()
+        .config(Config::new().metrics(metrics.clone()))
+        .query("version", |t| t(|_, _: ()| "1.0.0"))
+        .query("conflict", |t| {
+            t(|_, _: ()| Err(Error::new(ErrorCode::Conflict, "conflict".into())) as Result<(), _>)
+        })
+        .build()
+        .arced();
+    let mut conn = TestConnection::new(router, || ());
+
+    conn.query("version", ()).await.assert_ok::<String>();
+    conn.query("version", ()).await.assert_ok::<String>();
+    conn.query("conflict", ())
+        .await
+        .assert_error(ErrorCode::Conflict);
+    conn.query("missing", ())
+        .await
+        .assert_error(ErrorCode::NotFound);
+
+    let output = metrics.encode();
+    assert!(output.contains(r#"rspc_procedure_calls_total{kind="query",path="version"} 2"#));
+    assert!(output.contains(
+        r#"rspc_procedure_errors_total{kind="query",path="conflict",code="Conflict"} 1"#
+    ));
+    assert!(
+        output.contains(r#"rspc_procedure_duration_seconds_count{kind="query",path="version"} 2"#)
+    );
+    assert!(output.contains("rspc_subscriptions_active 0"));
+
+    // Unknown procedures aren't recorded so clients can't create unbounded labels
+    assert!(!output.contains("missing"));
+}
@@ -0,0 +1,172 @@
+mod common;
+
+use futures::{SinkExt, StreamExt};
+use reqwest::StatusCode;
+use rspc::{
+    internal::jsonrpc::JsonRPCError, Codec, CodecError, Config, ErrorCode, ExecError, JsonCodec,
+    Router,
+};
+use serde_json::{json, Value};
+use tokio_tungstenite::tungstenite::Message;
+
+#[test]
+fn payload_too_large() {
+    let err: JsonRPCError = ExecError::PayloadTooLarge("maxBodySize", 1024).into();
+    assert_eq!(err.code, ErrorCode::PayloadTooLarge.to_status_code() as i32);
+    assert_eq!(
+        err.data,
+        Some(json!({ "limit": "maxBodySize", "max": 1024 }))
+    );
+}
+
+#[test]
+fn batches_stop_decoding_at_the_limit() {
+    let json: &dyn Codec = &JsonCodec;
+    let request =
+        json!({ "id": 1, "method": "query", "params": { "path": "echo", "input": null } });
+
+    let batch = serde_json::to_vec(&json!([request, request])).unwrap();
+    assert_eq!(json.decode_batch(&batch, Some(2)).unwrap().len(), 2);
+
+    // The element over the limit isn't a request so this would be a decode error if it was decoded
+    let batch = serde_json::to_vec(&json!([request, request, "not a request"])).unwrap();
+    assert!(matches!(
+        json.decode_batch(&batch, Some(2)),
+        Err(CodecError::TooManyRequests(2))
+    ));
+    assert!(matches!(
+        json.decode_batch(&batch, None),
+        Err(CodecError::Decode(_))
+    ));
+}
+
+fn router() -> Router {
+    <Router>::new()
+        .config(
+            Config::new()
+                .max_body_size(64)
+                .max_input_length(16)
+                .max_message_size(256)
+                .max_batch_length(2),
+        )
+        .query("echo", |t| t(|_, v: Value| v))
+        .mutation("echo", |t| t(|_, v: Value| v))
+        .build()
+}
+
+/// asserts the response is a `PayloadTooLarge` error for the limit named `limit`.
+async fn assert_too_large(resp: reqwest::Response, limit: &str) {
+    assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body: Value = serde_json::from_slice(&resp.bytes().await.unwrap()).unwrap();
+    assert_eq!(body["result"]["data"]["code"], json!(413));
+    assert_eq!(body["result"]["data"]["data"]["limit"], json!(limit));
+}
+
+#[tokio::test]
+async fn http_body_size() {
+    let addr = common::serve(router().arced());
+    let client = reqwest::Client::new();
+    let url = format!("http://{}/rspc/echo", addr);
+
+    let resp = client.post(&url).body("\"short\"").send().await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let body = serde_json::to_string(&"a".repeat(100)).unwrap();
+    let resp = client.post(&url).body(body).send().await.unwrap();
+    assert_too_large(resp, "maxBodySize").await;
+}
+
+#[tokio::test]
+async fn http_input_length() {
+    let addr = common::serve(router().arced());
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .get(format!("http://{}/rspc/echo?input=%22short%22", addr))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let input = format!("%22{}%22", "a".repeat(20));
+    let resp = client
+        .get(format!("http://{}/rspc/echo?input={}", addr, input))
+        .send()
+        .await
+        .unwrap();
+    assert_too_large(resp, "maxInputLength").await;
+
+    // Percent-encoding the parameter's name doesn't avoid the limit
+    let resp = client
+        .get(format!("http://{}/rspc/echo?%69nput={}", addr, input))
+        .send()
+        .await
+        .unwrap();
+    assert_too_large(resp, "maxInputLength").await;
+}
+
+#[tokio::test]
+async fn http_batch_length() {
+    let addr = common::serve(router().arced());
+    let client = reqwest::Client::new();
+    let url = format!("http://{}/rspc/_batch", addr);
+    let request = json!({ "id": 1, "method": "query", "params": { "path": "echo", "input": 1 } });
+
+    let resp = client
+        .post(&url)
+        .body(json!([request, request]).to_string())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = client
+        .post(&url)
+        .body(json!([request, request, request]).to_string())
+        .send()
+        .await
+        .unwrap();
+    assert_too_large(resp, "maxBatchLength").await;
+}
+
+/// sends `msg` on a new websocket and returns the first response.
+async fn send_ws(addr: std::net::SocketAddr, msg: String) -> Value {
+    let (mut socket, _) = common::connect(addr, None).await;
+    socket.send(Message::Text(msg)).await.unwrap();
+    match socket.next().await.unwrap().unwrap() {
+        Message::Text(text) => serde_json::from_str(&text).unwrap(),
+        msg => panic!("expected a text message, got {:?}", msg),
+    }
+}
+
+#[tokio::test]
+async fn websocket_message_size() {
+    let addr = common::serve(router().arced());
+
+    let request =
+        json!({ "id": 1, "method": "query", "params": { "path": "echo", "input": "short" } });
+    let resp = send_ws(addr, request.to_string()).await;
+    assert_eq!(resp["result"]["data"], json!("short"));
+
+    let request = json!({ "id": 1, "method": "query", "params": { "path": "echo", "input": "a".repeat(300) } });
+    let resp = send_ws(addr, request.to_string()).await;
+    assert_eq!(resp["result"]["type"], json!("error"));
+    assert_eq!(
+        resp["result"]["data"]["data"]["limit"],
+        json!("maxMessageSize")
+    );
+}
+
+#[tokio::test]
+async fn websocket_batch_length() {
+    let addr = common::serve(router().arced());
+    let request = json!({ "id": 1, "method": "query", "params": { "path": "echo", "input": 1 } });
+
+    let resp = send_ws(addr, json!([request, request, request]).to_string()).await;
+    assert_eq!(resp["id"], Value::Null);
+    assert_eq!(resp["result"]["type"], json!("error"));
+    assert_eq!(
+        resp["result"]["data"]["data"]["limit"],
+        json!("maxBatchLength")
+    );
+}
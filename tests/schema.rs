@@ -0,0 +1,42 @@
+use rspc::{testing::TestConnection, Config, Router};
+use serde_json::Value;
+
+fn router(config: Config) -> Router {
+    <Router>::new()
+        .config(config)
+        .query("version", |t| t(|_, _: ()| "1.0.0"))
+        .query("double", |t| t(|_, v: i32| v * 2))
+        .mutation("reset", |t| t(|_, _: ()| ()))
+        .build()
+}
+
+fn paths(schema: &Value) -> Vec<(&str, &str)> {
+    schema["procedures"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| (p["kind"].as_str().unwrap(), p["key"].as_str().unwrap()))
+        .collect()
+}
+
+#[tokio::test]
+async fn introspection() {
+    let router = router(Config::new().enable_introspection());
+    let schema = serde_json::to_value(router.schema()).unwrap();
+    assert!(paths(&schema).contains(&("query", "double")));
+    assert!(paths(&schema).contains(&("mutation", "reset")));
+
+    // The introspection procedure returns the schema from before it was added
+    let mut conn = TestConnection::new(router.arced(), || ());
+    let introspected = conn.query("rspc.introspect", ()).await.assert_ok::<Value>();
+    assert_eq!(paths(&introspected).len(), 3);
+    assert!(!paths(&introspected).contains(&("query", "rspc.introspect")));
+}
+
+#[tokio::test]
+async fn introspection_is_disabled_by_default() {
+    let mut conn = TestConnection::new(router(Config::new()).arced(), || ());
+    conn.query("rspc.introspect", ())
+        .await
+        .assert_error(rspc::ErrorCode::NotFound);
+}
@@ -0,0 +1,137 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use rspc::{
+    internal::jsonrpc::RequestInner, plugins::single_flight::SingleFlight, testing::TestConnection,
+    ExecKind, Router,
+};
+use serde_json::{json, Value};
+use tokio::sync::Barrier;
+
+fn router(shared: Arc<AtomicUsize>, excluded: Arc<AtomicUsize>) -> Arc<Router<String>> {
+    let single_flight = SingleFlight::new()
+        .exclude("excluded")
+        .key(|user: &String| user.clone());
+    Router::<String>::new()
+        .middleware(move |_| single_flight.clone())
+        .query("shared", move |t| {
+            t(move |_, input: Value| {
+                // Counted before the future is returned so a call which is started but never polled still counts
+                shared.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    input
+                }
+            })
+        })
+        .query("excluded", move |t| {
+            t(move |_, _: ()| {
+                let excluded = excluded.clone();
+                async move {
+                    excluded.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+            })
+        })
+        .build()
+        .arced()
+}
+
+#[tokio::test]
+async fn single_flight() {
+    let shared = Arc::new(AtomicUsize::new(0));
+    let excluded = Arc::new(AtomicUsize::new(0));
+    let router = router(shared.clone(), excluded.clone());
+    let conn = |user: &'static str| TestConnection::new(router.clone(), move || user.to_string());
+    let (mut a, mut b, mut c, mut d) = (conn("user1"), conn("user1"), conn("user2"), conn("user1"));
+
+    // Identical calls from the same user share an execution
+    let (a, b, _, _) = tokio::join!(
+        a.query("shared", json!({ "x": 1, "y": 2 })),
+        b.query("shared", json!({ "y": 2, "x": 1 })),
+        c.query("shared", json!({ "x": 1, "y": 2 })),
+        d.query("shared", json!({ "x": 2 })),
+    );
+    assert_eq!(a.assert_ok::<Value>(), b.assert_ok::<Value>());
+    assert_eq!(shared.load(Ordering::SeqCst), 3);
+
+    let (mut a, mut b) = (conn("user1"), conn("user1"));
+    tokio::join!(a.query("excluded", ()), b.query("excluded", ()));
+    assert_eq!(excluded.load(Ordering::SeqCst), 2);
+
+    // A call after the execution has finished isn't shared
+    a.query("shared", json!({ "x": 1, "y": 2 })).await;
+    assert_eq!(shared.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn identical_calls_which_arrive_together() {
+    let shared = Arc::new(AtomicUsize::new(0));
+    let router = router(shared.clone(), Default::default());
+
+    // Every call is created and waits on the barrier before any of them is polled by the router
+    let barrier = Arc::new(Barrier::new(8));
+    let calls = (0..8)
+        .map(|_| {
+            let (router, barrier) = (router.clone(), barrier.clone());
+            let call = async move {
+                router
+                    .exec(
+                        "user1".into(),
+                        ExecKind::Query,
+                        "shared".into(),
+                        Some(json!(1)),
+                    )
+                    .await
+            };
+            tokio::spawn(async move {
+                barrier.wait().await;
+                call.await
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for call in calls {
+        assert_eq!(call.await.unwrap().unwrap(), json!(1));
+    }
+    assert_eq!(shared.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn calls_continue_when_the_first_caller_is_dropped() {
+    let shared = Arc::new(AtomicUsize::new(0));
+    let router = router(shared.clone(), Default::default());
+    let (mut a, mut b) = (
+        TestConnection::new(router.clone(), || "user1".to_string()),
+        TestConnection::new(router.clone(), || "user1".to_string()),
+    );
+
+    let query = || RequestInner::Query {
+        path: "shared".into(),
+        input: Some(json!(1)),
+    };
+    let first = a.send(query()).await;
+    let second = b.send(query()).await;
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    // Cancelling the call which started the execution drops it but the other call still gets the result
+    a.send_with_id(first, RequestInner::Cancel).await;
+    assert_eq!(b.next(&second).await.assert_ok::<Value>(), json!(1));
+    assert_eq!(shared.load(Ordering::SeqCst), 1);
+
+    // Once every call has been dropped the flight is removed so a new call starts its own execution
+    let first = a.send(query()).await;
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    a.send_with_id(first, RequestInner::Cancel).await;
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_eq!(
+        b.query("shared", json!(1)).await.assert_ok::<Value>(),
+        json!(1)
+    );
+    assert_eq!(shared.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,78 @@
+use std::{sync::Arc, time::Duration};
+
+use async_stream::stream;
+use rspc::{
+    internal::jsonrpc::{RequestId, RequestInner},
+    testing::TestConnection,
+    ErrorCode, Router,
+};
+
+fn router() -> Arc<Router> {
+    <Router>::new()
+        .subscription("count", |t| {
+            t(|_, n: i32| {
+                stream! {
+                    for i in 0..n {
+                        yield i;
+                    }
+                }
+            })
+        })
+        .subscription("idle", |t| {
+            t(|_, _: ()| futures::stream::pending::<i32>()).timeout(Duration::from_millis(10))
+        })
+        .build()
+        .arced()
+}
+
+#[tokio::test]
+async fn subscription_completes() {
+    let mut conn = TestConnection::new(router(), || ());
+
+    let id = conn.subscribe("count", 2).await;
+    assert_eq!(conn.next(&id).await.assert_event::<i32>(), 0);
+    assert_eq!(conn.next(&id).await.assert_event::<i32>(), 1);
+    conn.next(&id).await.assert_complete();
+
+    // A completed subscription is removed from the connection so its id can be used again
+    conn.send_with_id(
+        id.clone(),
+        RequestInner::Subscription {
+            path: "count".into(),
+            input: Some(1.into()),
+        },
+    )
+    .await;
+    assert_eq!(conn.next(&id).await.assert_event::<i32>(), 0);
+    conn.next(&id).await.assert_complete();
+}
+
+#[tokio::test]
+async fn subscription_errors_then_completes() {
+    let mut conn = TestConnection::new(router(), || ());
+
+    let id = conn.subscribe("idle", ()).await;
+    conn.next(&id).await.assert_error(ErrorCode::Timeout);
+    conn.next(&id).await.assert_complete();
+}
+
+#[tokio::test]
+async fn subscription_which_fails_to_start_completes() {
+    let mut conn = TestConnection::new(router(), || ());
+
+    let id = conn.subscribe("missing", ()).await;
+    conn.next(&id).await.assert_error(ErrorCode::NotFound);
+    conn.next(&id).await.assert_complete();
+
+    let id = RequestId::String("invalid-input".into());
+    conn.send_with_id(
+        id.clone(),
+        RequestInner::Subscription {
+            path: "count".into(),
+            input: Some("not a number".into()),
+        },
+    )
+    .await;
+    conn.next(&id).await.assert_error(ErrorCode::BadRequest);
+    conn.next(&id).await.assert_complete();
+}
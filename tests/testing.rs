@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use async_stream::stream;
+use rspc::{
+    internal::jsonrpc::{RequestInner, ResponseInner},
+    testing::TestConnection,
+    Error, ErrorCode, Router,
+};
+
+fn router() -> Router<i32> {
+    <Router<i32>>::new()
+        .query("ctx", |t| t(|ctx, _: ()| ctx))
+        .mutation("conflict", |t| {
+            t(|_, _: ()| {
+                Err(Error::new(ErrorCode::Conflict, "already exists".into())) as Result<(), _>
+            })
+        })
+        .subscription("count", |t| {
+            t(|_, n: i32| {
+                stream! {
+                    for i in 0..n {
+                        yield i;
+                    }
+                }
+            })
+        })
+        .build()
+}
+
+#[tokio::test]
+async fn queries_and_mutations() {
+    let mut conn = TestConnection::new(router().arced(), || 42);
+
+    assert_eq!(conn.query("ctx", ()).await.assert_ok::<i32>(), 42);
+    let err = conn
+        .mutation("conflict", ())
+        .await
+        .assert_error(ErrorCode::Conflict);
+    assert_eq!(err.message, "already exists");
+    conn.query("missing", ())
+        .await
+        .assert_error(ErrorCode::NotFound);
+}
+
+#[tokio::test]
+async fn subscriptions_are_received_in_order() {
+    let mut conn = TestConnection::new(router().arced(), || 42);
+
+    let first = conn.subscribe("count", 2).await;
+    let second = conn.subscribe("count", 1).await;
+
+    // Messages for other requests are kept until they are asked for
+    assert_eq!(conn.next(&second).await.assert_event::<i32>(), 0);
+    conn.next(&second).await.assert_complete();
+    assert_eq!(conn.next(&first).await.assert_event::<i32>(), 0);
+    assert_eq!(conn.next(&first).await.assert_event::<i32>(), 1);
+    conn.next(&first).await.assert_complete();
+    conn.assert_no_messages(&first);
+}
+
+#[tokio::test]
+async fn batch() {
+    let mut conn = TestConnection::new(router().arced(), || 42);
+
+    let mut responses = conn
+        .batch([
+            RequestInner::Query {
+                path: "ctx".into(),
+                input: None,
+            },
+            RequestInner::Mutation {
+                path: "conflict".into(),
+                input: None,
+            },
+            RequestInner::Subscription {
+                path: "count".into(),
+                input: Some(1.into()),
+            },
+        ])
+        .await
+        .into_iter();
+
+    assert_eq!(responses.next().unwrap().assert_ok::<i32>(), 42);
+    responses.next().unwrap().assert_error(ErrorCode::Conflict);
+    // Subscriptions can't be batched
+    responses
+        .next()
+        .unwrap()
+        .assert_error(ErrorCode::BadRequest);
+}
+
+#[tokio::test]
+#[should_panic(expected = "timed out")]
+async fn next_times_out() {
+    let mut conn = TestConnection::new(router().arced(), || 42).timeout(Duration::from_millis(10));
+
+    let id = conn.subscribe("count", 0).await;
+    conn.next(&id).await.assert_complete();
+    conn.next(&id).await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "expected a response")]
+async fn assertions_fail_on_the_wrong_message() {
+    let mut conn = TestConnection::new(router().arced(), || 42);
+
+    let resp = conn.query("missing", ()).await;
+    assert!(matches!(resp.result(), ResponseInner::Error(_)));
+    resp.assert_ok::<i32>();
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use async_stream::stream;
+use rspc::{testing::TestConnection, Config, ErrorCode, Router};
+
+async fn sleep(ms: u64) -> &'static str {
+    tokio::time::sleep(Duration::from_millis(ms)).await;
+    "done"
+}
+
+#[tokio::test]
+async fn timeouts() {
+    let router = <Router>::new()
+        .config(Config::new().default_timeout(Duration::from_millis(20)))
+        .query("fast", |t| t(|_, _: ()| sleep(0)))
+        .query("slow", |t| t(|_, _: ()| sleep(200)))
+        .mutation("override", |t| {
+            t(|_, _: ()| sleep(50)).timeout(Duration::from_millis(500))
+        })
+        .build()
+        .arced();
+    let mut conn = TestConnection::new(router, || ());
+
+    conn.query("fast", ()).await.assert_ok::<String>();
+    conn.query("slow", ())
+        .await
+        .assert_error(ErrorCode::Timeout);
+    // The procedure's own timeout takes precedence over the router's default
+    conn.mutation("override", ()).await.assert_ok::<String>();
+}
+
+#[tokio::test]
+async fn subscription_idle_timeout() {
+    let router = <Router>::new()
+        .config(Config::new().subscription_idle_timeout(Duration::from_millis(50)))
+        .subscription("stalls", |t| {
+            t(|_, _: ()| {
+                stream! {
+                    yield 0;
+                    sleep(10).await;
+                    yield 1;
+                    sleep(500).await;
+                    yield 2;
+                }
+            })
+        })
+        .subscription("slowStart", |t| {
+            t(|_, _: ()| {
+                stream! {
+                    sleep(500).await;
+                    yield 0;
+                }
+            })
+        })
+        .build()
+        .arced();
+    let mut conn = TestConnection::new(router, || ());
+
+    // Items which arrive within the timeout of the previous one keep the subscription alive until it goes idle
+    let id = conn.subscribe("stalls", ()).await;
+    assert_eq!(conn.next(&id).await.assert_event::<i32>(), 0);
+    assert_eq!(conn.next(&id).await.assert_event::<i32>(), 1);
+    conn.next(&id).await.assert_error(ErrorCode::Timeout);
+    conn.next(&id).await.assert_complete();
+
+    // The timeout is also the deadline for the first item
+    let id = conn.subscribe("slowStart", ()).await;
+    conn.next(&id).await.assert_error(ErrorCode::Timeout);
+    conn.next(&id).await.assert_complete();
+}
@@ -0,0 +1,149 @@
+#![cfg(feature = "tracing")]
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+
+use rspc::{internal::TraceParent, testing::TestConnection, Error, ErrorCode, Router};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+/// records the name, fields and parent of every span.
+#[derive(Clone, Default)]
+struct Recorder {
+    spans: Arc<Mutex<Vec<String>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+struct Fields<'a>(&'a mut String);
+
+impl Visit for Fields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.push_str(&format!(" {}={:?}", field.name(), value));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut span = format!("{id} {}", attrs.metadata().name());
+        if let Some(parent) = attrs.parent() {
+            span.push_str(&format!(" parent={}", parent.into_u64()));
+        }
+        attrs.record(&mut Fields(&mut span));
+        self.spans.lock().unwrap().push(span);
+        span::Id::from_u64(id)
+    }
+
+    fn record(&self, id: &span::Id, values: &span::Record<'_>) {
+        let mut spans = self.spans.lock().unwrap();
+        let span = &mut spans[id.into_u64() as usize - 1];
+        values.record(&mut Fields(span));
+    }
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+    fn event(&self, _: &Event<'_>) {}
+    fn enter(&self, _: &span::Id) {}
+    fn exit(&self, _: &span::Id) {}
+}
+
+#[test]
+fn trace_parent() {
+    let header = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+    let parent = TraceParent::parse(header).unwrap();
+    assert!(parent.sampled);
+    assert_eq!(parent.to_string(), header);
+
+    // An all zero trace id is invalid
+    assert!(
+        TraceParent::parse("00-00000000000000000000000000000000-00f067aa0ba902b7-01").is_none()
+    );
+}
+
+#[tokio::test]
+async fn procedure_spans() {
+    let recorder = Recorder::default();
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+
+    let router = <Router>::new()
+        .query("conflict", |t| {
+            t(|_, _: ()| Err(Error::new(ErrorCode::Conflict, "conflict".into())) as Result<(), _>)
+        })
+        .subscription("pings", |t| {
+            t(|_, _: ()| {
+                async_stream::stream! {
+                    yield 1;
+                    yield 2;
+                }
+            })
+        })
+        .build()
+        .arced();
+    let mut conn = TestConnection::new(router, || ());
+
+    conn.query("conflict", ())
+        .await
+        .assert_error(ErrorCode::Conflict);
+    let id = conn.subscribe("pings", ()).await;
+    conn.next(&id).await.assert_event::<i32>();
+    conn.next(&id).await.assert_event::<i32>();
+    conn.next(&id).await.assert_complete();
+
+    let spans = recorder.spans.lock().unwrap().clone();
+    let query = spans
+        .iter()
+        .find(|span| span.contains(r#"rspc.path="conflict""#))
+        .unwrap();
+    assert!(query.contains(r#"rspc.procedure rspc.kind="query""#));
+    assert!(query.contains("rspc.error_code=409"));
+
+    let subscription = spans
+        .iter()
+        .find(|span| span.contains(r#"rspc.path="pings""#))
+        .unwrap();
+    let subscription_id = subscription.split(' ').next().unwrap();
+    let events = spans
+        .iter()
+        .filter(|span| span.contains(&format!("rspc.subscription.event parent={subscription_id}")))
+        .count();
+    assert_eq!(events, 2);
+}
+
+#[cfg(feature = "opentelemetry")]
+#[test]
+fn request_span_continues_the_remote_trace() {
+    use opentelemetry::trace::{SpanId, TraceContextExt, TraceId, TracerProvider as _};
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let tracer = opentelemetry::sdk::trace::TracerProvider::builder()
+        .build()
+        .tracer("rspc");
+    let subscriber =
+        tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let parent =
+        TraceParent::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").unwrap();
+    let cx = TraceParent::span(Some(&parent), "http").context();
+    let span = cx.span();
+    let span_context = span.span_context();
+
+    // The request span is a new span in the caller's trace
+    assert_eq!(
+        span_context.trace_id(),
+        TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap()
+    );
+    assert_ne!(
+        span_context.span_id(),
+        SpanId::from_hex("00f067aa0ba902b7").unwrap()
+    );
+    assert!(span_context.is_sampled());
+}
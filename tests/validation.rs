@@ -0,0 +1,75 @@
+use rspc::{testing::TestConnection, ErrorCode, Router, Type};
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Deserialize, Type)]
+#[allow(dead_code)]
+struct Tag {
+    #[specta(min_length = 2)]
+    name: String,
+}
+
+#[derive(Deserialize, Type)]
+#[allow(dead_code)]
+struct Signup {
+    #[specta(min_length = 3, max_length = 8, pattern = "^[a-z]+$")]
+    username: String,
+    #[specta(min = 0, max = 150.5)]
+    age: f64,
+    #[specta(max_items = 2)]
+    tags: Vec<Tag>,
+    nickname: Option<String>,
+}
+
+#[tokio::test]
+async fn validation() {
+    let router = <Router>::new()
+        .mutation("signup", |t| t(|_, signup: Signup| signup.username))
+        .build()
+        .arced();
+    let mut conn = TestConnection::new(router, || ());
+
+    let username = conn
+        .mutation(
+            "signup",
+            json!({ "username": "monty", "age": 3, "tags": [], "nickname": null }),
+        )
+        .await
+        .assert_ok::<String>();
+    assert_eq!(username, "monty");
+
+    let err = conn
+        .mutation(
+            "signup",
+            json!({
+                "username": "AB",
+                "age": -1,
+                "tags": [{ "name": "a" }, { "name": "bb" }, { "name": "cc" }],
+                "nickname": null
+            }),
+        )
+        .await
+        .assert_error(ErrorCode::BadRequest);
+    let errors = err.data.unwrap()["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|err| {
+            (
+                err["path"].as_str().unwrap().to_string(),
+                err["constraint"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let expected: Vec<(String, String)> = [
+        ("username", "minLength"),
+        ("username", "pattern"),
+        ("age", "minimum"),
+        ("tags", "maxItems"),
+        ("tags[0].name", "minLength"),
+    ]
+    .iter()
+    .map(|(path, constraint)| (path.to_string(), constraint.to_string()))
+    .collect();
+    assert_eq!(errors, expected);
+}